@@ -0,0 +1,84 @@
+use crate::{
+    element::Element,
+    error::Error,
+    parse_context::ParseContext,
+    prelude::{Annotation, Occurs},
+    source_span::SourceSpan,
+};
+
+/// A model group whose elements may appear in any order, each at most once.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct All {
+    pub occurs: Vec<Occurs>,
+
+    pub annotations: Vec<Annotation>,
+    pub elements: Vec<Element>,
+
+    pub span: Option<SourceSpan>,
+}
+
+impl All {
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut xmltree::Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
+
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(
+        element: &mut xmltree::Element,
+        ctx: &mut ParseContext,
+    ) -> Result<Self, Error> {
+        let mut r = All {
+            span: ctx.span(element),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope("all", None, &r.span);
+        r.occurs = Occurs::read_with_context(element, ctx);
+
+        while let Some(mut annotation) = element.take_child("annotation") {
+            r.annotations
+                .push(Annotation::read_with_context(&mut annotation, ctx)?);
+        }
+
+        while let Some(mut element) = element.take_child("element") {
+            r.elements
+                .push(Element::read_with_context(&mut element, ctx)?);
+        }
+
+        ctx.leave(element);
+        Ok(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::Schema;
+
+    #[test]
+    fn all() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:complexType name="point">
+        <xs:all minOccurs="0">
+            <xs:element name="x" type="xs:decimal"/>
+            <xs:element name="y" type="xs:decimal" minOccurs="0"/>
+        </xs:all>
+    </xs:complexType>
+    <xs:group name="size">
+        <xs:all>
+            <xs:element name="width" type="xs:decimal"/>
+        </xs:all>
+    </xs:group>
+</xs:schema>
+"#;
+
+        let item = Schema::parse(xml).unwrap();
+
+        let all = item.complex_types[0].all.as_ref().unwrap();
+        assert_eq!(all.occurs.len(), 1);
+        assert_eq!(all.elements.len(), 2);
+        assert_eq!(all.elements[1].name, "y".to_string());
+
+        assert_eq!(item.groups[0].all.as_ref().unwrap().elements.len(), 1);
+    }
+}
@@ -1,36 +1,62 @@
 use xmltree::Element;
 
-#[derive(Debug, Clone, Default)]
+use crate::{
+    diagnostic::Severity, error::Error, parse_context::ParseContext, source_span::SourceSpan,
+};
+
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum AnnotationContent {
     #[default]
     None,
     Documentation(String),
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Annotation {
     pub content: Vec<AnnotationContent>,
+
+    pub span: Option<SourceSpan>,
 }
 
 impl Annotation {
     pub fn new() -> Self {
         Annotation {
             content: Vec::new(),
+            span: None,
         }
     }
 
-    pub fn read(element: &mut Element) -> Self {
-        let mut r = Annotation::new();
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
+
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(element: &mut Element, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let mut r = Annotation {
+            span: ctx.span(element),
+            ..Annotation::new()
+        };
+        let ctx = &mut *ctx.scope("annotation", None, &r.span);
 
         while let Some(s) = element.take_child("documentation") {
             match s.get_text() {
                 Some(doc) => r
                     .content
                     .push(AnnotationContent::Documentation(doc.to_string())),
-                None => {}
-            };
+                None => {
+                    let span = ctx.span(&s);
+                    ctx.report(
+                        Severity::Warning,
+                        "empty-documentation",
+                        "documentation without text is ignored".to_string(),
+                        span,
+                    );
+                }
+            }
         }
 
-        r
+        ctx.leave(element);
+        Ok(r)
     }
 }
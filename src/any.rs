@@ -0,0 +1,272 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    error::Error,
+    parse_context::ParseContext,
+    prelude::{Annotation, Occurs},
+    source_span::SourceSpan,
+};
+
+/// An element wildcard, `<xs:any>`, or an attribute wildcard, `<xs:anyAttribute>`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Any {
+    /// `##any`, `##other` or a list of namespaces, `##targetNamespace` and `##local`. `None`
+    /// means `##any`.
+    pub namespace: Option<String>,
+
+    /// `strict`, `lax` or `skip`. `None` means `strict`.
+    pub process_contents: Option<String>,
+    pub occurs: Vec<Occurs>,
+
+    pub annotations: Vec<Annotation>,
+
+    pub span: Option<SourceSpan>,
+}
+
+impl Any {
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut xmltree::Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
+
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(
+        element: &mut xmltree::Element,
+        ctx: &mut ParseContext,
+    ) -> Result<Self, Error> {
+        let mut r = Any {
+            span: ctx.span(element),
+            namespace: element.attributes.remove("namespace"),
+            process_contents: element.attributes.remove("processContents"),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope(&element.name, r.namespace.as_deref(), &r.span);
+        r.occurs = Occurs::read_with_context(element, ctx);
+
+        while let Some(mut annotation) = element.take_child("annotation") {
+            r.annotations
+                .push(Annotation::read_with_context(&mut annotation, ctx)?);
+        }
+
+        ctx.leave(element);
+        Ok(r)
+    }
+
+    /// The namespaces the wildcard allows. `target_namespace` is the target namespace of the
+    /// schema document the wildcard is written in.
+    pub fn namespaces(&self, target_namespace: Option<&str>) -> NamespaceConstraint {
+        let constraint = self.namespace.as_deref().unwrap_or("##any").trim();
+
+        match constraint {
+            "##any" => NamespaceConstraint::Any,
+            "##other" => NamespaceConstraint::Not(target_namespace.map(|n| n.to_string())),
+            _ => NamespaceConstraint::Set(
+                constraint
+                    .split_whitespace()
+                    .map(|n| match n {
+                        "##targetNamespace" => target_namespace.map(|n| n.to_string()),
+                        "##local" => None,
+                        n => Some(n.to_string()),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Whether an element in `namespace` matches the wildcard. `target_namespace` is the target
+    /// namespace of the schema document the wildcard is written in. `None` stands for no
+    /// namespace.
+    pub fn allows(&self, target_namespace: Option<&str>, namespace: Option<&str>) -> bool {
+        self.namespaces(target_namespace).allows(namespace)
+    }
+}
+
+/// How strictly the elements or attributes matched by a wildcard are validated, from the
+/// `processContents` attribute. Orders from the weakest to the strongest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProcessContents {
+    Skip,
+    Lax,
+    Strict,
+}
+
+impl ProcessContents {
+    /// The value of the attribute, `None` means `strict`.
+    pub fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some("skip") => ProcessContents::Skip,
+            Some("lax") => ProcessContents::Lax,
+            _ => ProcessContents::Strict,
+        }
+    }
+}
+
+/// The namespaces a wildcard allows, with `##targetNamespace` and `##local` resolved. `None`
+/// stands for no namespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NamespaceConstraint {
+    Any,
+
+    /// Every namespace except the given one. Names without a namespace are not allowed either.
+    Not(Option<String>),
+    Set(BTreeSet<Option<String>>),
+}
+
+impl NamespaceConstraint {
+    pub fn allows(&self, namespace: Option<&str>) -> bool {
+        match self {
+            NamespaceConstraint::Any => true,
+            NamespaceConstraint::Not(excluded) => {
+                namespace.is_some() && namespace != excluded.as_deref()
+            }
+            NamespaceConstraint::Set(set) => set.contains(&namespace.map(|n| n.to_string())),
+        }
+    }
+
+    /// Whether every namespace allowed by `self` is allowed by `other` as well.
+    pub fn is_subset(&self, other: &NamespaceConstraint) -> bool {
+        match (self, other) {
+            (_, NamespaceConstraint::Any) => true,
+            (NamespaceConstraint::Set(set), other) => {
+                set.iter().all(|n| other.allows(n.as_deref()))
+            }
+            (NamespaceConstraint::Not(a), NamespaceConstraint::Not(b)) => a == b || b.is_none(),
+            _ => false,
+        }
+    }
+
+    /// The namespaces allowed by either constraint. `None` if the union cannot be expressed
+    /// as a constraint.
+    pub fn union(&self, other: &NamespaceConstraint) -> Option<NamespaceConstraint> {
+        use NamespaceConstraint::*;
+
+        match (self, other) {
+            (a, b) if a == b => Some(a.clone()),
+            (Any, _) | (_, Any) => Some(Any),
+            (Set(a), Set(b)) => Some(Set(a.union(b).cloned().collect())),
+            (Not(_), Not(_)) => Some(Not(None)),
+            (Not(excluded), Set(set)) | (Set(set), Not(excluded)) => {
+                let local = set.contains(&None);
+                match excluded {
+                    None if local => Some(Any),
+                    None => Some(Not(None)),
+                    Some(_) => match (local, set.contains(excluded)) {
+                        (true, true) => Some(Any),
+                        (false, false) => Some(Not(excluded.clone())),
+                        _ => None,
+                    },
+                }
+            }
+        }
+    }
+
+    /// The namespaces allowed by both constraints. `None` if the intersection cannot be
+    /// expressed as a constraint.
+    pub fn intersection(&self, other: &NamespaceConstraint) -> Option<NamespaceConstraint> {
+        use NamespaceConstraint::*;
+
+        match (self, other) {
+            (a, b) if a == b => Some(a.clone()),
+            (Any, c) | (c, Any) => Some(c.clone()),
+            (Set(a), Set(b)) => Some(Set(a.intersection(b).cloned().collect())),
+            (Not(_), Set(set)) | (Set(set), Not(_)) => Some(Set(set
+                .iter()
+                .filter(|n| other.allows(n.as_deref()) && self.allows(n.as_deref()))
+                .cloned()
+                .collect())),
+            (Not(None), Not(c)) | (Not(c), Not(None)) => Some(Not(c.clone())),
+            (Not(_), Not(_)) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Any, NamespaceConstraint};
+    use crate::{choice::ChoiceItems, prelude::Occurs, schema::Schema};
+
+    #[test]
+    fn any() {
+        let xml = r###"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:complexType name="extensible">
+        <xs:choice>
+            <xs:element name="value" type="xs:string"/>
+            <xs:any namespace="##other" processContents="lax" maxOccurs="unbounded"/>
+        </xs:choice>
+    </xs:complexType>
+</xs:schema>
+"###;
+
+        let item = Schema::parse(xml).unwrap();
+
+        match &item.complex_types[0].choices[0].elements[1] {
+            ChoiceItems::Any(any) => {
+                assert_eq!(any.namespace, Some("##other".to_string()));
+                assert_eq!(any.process_contents, Some("lax".to_string()));
+                assert_eq!(any.occurs, vec![Occurs::MaxOccurs("unbounded".to_string())]);
+            }
+            _ => panic!("Expected any"),
+        }
+    }
+
+    #[test]
+    fn allows() {
+        let wildcard = |namespace: &str| Any {
+            namespace: Some(namespace.to_string()),
+            ..Default::default()
+        };
+
+        assert!(Any::default().allows(Some("urn:t"), None));
+
+        let other = wildcard("##other");
+        assert!(other.allows(Some("urn:t"), Some("urn:u")));
+        assert!(!other.allows(Some("urn:t"), Some("urn:t")));
+        assert!(!other.allows(Some("urn:t"), None));
+        assert!(!other.allows(None, None));
+
+        let list = wildcard("##targetNamespace ##local urn:u");
+        assert!(list.allows(Some("urn:t"), Some("urn:t")));
+        assert!(list.allows(Some("urn:t"), None));
+        assert!(list.allows(Some("urn:t"), Some("urn:u")));
+        assert!(!list.allows(Some("urn:t"), Some("urn:v")));
+    }
+
+    #[test]
+    fn namespace_constraints() {
+        let other = |namespace: &str| NamespaceConstraint::Not(Some(namespace.to_string()));
+        let set = |namespaces: &[Option<&str>]| {
+            NamespaceConstraint::Set(namespaces.iter().map(|n| n.map(String::from)).collect())
+        };
+
+        let wildcard = Any {
+            namespace: Some("##targetNamespace ##local".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            wildcard.namespaces(Some("urn:t")),
+            set(&[Some("urn:t"), None])
+        );
+
+        assert!(set(&[Some("urn:u")]).is_subset(&other("urn:t")));
+        assert!(!set(&[None]).is_subset(&other("urn:t")));
+        assert!(other("urn:t").is_subset(&NamespaceConstraint::Not(None)));
+        assert!(!NamespaceConstraint::Any.is_subset(&other("urn:t")));
+
+        assert_eq!(
+            other("urn:t").union(&set(&[Some("urn:t"), None])),
+            Some(NamespaceConstraint::Any)
+        );
+        assert_eq!(other("urn:t").union(&set(&[None])), None);
+        assert_eq!(
+            other("urn:t").union(&other("urn:u")),
+            Some(NamespaceConstraint::Not(None))
+        );
+
+        assert_eq!(
+            other("urn:t").intersection(&set(&[Some("urn:t"), Some("urn:u"), None])),
+            Some(set(&[Some("urn:u")]))
+        );
+        assert_eq!(other("urn:t").intersection(&other("urn:u")), None);
+    }
+}
@@ -1,53 +1,97 @@
 use xmltree::Element;
 
+use crate::{
+    error::Error, parse_context::ParseContext, prelude::SimpleType, source_span::SourceSpan,
+};
+
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
 pub enum AttributeMeta {
     Reference(String),
     Use(String),
     Fixed(String),
     Default(String),
+
+    /// `qualified` or `unqualified`, overrides `attributeFormDefault` for a local attribute.
+    Form(String),
 }
 
-#[derive(Debug, PartialEq, PartialOrd, Clone, Default)]
+#[derive(Debug, PartialEq, Clone, Default)]
 pub struct Attribute {
     pub name: Option<String>,
     pub r#type: Option<String>,
+
+    /// The anonymous type of the attribute, if it is not given by `type`.
+    pub simple_type: Option<SimpleType>,
     pub additional_attributes: Vec<AttributeMeta>,
+
+    pub span: Option<SourceSpan>,
 }
 
 impl Attribute {
-    pub fn read(element: &mut Element) -> Self {
-        let mut r = Attribute::default();
-        if element.attributes.contains_key("name") {
-            r.name = Some(element.attributes["name"].clone());
+    /// The qualified name of the global attribute this one refers to, if any.
+    pub fn reference(&self) -> Option<&str> {
+        self.additional_attributes.iter().find_map(|a| match a {
+            AttributeMeta::Reference(reference) => Some(reference.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
+
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(element: &mut Element, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let mut r = Attribute {
+            span: ctx.span(element),
+            name: element.attributes.remove("name"),
+            r#type: element.attributes.remove("type"),
+            ..Default::default()
+        };
+
+        if let Some(reference) = element.attributes.remove("ref") {
+            r.additional_attributes
+                .push(AttributeMeta::Reference(reference));
         }
 
-        if element.attributes.contains_key("type") {
-            r.r#type = Some(element.attributes["type"].clone());
+        let ctx = &mut *ctx.scope("attribute", r.name.as_deref().or(r.reference()), &r.span);
+
+        if let Some(r#use) = element.attributes.remove("use") {
+            r.additional_attributes.push(AttributeMeta::Use(r#use));
         }
 
-        if element.attributes.contains_key("ref") {
-            r.additional_attributes
-                .push(AttributeMeta::Reference(element.attributes["ref"].clone()));
+        if let Some(form) = element.attributes.remove("form") {
+            r.additional_attributes.push(AttributeMeta::Form(form));
         }
 
-        if element.attributes.contains_key("use") {
-            r.additional_attributes
-                .push(AttributeMeta::Use(element.attributes["use"].clone()));
+        let fixed = element.attributes.remove("fixed");
+        let default = element.attributes.remove("default");
+        if fixed.is_some() && default.is_some() {
+            return Err(ctx.invalid("default and fixed must not both be present"));
+        }
+
+        if let Some(fixed) = fixed {
+            r.additional_attributes.push(AttributeMeta::Fixed(fixed));
         }
 
-        if element.attributes.contains_key("fixed") {
+        if let Some(default) = default {
             r.additional_attributes
-                .push(AttributeMeta::Fixed(element.attributes["fixed"].clone()));
+                .push(AttributeMeta::Default(default));
         }
 
-        if element.attributes.contains_key("default") {
-            r.additional_attributes.push(AttributeMeta::Default(
-                element.attributes["default"].clone(),
-            ));
+        if let Some(mut simple_type) = element.take_child("simpleType") {
+            if r.r#type.is_some() {
+                return Err(
+                    ctx.invalid("type and an anonymous simpleType must not both be present")
+                );
+            }
+
+            r.simple_type = Some(SimpleType::read_with_context(&mut simple_type, ctx)?);
         }
 
-        r
+        ctx.leave(element);
+        Ok(r)
     }
 }
 
@@ -76,7 +120,7 @@ mod tests {
 "#;
 
         let mut element = Element::parse(xml.as_bytes()).unwrap();
-        let item = Schema::read(&mut element);
+        let item = Schema::read(&mut element).unwrap();
 
         assert_eq!(item.attribute_groups.len(), 1);
         assert_eq!(item.attribute_groups[0].annotations.len(), 1);
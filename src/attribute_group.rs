@@ -1,42 +1,66 @@
 use xmltree::Element;
 
-use crate::prelude::{Annotation, Attribute};
+use crate::{
+    error::Error,
+    parse_context::ParseContext,
+    prelude::{Annotation, Any, Attribute},
+    source_span::SourceSpan,
+};
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct AttributeGroup {
     pub name: String,
     pub reference: Option<String>,
     pub annotations: Vec<Annotation>,
     pub attributes: Vec<Attribute>,
     pub attribute_groups: Vec<AttributeGroup>,
+    pub any_attribute: Option<Any>,
+
+    pub span: Option<SourceSpan>,
 }
 
 impl AttributeGroup {
-    pub fn read(element: &mut Element) -> Self {
-        let mut r = AttributeGroup::default();
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
 
-        if element.attributes.contains_key("name") {
-            r.name = element.attributes["name"].clone();
-        }
-
-        if element.attributes.contains_key("ref") {
-            r.reference = Some(element.attributes["ref"].clone());
-        }
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(element: &mut Element, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let mut r = AttributeGroup {
+            span: ctx.span(element),
+            name: element.attributes.remove("name").unwrap_or_default(),
+            reference: element.attributes.remove("ref"),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope(
+            "attributeGroup",
+            Some(r.reference.as_deref().unwrap_or(&r.name)),
+            &r.span,
+        );
 
         // Read annotations values.
         while let Some(mut annotation) = element.take_child("annotation") {
-            r.annotations.push(Annotation::read(&mut annotation));
+            r.annotations
+                .push(Annotation::read_with_context(&mut annotation, ctx)?);
         }
 
         while let Some(mut attribute) = element.take_child("attribute") {
-            r.attributes.push(Attribute::read(&mut attribute));
+            r.attributes
+                .push(Attribute::read_with_context(&mut attribute, ctx)?);
         }
 
         while let Some(mut grp) = element.take_child("attributeGroup") {
-            r.attribute_groups.push(AttributeGroup::read(&mut grp));
+            r.attribute_groups
+                .push(AttributeGroup::read_with_context(&mut grp, ctx)?);
+        }
+
+        if let Some(mut any_attribute) = element.take_child("anyAttribute") {
+            r.any_attribute = Some(Any::read_with_context(&mut any_attribute, ctx)?);
         }
 
-        r
+        ctx.leave(element);
+        Ok(r)
     }
 }
 
@@ -60,7 +84,7 @@ mod tests {
 "#;
 
         let mut element = Element::parse(xml.as_bytes()).unwrap();
-        let item = Schema::read(&mut element);
+        let item = Schema::read(&mut element).unwrap();
 
         assert_eq!(item.attribute_groups.len(), 1);
         assert_eq!(item.attribute_groups[0].annotations.len(), 1);
@@ -92,7 +116,7 @@ mod tests {
 "#;
 
         let mut element = Element::parse(xml.as_bytes()).unwrap();
-        let item = Schema::read(&mut element);
+        let item = Schema::read(&mut element).unwrap();
 
         assert_eq!(item.attribute_groups.len(), 1);
         assert_eq!(item.attribute_groups[0].annotations.len(), 1);
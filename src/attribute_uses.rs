@@ -0,0 +1,817 @@
+use std::collections::HashSet;
+
+use crate::{
+    attribute::AttributeMeta,
+    diagnostic::{Diagnostic, Severity},
+    prelude::{
+        Any, Attribute, AttributeGroup, ComplexType, DerivationMethod, NamespaceConstraint,
+        ProcessContents, QName,
+    },
+    schema_set::{ComponentId, SchemaSet},
+    source_span::SourceSpan,
+};
+
+/// An attribute that may or must appear on elements of a complex type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeUse<'a> {
+    pub name: QName,
+
+    /// The named type of the attribute. `None` if the type is anonymous, see
+    /// [`Attribute::simple_type`] of `declaration`, or not given at all, i.e. `xs:anySimpleType`.
+    pub r#type: Option<QName>,
+    pub required: bool,
+    pub default: Option<String>,
+    pub fixed: Option<String>,
+
+    /// The attribute declaration: the local declaration, or the global one for references.
+    pub declaration: &'a Attribute,
+
+    /// Index of the schema document `declaration` is written in.
+    pub schema: usize,
+
+    /// Where the use was written, i.e. the local declaration or the reference.
+    pub span: Option<SourceSpan>,
+}
+
+/// The attribute wildcard of a complex type, `<xs:anyAttribute>`, with the wildcards of its
+/// attribute groups and base types applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeWildcard {
+    pub namespaces: NamespaceConstraint,
+    pub process_contents: ProcessContents,
+
+    /// Where the first of the wildcards that make up this one was written.
+    pub span: Option<SourceSpan>,
+}
+
+/// The attributes that may appear on elements of a complex type.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AttributeUses<'a> {
+    pub uses: Vec<AttributeUse<'a>>,
+
+    /// Attributes without a use are allowed if the wildcard allows their namespace.
+    pub wildcard: Option<AttributeWildcard>,
+}
+
+/// An attribute use as written in a type, before derivation is applied.
+struct LocalUse<'a> {
+    attribute: AttributeUse<'a>,
+    prohibited: bool,
+}
+
+impl SchemaSet {
+    /// The effective attribute uses and wildcard of the global complex type `name`, together
+    /// with the problems found while computing them. `None` if there is no such type.
+    pub fn attribute_uses(&self, name: &QName) -> Option<(AttributeUses<'_>, Vec<Diagnostic>)> {
+        let id = self.lookup(name, |t| &t.complex_types)?;
+        Some(self.attribute_uses_of(id.schema, &self.schemas[id.schema].complex_types[id.index]))
+    }
+
+    /// The effective attribute uses and wildcard of a complex type defined in the schema
+    /// document with the index `schema`. This works for anonymous types as well.
+    ///
+    /// Attribute group references are expanded, the uses of the base type are inherited on
+    /// extension and overridden or removed with `use="prohibited"` on restriction. Prohibited
+    /// uses are not part of the result. The wildcard of a type is the intersection of the
+    /// wildcards written in it and its attribute groups, on extension united with the wildcard
+    /// of the base type.
+    pub fn attribute_uses_of<'a>(
+        &'a self,
+        schema: usize,
+        complex_type: &'a ComplexType,
+    ) -> (AttributeUses<'a>, Vec<Diagnostic>) {
+        let mut collector = AttributeCollector {
+            set: self,
+            path: format!("complexType[{}]", complex_type.name),
+            diagnostics: Vec::new(),
+        };
+        let id = self.complex_type_id(schema, complex_type);
+        let r = collector.effective(schema, complex_type, id, &mut HashSet::new());
+
+        (r, collector.diagnostics)
+    }
+
+    /// A use of the global attribute declaration `name`, for attributes allowed by a wildcard.
+    /// `None` if there is no such declaration.
+    pub(crate) fn global_attribute_use(&self, name: &QName) -> Option<AttributeUse<'_>> {
+        let id = self.lookup(name, |t| &t.attributes)?;
+        let declaration = &self.schemas[id.schema].attributes[id.index];
+
+        let mut default = None;
+        let mut fixed = None;
+        for meta in declaration.additional_attributes.iter() {
+            match meta {
+                AttributeMeta::Default(value) => default = Some(value.clone()),
+                AttributeMeta::Fixed(value) => fixed = Some(value.clone()),
+                _ => {}
+            }
+        }
+
+        Some(AttributeUse {
+            name: name.clone(),
+            r#type: declaration
+                .r#type
+                .as_ref()
+                .and_then(|t| self.schemas[id.schema].resolve_qname(t)),
+            required: false,
+            default,
+            fixed,
+            declaration,
+            schema: id.schema,
+            span: declaration.span.clone(),
+        })
+    }
+}
+
+struct AttributeCollector<'a> {
+    set: &'a SchemaSet,
+    path: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> AttributeCollector<'a> {
+    /// `id` identifies a global type, which is kept in `types` while its base types are
+    /// visited to detect circular derivations. Anonymous types cannot be the base of another
+    /// type.
+    fn effective(
+        &mut self,
+        schema: usize,
+        complex_type: &'a ComplexType,
+        id: Option<ComponentId>,
+        types: &mut HashSet<ComponentId>,
+    ) -> AttributeUses<'a> {
+        if id.is_some_and(|id| !types.insert(id)) {
+            self.report(
+                "ct-props-correct.3",
+                format!(
+                    "complex type '{}' is derived from itself",
+                    complex_type.name
+                ),
+                complex_type.span.clone(),
+            );
+            return AttributeUses::default();
+        }
+
+        let (local, wildcard) = self.local_uses(schema, complex_type);
+
+        let base = complex_type.derivation().and_then(|(method, base)| {
+            let base = self.set.schemas[schema].resolve_qname(base)?;
+            let id = self.set.lookup(&base, |t| &t.complex_types)?;
+            Some((method, id))
+        });
+
+        let r = match base {
+            None => AttributeUses {
+                uses: local
+                    .into_iter()
+                    .filter(|u| !u.prohibited)
+                    .map(|u| u.attribute)
+                    .collect(),
+                wildcard,
+            },
+            Some((method, id)) => {
+                let base_type = &self.set.schemas[id.schema].complex_types[id.index];
+                let base = self.effective(id.schema, base_type, Some(id), types);
+
+                match method {
+                    DerivationMethod::Extension => self.extend(base, local, wildcard),
+                    // Complex types are never derived by list or union.
+                    _ => self.restrict(base, local, wildcard),
+                }
+            }
+        };
+
+        if let Some(id) = id {
+            types.remove(&id);
+        }
+        r
+    }
+
+    fn extend(
+        &mut self,
+        mut base: AttributeUses<'a>,
+        local: Vec<LocalUse<'a>>,
+        wildcard: Option<AttributeWildcard>,
+    ) -> AttributeUses<'a> {
+        for u in local.into_iter().filter(|u| !u.prohibited) {
+            if base.uses.iter().any(|b| b.name == u.attribute.name) {
+                self.report(
+                    "ct-props-correct.4",
+                    format!(
+                        "attribute '{}' is already declared by the base type",
+                        u.attribute.name
+                    ),
+                    u.attribute.span,
+                );
+            } else {
+                base.uses.push(u.attribute);
+            }
+        }
+
+        base.wildcard = match (wildcard, base.wildcard) {
+            (Some(mut wildcard), Some(inherited)) => {
+                match wildcard.namespaces.union(&inherited.namespaces) {
+                    Some(namespaces) => wildcard.namespaces = namespaces,
+                    None => self.report(
+                        "cos-aw-union",
+                        "the union with the attribute wildcard of the base type cannot be \
+                         expressed"
+                            .to_string(),
+                        wildcard.span.clone(),
+                    ),
+                }
+                Some(wildcard)
+            }
+            (wildcard, inherited) => wildcard.or(inherited),
+        };
+
+        base
+    }
+
+    fn restrict(
+        &mut self,
+        mut base: AttributeUses<'a>,
+        local: Vec<LocalUse<'a>>,
+        wildcard: Option<AttributeWildcard>,
+    ) -> AttributeUses<'a> {
+        let base_uses = &mut base.uses;
+        for u in local {
+            let found = base_uses.iter().position(|b| b.name == u.attribute.name);
+
+            match (found, u.prohibited) {
+                (Some(index), true) => {
+                    if base_uses[index].required {
+                        self.report(
+                            "derivation-ok-restriction.3",
+                            format!(
+                                "required attribute '{}' of the base type must not be prohibited",
+                                u.attribute.name
+                            ),
+                            u.attribute.span,
+                        );
+                    } else {
+                        base_uses.remove(index);
+                    }
+                }
+                (Some(index), false) => {
+                    if base_uses[index].required && !u.attribute.required {
+                        self.report(
+                            "derivation-ok-restriction.3",
+                            format!(
+                                "attribute '{}' is required by the base type",
+                                u.attribute.name
+                            ),
+                            u.attribute.span.clone(),
+                        );
+                    }
+                    base_uses[index] = u.attribute;
+                }
+                (None, true) => {}
+                (None, false)
+                    if base.wildcard.as_ref().is_some_and(|w| {
+                        w.namespaces.allows(u.attribute.name.namespace.as_deref())
+                    }) =>
+                {
+                    base_uses.push(u.attribute);
+                }
+                (None, false) => {
+                    self.report(
+                        "derivation-ok-restriction.2.2",
+                        format!(
+                            "attribute '{}' is not declared by the base type",
+                            u.attribute.name
+                        ),
+                        u.attribute.span,
+                    );
+                }
+            }
+        }
+
+        if let Some(wildcard) = &wildcard {
+            match &base.wildcard {
+                None => self.report(
+                    "derivation-ok-restriction.4.1",
+                    "the base type has no attribute wildcard".to_string(),
+                    wildcard.span.clone(),
+                ),
+                Some(inherited) => {
+                    if !wildcard.namespaces.is_subset(&inherited.namespaces) {
+                        self.report(
+                            "derivation-ok-restriction.4.2",
+                            "the attribute wildcard allows namespaces the wildcard of the base \
+                             type does not allow"
+                                .to_string(),
+                            wildcard.span.clone(),
+                        );
+                    }
+
+                    if wildcard.process_contents < inherited.process_contents {
+                        self.report(
+                            "derivation-ok-restriction.4.3",
+                            "the attribute wildcard must not process attributes less strictly \
+                             than the wildcard of the base type"
+                                .to_string(),
+                            wildcard.span.clone(),
+                        );
+                    }
+                }
+            }
+        }
+        base.wildcard = wildcard;
+
+        base
+    }
+
+    /// The attribute uses written in the type itself, with attribute groups expanded, and the
+    /// intersection of the wildcards written in the type and its attribute groups.
+    fn local_uses(
+        &mut self,
+        schema: usize,
+        t: &'a ComplexType,
+    ) -> (Vec<LocalUse<'a>>, Option<AttributeWildcard>) {
+        let mut attributes: Vec<&'a Attribute> = t.attributes.iter().collect();
+        let mut groups: Vec<&'a AttributeGroup> = t.attribute_groups.iter().collect();
+        let mut wildcards: Vec<(usize, &'a Any)> =
+            t.any_attribute.iter().map(|w| (schema, w)).collect();
+
+        for c in t.simple_content.iter() {
+            attributes.extend(c.attributes.iter());
+            groups.extend(c.attribute_groups.iter());
+            wildcards.extend(c.any_attribute.iter().map(|w| (schema, w)));
+
+            for e in c.extensions.iter() {
+                attributes.extend(e.attributes.iter());
+                groups.extend(e.attribute_groups.iter());
+                wildcards.extend(e.any_attribute.iter().map(|w| (schema, w)));
+            }
+
+            for r in c.restrictions.iter() {
+                attributes.extend(r.attributes.iter());
+                groups.extend(r.attribute_groups.iter());
+                wildcards.extend(r.any_attribute.iter().map(|w| (schema, w)));
+            }
+        }
+
+        for c in t.complex_content.iter() {
+            for e in c.extensions.iter() {
+                attributes.extend(e.attributes.iter());
+                groups.extend(e.attribute_groups.iter());
+                wildcards.extend(e.any_attribute.iter().map(|w| (schema, w)));
+            }
+
+            for r in c.restrictions.iter() {
+                attributes.extend(r.attributes.iter());
+                groups.extend(r.attribute_groups.iter());
+                wildcards.extend(r.any_attribute.iter().map(|w| (schema, w)));
+            }
+        }
+
+        let mut r = Vec::new();
+        for a in attributes {
+            r.extend(self.attribute_use(schema, a));
+        }
+
+        for g in groups {
+            self.group_uses(schema, g, &mut HashSet::new(), &mut r, &mut wildcards);
+        }
+
+        // Only the first of several uses with the same name counts.
+        let mut names = HashSet::new();
+        let mut unique = Vec::new();
+        for u in r {
+            if names.insert(u.attribute.name.clone()) {
+                unique.push(u);
+            } else {
+                self.report(
+                    "ct-props-correct.4",
+                    format!(
+                        "attribute '{}' is declared more than once",
+                        u.attribute.name
+                    ),
+                    u.attribute.span,
+                );
+            }
+        }
+
+        (unique, self.intersection(wildcards))
+    }
+
+    /// The intersection of attribute wildcards, which processes attributes like the first one.
+    fn intersection(&mut self, wildcards: Vec<(usize, &'a Any)>) -> Option<AttributeWildcard> {
+        let mut r: Option<AttributeWildcard> = None;
+        for (schema, any) in wildcards {
+            let target_namespace = self.set.schemas[schema].target_namespace.as_deref();
+            let namespaces = any.namespaces(target_namespace);
+
+            match r.as_mut() {
+                None => {
+                    r = Some(AttributeWildcard {
+                        namespaces,
+                        process_contents: ProcessContents::parse(any.process_contents.as_deref()),
+                        span: any.span.clone(),
+                    })
+                }
+                Some(wildcard) => match wildcard.namespaces.intersection(&namespaces) {
+                    Some(namespaces) => wildcard.namespaces = namespaces,
+                    None => self.report(
+                        "cos-aw-intersect",
+                        "the intersection of the attribute wildcards cannot be expressed"
+                            .to_string(),
+                        any.span.clone(),
+                    ),
+                },
+            }
+        }
+
+        r
+    }
+
+    fn group_uses(
+        &mut self,
+        schema: usize,
+        group: &'a AttributeGroup,
+        references: &mut HashSet<ComponentId>,
+        r: &mut Vec<LocalUse<'a>>,
+        wildcards: &mut Vec<(usize, &'a Any)>,
+    ) {
+        let (schema, group, id) = match &group.reference {
+            Some(reference) => {
+                let found = self.set.schemas[schema]
+                    .resolve_qname(reference)
+                    .and_then(|name| self.set.lookup(&name, |t| &t.attribute_groups));
+
+                // Unresolved references are reported when the set is built.
+                let Some(id) = found else {
+                    return;
+                };
+
+                if !references.insert(id) {
+                    self.report(
+                        "src-attribute_group.3",
+                        format!("attribute group '{}' refers to itself", reference),
+                        group.span.clone(),
+                    );
+                    return;
+                }
+
+                (
+                    id.schema,
+                    &self.set.schemas[id.schema].attribute_groups[id.index],
+                    Some(id),
+                )
+            }
+            None => (schema, group, None),
+        };
+
+        for a in group.attributes.iter() {
+            r.extend(self.attribute_use(schema, a));
+        }
+        wildcards.extend(group.any_attribute.iter().map(|w| (schema, w)));
+
+        for g in group.attribute_groups.iter() {
+            self.group_uses(schema, g, references, r, wildcards);
+        }
+
+        if let Some(id) = id {
+            references.remove(&id);
+        }
+    }
+
+    fn attribute_use(&mut self, schema: usize, a: &'a Attribute) -> Option<LocalUse<'a>> {
+        let document = &self.set.schemas[schema];
+
+        let mut r#use = None;
+        let mut form = None;
+        let mut default = None;
+        let mut fixed = None;
+        for meta in a.additional_attributes.iter() {
+            match meta {
+                AttributeMeta::Reference(_) => {}
+                AttributeMeta::Use(value) => r#use = Some(value.as_str()),
+                AttributeMeta::Form(value) => form = Some(value.as_str()),
+                AttributeMeta::Default(value) => default = Some(value.clone()),
+                AttributeMeta::Fixed(value) => fixed = Some(value.clone()),
+            }
+        }
+
+        let (name, declaration, declaring_schema) = match a.reference() {
+            Some(reference) => {
+                let name = document.resolve_qname(reference)?;
+                let id = self.set.lookup(&name, |t| &t.attributes)?;
+                let global = &self.set.schemas[id.schema].attributes[id.index];
+
+                // Values of the use take precedence over those of the declaration.
+                for meta in global.additional_attributes.iter() {
+                    match meta {
+                        AttributeMeta::Default(value) if default.is_none() && fixed.is_none() => {
+                            default = Some(value.clone())
+                        }
+                        AttributeMeta::Fixed(value) if fixed.is_none() => {
+                            fixed = Some(value.clone())
+                        }
+                        _ => {}
+                    }
+                }
+
+                (name, global, id.schema)
+            }
+            None => {
+                let qualified = match form {
+                    Some(form) => form == "qualified",
+                    None => document.attribute_form_default.as_deref() == Some("qualified"),
+                };
+                let namespace = match qualified {
+                    true => document.target_namespace.as_deref(),
+                    false => None,
+                };
+
+                (
+                    QName::new(namespace, a.name.as_deref().unwrap_or_default()),
+                    a,
+                    schema,
+                )
+            }
+        };
+
+        let r#type = declaration
+            .r#type
+            .as_ref()
+            .and_then(|t| self.set.schemas[declaring_schema].resolve_qname(t));
+
+        Some(LocalUse {
+            attribute: AttributeUse {
+                name,
+                r#type,
+                required: r#use == Some("required"),
+                default,
+                fixed,
+                declaration,
+                schema: declaring_schema,
+                span: a.span.clone(),
+            },
+            prohibited: r#use == Some("prohibited"),
+        })
+    }
+
+    fn report(&mut self, code: &str, message: String, span: Option<SourceSpan>) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            code: code.to_string(),
+            message,
+            path: self.path.clone(),
+            span,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Schema;
+
+    const XML: &str = r###"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:xml="http://www.w3.org/XML/1998/namespace" targetNamespace="urn:t" xmlns="urn:t">
+    <xs:attribute name="global" type="xs:string" default="g"/>
+
+    <xs:attributeGroup name="font">
+        <xs:attribute name="font-family" type="xs:token"/>
+        <xs:attribute name="font-size" type="xs:decimal"/>
+    </xs:attributeGroup>
+    <xs:attributeGroup name="print-style">
+        <xs:attributeGroup ref="font"/>
+        <xs:attribute name="color" type="xs:token" default="black"/>
+    </xs:attributeGroup>
+    <xs:attributeGroup name="loop">
+        <xs:attributeGroup ref="loop"/>
+    </xs:attributeGroup>
+
+    <xs:complexType name="dynamics">
+        <xs:attributeGroup ref="print-style"/>
+        <xs:attribute name="id" type="xs:ID" use="required"/>
+        <xs:attribute ref="global"/>
+        <xs:attribute name="q" form="qualified"/>
+    </xs:complexType>
+
+    <xs:complexType name="extended">
+        <xs:complexContent>
+            <xs:extension base="dynamics">
+                <xs:attribute name="placement" type="xs:token"/>
+                <xs:attribute name="color"/>
+            </xs:extension>
+        </xs:complexContent>
+    </xs:complexType>
+
+    <xs:complexType name="restricted">
+        <xs:complexContent>
+            <xs:restriction base="extended">
+                <xs:attribute name="placement" use="prohibited"/>
+                <xs:attribute name="font-size" type="xs:positiveInteger" fixed="12"/>
+                <xs:attribute name="id" type="xs:ID"/>
+                <xs:attribute name="new"/>
+            </xs:restriction>
+        </xs:complexContent>
+    </xs:complexType>
+
+    <xs:complexType name="duplicates">
+        <xs:attributeGroup ref="font"/>
+        <xs:attributeGroup ref="print-style"/>
+        <xs:attributeGroup ref="loop"/>
+    </xs:complexType>
+
+    <xs:attributeGroup name="foreign">
+        <xs:anyAttribute namespace="##other urn:u ##local" processContents="lax"/>
+    </xs:attributeGroup>
+
+    <xs:complexType name="open">
+        <xs:attributeGroup ref="foreign"/>
+        <xs:anyAttribute namespace="##local urn:u" processContents="strict"/>
+    </xs:complexType>
+
+    <xs:complexType name="open-extended">
+        <xs:complexContent>
+            <xs:extension base="open">
+                <xs:anyAttribute namespace="##targetNamespace"/>
+            </xs:extension>
+        </xs:complexContent>
+    </xs:complexType>
+
+    <xs:complexType name="open-restricted">
+        <xs:complexContent>
+            <xs:restriction base="open">
+                <xs:attribute name="local"/>
+                <xs:attribute name="other" form="qualified"/>
+                <xs:anyAttribute namespace="##local urn:v" processContents="lax"/>
+            </xs:restriction>
+        </xs:complexContent>
+    </xs:complexType>
+
+    <xs:complexType name="text">
+        <xs:simpleContent>
+            <xs:extension base="xs:string">
+                <xs:attribute ref="xml:lang"/>
+            </xs:extension>
+        </xs:simpleContent>
+    </xs:complexType>
+</xs:schema>
+"###;
+
+    fn names(uses: &[AttributeUse]) -> Vec<String> {
+        uses.iter().map(|u| u.name.to_string()).collect()
+    }
+
+    fn set() -> SchemaSet {
+        SchemaSet::new(vec![Schema::parse(XML).unwrap()])
+    }
+
+    #[test]
+    fn groups() {
+        let set = set();
+        let (AttributeUses { uses, .. }, diagnostics) = set
+            .attribute_uses(&QName::new(Some("urn:t"), "dynamics"))
+            .unwrap();
+
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(
+            names(&uses),
+            vec![
+                "id",
+                "{urn:t}global",
+                "{urn:t}q",
+                "color",
+                "font-family",
+                "font-size"
+            ]
+        );
+
+        assert!(uses[0].required);
+        assert_eq!(
+            uses[0].r#type,
+            Some(QName::new(Some(crate::document::XSD_NAMESPACE), "ID"))
+        );
+        assert_eq!(uses[1].default, Some("g".to_string()));
+        assert_eq!(uses[1].declaration.name, Some("global".to_string()));
+        assert_eq!(uses[3].default, Some("black".to_string()));
+    }
+
+    #[test]
+    fn derivation() {
+        let set = set();
+
+        let (AttributeUses { uses, .. }, diagnostics) = set
+            .attribute_uses(&QName::new(Some("urn:t"), "extended"))
+            .unwrap();
+        assert_eq!(uses.len(), 7);
+        assert_eq!(names(&uses)[6], "placement".to_string());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "ct-props-correct.4".to_string());
+
+        let (AttributeUses { uses, .. }, diagnostics) = set
+            .attribute_uses(&QName::new(Some("urn:t"), "restricted"))
+            .unwrap();
+        assert_eq!(
+            names(&uses),
+            vec![
+                "id",
+                "{urn:t}global",
+                "{urn:t}q",
+                "color",
+                "font-family",
+                "font-size"
+            ]
+        );
+        assert_eq!(uses[5].fixed, Some("12".to_string()));
+
+        let codes: Vec<&str> = diagnostics.iter().map(|d| d.code.as_str()).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "ct-props-correct.4",
+                "derivation-ok-restriction.3",
+                "derivation-ok-restriction.2.2"
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicates() {
+        let set = set();
+        let (AttributeUses { uses, .. }, diagnostics) = set
+            .attribute_uses(&QName::new(Some("urn:t"), "duplicates"))
+            .unwrap();
+
+        assert_eq!(names(&uses), vec!["font-family", "font-size", "color"]);
+
+        let codes: Vec<&str> = diagnostics.iter().map(|d| d.code.as_str()).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "src-attribute_group.3",
+                "ct-props-correct.4",
+                "ct-props-correct.4"
+            ]
+        );
+    }
+
+    #[test]
+    fn simple_content() {
+        let mut set = set();
+        set.schemas
+            .push(Schema::parse(include_str!("schemas/xml.xsd")).unwrap());
+        let set = SchemaSet::new(set.schemas);
+
+        let (AttributeUses { uses, .. }, diagnostics) = set
+            .attribute_uses(&QName::new(Some("urn:t"), "text"))
+            .unwrap();
+        assert!(diagnostics.is_empty());
+        assert_eq!(
+            names(&uses),
+            vec!["{http://www.w3.org/XML/1998/namespace}lang"]
+        );
+        assert!(uses[0].declaration.simple_type.is_some());
+        assert!(uses[0].r#type.is_none());
+    }
+
+    #[test]
+    fn wildcards() {
+        let set = set();
+        let namespaces = |namespaces: &[Option<&str>]| {
+            NamespaceConstraint::Set(namespaces.iter().map(|n| n.map(String::from)).collect())
+        };
+
+        let (AttributeUses { wildcard, .. }, diagnostics) = set
+            .attribute_uses(&QName::new(Some("urn:t"), "open"))
+            .unwrap();
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        let wildcard = wildcard.unwrap();
+        assert_eq!(wildcard.namespaces, namespaces(&[None, Some("urn:u")]));
+        assert_eq!(wildcard.process_contents, ProcessContents::Strict);
+
+        let (AttributeUses { wildcard, .. }, diagnostics) = set
+            .attribute_uses(&QName::new(Some("urn:t"), "open-extended"))
+            .unwrap();
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(
+            wildcard.unwrap().namespaces,
+            namespaces(&[None, Some("urn:t"), Some("urn:u")])
+        );
+
+        let (AttributeUses { uses, wildcard }, diagnostics) = set
+            .attribute_uses(&QName::new(Some("urn:t"), "open-restricted"))
+            .unwrap();
+        assert_eq!(names(&uses), vec!["local"]);
+        assert_eq!(
+            wildcard.unwrap().namespaces,
+            namespaces(&[None, Some("urn:v")])
+        );
+
+        let codes: Vec<&str> = diagnostics.iter().map(|d| d.code.as_str()).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "derivation-ok-restriction.2.2",
+                "derivation-ok-restriction.4.2",
+                "derivation-ok-restriction.4.3"
+            ]
+        );
+    }
+}
@@ -0,0 +1,592 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    content_model::{ContentModel, Particle, Term},
+    prelude::QName,
+    schema_set::SchemaSet,
+};
+
+impl SchemaSet {
+    /// The automaton of the effective content model of the global complex type `name`, with
+    /// substitution groups expanded. `None` if there is no such type.
+    pub fn content_automaton(&self, name: &QName) -> Option<ContentAutomaton<'_>> {
+        let (model, _) = self.content_model(name)?;
+        let model = self.substitution_groups().expand(self, model);
+
+        Some(ContentAutomaton::new(self, &model))
+    }
+}
+
+/// A content model compiled into an automaton over element names, to validate or complete
+/// the children of an element one at a time.
+///
+/// States are sets of configurations. A configuration lists what remains to be matched, and
+/// every repeated particle in it carries a counter of how often it may still occur. Large
+/// `maxOccurs` values therefore cost nothing, unlike unfolding them into plain states. A
+/// configuration that only differs from another one of the same state by counters that allow
+/// less is dropped, so nested repeats do not multiply the configurations.
+///
+/// ```
+/// # use xsdl_parser::prelude::{QName, Schema, SchemaSet};
+/// let xml = r#"
+/// <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+///     <xs:complexType name="measure">
+///         <xs:sequence>
+///             <xs:element name="note" maxOccurs="1000"/>
+///         </xs:sequence>
+///     </xs:complexType>
+/// </xs:schema>"#;
+/// let set = SchemaSet::new(vec![Schema::parse(xml).unwrap()]);
+/// let automaton = set.content_automaton(&QName::new(None, "measure")).unwrap();
+///
+/// let mut state = automaton.start();
+/// assert!(!automaton.is_accepting(&state));
+/// assert!(automaton.step(&mut state, &QName::new(None, "note")).is_some());
+/// assert!(automaton.is_accepting(&state));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ContentAutomaton<'a> {
+    nodes: Vec<Node>,
+    leaves: Vec<Leaf<'a>>,
+    root: Option<usize>,
+}
+
+/// The state of a [`ContentAutomaton`] after some children.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentState {
+    configurations: BTreeSet<Vec<Item>>,
+}
+
+/// A particle of the content model.
+#[derive(Debug, Clone)]
+struct Node {
+    min_occurs: u32,
+    max_occurs: Option<u32>,
+    kind: NodeKind,
+}
+
+#[derive(Debug, Clone)]
+enum NodeKind {
+    /// Index into the leaves.
+    Leaf(usize),
+    Sequence(Vec<usize>),
+    Choice(Vec<usize>),
+    All(Vec<usize>),
+}
+
+/// An element or wildcard term.
+#[derive(Debug, Clone)]
+struct Leaf<'a> {
+    term: Term<'a>,
+
+    /// The target namespace of the schema document a wildcard is written in.
+    target_namespace: Option<String>,
+}
+
+impl Leaf<'_> {
+    fn matches(&self, name: &QName) -> bool {
+        match &self.term {
+            Term::Element(e) => e.name == *name,
+            Term::Any { wildcard, .. } => {
+                wildcard.allows(self.target_namespace.as_deref(), name.namespace.as_deref())
+            }
+            _ => false,
+        }
+    }
+}
+
+/// What remains of a particle.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Item {
+    /// The particle `node`, which must occur `min` more times and may occur up to `max` more
+    /// times.
+    Repeat {
+        node: usize,
+        min: u32,
+        max: Option<u32>,
+    },
+
+    /// The `all` group `node`, after the particles with a bit in `used` have occurred.
+    All { node: usize, used: Vec<u64> },
+}
+
+impl<'a> ContentAutomaton<'a> {
+    /// Compile `model`. References to global elements are matched by name only, so
+    /// substitution groups should be expanded before, see [`SubstitutionGroups::expand`].
+    ///
+    /// [`SubstitutionGroups::expand`]: crate::prelude::SubstitutionGroups::expand
+    pub fn new(set: &'a SchemaSet, model: &ContentModel<'a>) -> Self {
+        let mut r = ContentAutomaton {
+            nodes: Vec::new(),
+            leaves: Vec::new(),
+            root: None,
+        };
+        r.root = model.particle.as_ref().map(|p| r.compile(set, p));
+
+        r
+    }
+
+    fn compile(&mut self, set: &'a SchemaSet, particle: &Particle<'a>) -> usize {
+        let mut children = |particles: &[Particle<'a>]| -> Vec<usize> {
+            particles.iter().map(|p| self.compile(set, p)).collect()
+        };
+
+        let kind = match &particle.term {
+            Term::Sequence(particles) => NodeKind::Sequence(children(particles)),
+            Term::Choice(particles) => NodeKind::Choice(children(particles)),
+            Term::All(particles) => NodeKind::All(children(particles)),
+            term => {
+                let target_namespace = match term {
+                    Term::Any { schema, .. } => set.schemas[*schema].target_namespace.clone(),
+                    _ => None,
+                };
+                self.leaves.push(Leaf {
+                    term: term.clone(),
+                    target_namespace,
+                });
+                NodeKind::Leaf(self.leaves.len() - 1)
+            }
+        };
+
+        self.nodes.push(Node {
+            min_occurs: particle.min_occurs,
+            max_occurs: particle.max_occurs,
+            kind,
+        });
+        self.nodes.len() - 1
+    }
+
+    /// The state before the first child.
+    pub fn start(&self) -> ContentState {
+        let configuration = self.root.map(|root| vec![self.item(root)]);
+
+        ContentState {
+            configurations: BTreeSet::from([configuration.unwrap_or_default()]),
+        }
+    }
+
+    /// Advance `state` over a child element called `name`. Returns the element or wildcard term
+    /// the child matches, or `None` if it is not allowed here, in which case `state` is left
+    /// unchanged.
+    pub fn step(&self, state: &mut ContentState, name: &QName) -> Option<&Term<'a>> {
+        let mut next = BTreeSet::new();
+        let mut matched: Option<usize> = None;
+
+        for configuration in state.configurations.iter() {
+            let mut derived = Vec::new();
+            self.derive(configuration, name, &mut derived);
+
+            for (leaf, configuration) in derived {
+                matched = Some(matched.map_or(leaf, |m| m.min(leaf)));
+                next.insert(configuration);
+            }
+        }
+
+        let leaf = matched?;
+        state.configurations = prune(next);
+        Some(&self.leaves[leaf].term)
+    }
+
+    /// True if the children so far are a complete match of the content model.
+    pub fn is_accepting(&self, state: &ContentState) -> bool {
+        state
+            .configurations
+            .iter()
+            .any(|c| c.iter().all(|item| self.nullable(item)))
+    }
+
+    /// The element and wildcard terms a next child could match, in the order of the content
+    /// model.
+    pub fn expected_next(&self, state: &ContentState) -> Vec<&Term<'a>> {
+        let mut leaves = BTreeSet::new();
+        for configuration in state.configurations.iter() {
+            self.first(configuration, &mut leaves);
+        }
+
+        leaves.into_iter().map(|l| &self.leaves[l].term).collect()
+    }
+
+    fn item(&self, node: usize) -> Item {
+        Item::Repeat {
+            node,
+            min: self.nodes[node].min_occurs,
+            max: self.nodes[node].max_occurs,
+        }
+    }
+
+    /// The `all` group `node` before any of its particles occurred.
+    fn all_item(&self, node: usize) -> Item {
+        Item::All {
+            node,
+            used: vec![0; self.all_children(node).len().div_ceil(64)],
+        }
+    }
+
+    fn all_children(&self, node: usize) -> &[usize] {
+        match &self.nodes[node].kind {
+            NodeKind::All(children) => children,
+            _ => &[],
+        }
+    }
+
+    /// True if `item` may match nothing at all.
+    fn nullable(&self, item: &Item) -> bool {
+        match item {
+            Item::Repeat { node, min, max } => {
+                *min == 0 || *max == Some(0) || self.nullable_term(*node)
+            }
+            Item::All { node, used } => self
+                .all_children(*node)
+                .iter()
+                .enumerate()
+                .all(|(i, c)| has_bit(used, i) || self.nullable(&self.item(*c))),
+        }
+    }
+
+    fn nullable_term(&self, node: usize) -> bool {
+        match &self.nodes[node].kind {
+            NodeKind::Leaf(_) => false,
+            NodeKind::Sequence(children) | NodeKind::All(children) => {
+                children.iter().all(|c| self.nullable(&self.item(*c)))
+            }
+            NodeKind::Choice(children) => children.iter().any(|c| self.nullable(&self.item(*c))),
+        }
+    }
+
+    /// The configurations after `name` when starting in `configuration`, together with the
+    /// leaf that matched.
+    fn derive(&self, configuration: &[Item], name: &QName, r: &mut Vec<(usize, Vec<Item>)>) {
+        let Some((first, rest)) = configuration.split_first() else {
+            return;
+        };
+
+        let mut inner = Vec::new();
+        match first {
+            Item::Repeat { node, min, max } => {
+                if *max != Some(0) {
+                    self.derive_term(*node, name, &mut inner);
+
+                    let again = Item::Repeat {
+                        node: *node,
+                        min: min.saturating_sub(1),
+                        max: max.map(|m| m - 1),
+                    };
+                    for (_, c) in inner.iter_mut() {
+                        if max.is_none_or(|m| m > 1) {
+                            c.push(again.clone());
+                        }
+                    }
+                }
+            }
+            Item::All { node, used } => {
+                for (i, child) in self.all_children(*node).iter().enumerate() {
+                    if has_bit(used, i) {
+                        continue;
+                    }
+
+                    let start = inner.len();
+                    self.derive(&[self.item(*child)], name, &mut inner);
+                    for (_, c) in inner[start..].iter_mut() {
+                        c.push(Item::All {
+                            node: *node,
+                            used: with_bit(used, i),
+                        });
+                    }
+                }
+            }
+        }
+
+        for (leaf, mut c) in inner {
+            c.extend_from_slice(rest);
+            r.push((leaf, c));
+        }
+
+        if self.nullable(first) {
+            self.derive(rest, name, r);
+        }
+    }
+
+    /// Like [`ContentAutomaton::derive`], for one occurrence of the term of `node`.
+    fn derive_term(&self, node: usize, name: &QName, r: &mut Vec<(usize, Vec<Item>)>) {
+        match &self.nodes[node].kind {
+            NodeKind::Leaf(leaf) => {
+                if self.leaves[*leaf].matches(name) {
+                    r.push((*leaf, Vec::new()));
+                }
+            }
+            NodeKind::Sequence(children) => {
+                let items: Vec<Item> = children.iter().map(|c| self.item(*c)).collect();
+                self.derive(&items, name, r);
+            }
+            NodeKind::Choice(children) => {
+                for c in children {
+                    self.derive(&[self.item(*c)], name, r);
+                }
+            }
+            NodeKind::All(_) => self.derive(&[self.all_item(node)], name, r),
+        }
+    }
+
+    /// The leaves that can match the next child in `configuration`.
+    fn first(&self, configuration: &[Item], r: &mut BTreeSet<usize>) {
+        let Some((first, rest)) = configuration.split_first() else {
+            return;
+        };
+
+        match first {
+            Item::Repeat { node, max, .. } if *max != Some(0) => match &self.nodes[*node].kind {
+                NodeKind::Leaf(leaf) => {
+                    r.insert(*leaf);
+                }
+                NodeKind::Sequence(children) => {
+                    let items: Vec<Item> = children.iter().map(|c| self.item(*c)).collect();
+                    self.first(&items, r);
+                }
+                NodeKind::Choice(children) => {
+                    for c in children {
+                        self.first(&[self.item(*c)], r);
+                    }
+                }
+                NodeKind::All(_) => self.first(&[self.all_item(*node)], r),
+            },
+            Item::Repeat { .. } => {}
+            Item::All { node, used } => {
+                for (i, child) in self.all_children(*node).iter().enumerate() {
+                    if !has_bit(used, i) {
+                        self.first(&[self.item(*child)], r);
+                    }
+                }
+            }
+        }
+
+        if self.nullable(first) {
+            self.first(rest, r);
+        }
+    }
+}
+
+/// True if the bit of the `i`th particle of an `all` group is set in `used`.
+fn has_bit(used: &[u64], i: usize) -> bool {
+    used[i / 64] & (1 << (i % 64)) != 0
+}
+
+/// `used` with the bit of the `i`th particle of an `all` group set.
+fn with_bit(used: &[u64], i: usize) -> Vec<u64> {
+    let mut r = used.to_vec();
+    r[i / 64] |= 1 << (i % 64);
+    r
+}
+
+/// Drop the configurations another one subsumes, see [`subsumes`].
+fn prune(configurations: BTreeSet<Vec<Item>>) -> BTreeSet<Vec<Item>> {
+    let mut kept: Vec<Vec<Item>> = Vec::new();
+    for c in configurations {
+        if kept.iter().any(|k| subsumes(k, &c)) {
+            continue;
+        }
+
+        kept.retain(|k| !subsumes(&c, k));
+        kept.push(c);
+    }
+
+    kept.into_iter().collect()
+}
+
+/// True if configuration `a` accepts every sequence of children `b` accepts: both are the same
+/// but for repeats in `a` that must occur less or may occur more often than in `b`.
+fn subsumes(a: &[Item], b: &[Item]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| match (a, b) {
+            (
+                Item::Repeat { node, min, max },
+                Item::Repeat {
+                    node: other,
+                    min: other_min,
+                    max: other_max,
+                },
+            ) => {
+                node == other
+                    && min <= other_min
+                    && max.is_none_or(|m| other_max.is_some_and(|o| m >= o))
+            }
+            (a, b) => a == b,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Schema;
+
+    const XML: &str = r###"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:song" xmlns="urn:song"
+    elementFormDefault="qualified">
+    <xs:complexType name="song">
+        <xs:sequence>
+            <xs:element name="title"/>
+            <xs:choice maxOccurs="unbounded">
+                <xs:element name="verse"/>
+                <xs:element name="chorus"/>
+            </xs:choice>
+            <xs:any namespace="##other" minOccurs="0"/>
+        </xs:sequence>
+    </xs:complexType>
+    <xs:complexType name="pairs">
+        <xs:sequence minOccurs="2" maxOccurs="3">
+            <xs:element name="key"/>
+            <xs:element name="value" minOccurs="0"/>
+        </xs:sequence>
+    </xs:complexType>
+    <xs:complexType name="settings">
+        <xs:all>
+            <xs:element name="solo"/>
+            <xs:element name="mute" minOccurs="0"/>
+        </xs:all>
+    </xs:complexType>
+    <xs:complexType name="long">
+        <xs:sequence>
+            <xs:element name="bar" minOccurs="100" maxOccurs="5000"/>
+        </xs:sequence>
+    </xs:complexType>
+    <xs:complexType name="nested">
+        <xs:sequence minOccurs="0" maxOccurs="1000">
+            <xs:element name="bar" minOccurs="0" maxOccurs="1000"/>
+        </xs:sequence>
+    </xs:complexType>
+    <xs:complexType name="empty"/>
+</xs:schema>"###;
+
+    fn name(local: &str) -> QName {
+        QName::new(Some("urn:song"), local)
+    }
+
+    fn names(terms: Vec<&Term>) -> Vec<String> {
+        terms
+            .into_iter()
+            .map(|t| match t {
+                Term::Element(e) => e.name.name.clone(),
+                _ => "*".to_string(),
+            })
+            .collect()
+    }
+
+    /// Steps over `children` and returns whether all were accepted and the final state is
+    /// accepting.
+    fn run(automaton: &ContentAutomaton, children: &[&str]) -> (bool, bool) {
+        let mut state = automaton.start();
+        for child in children {
+            let child = match child.strip_prefix("x:") {
+                Some(local) => QName::new(Some("urn:x"), local),
+                None => name(child),
+            };
+            if automaton.step(&mut state, &child).is_none() {
+                return (false, false);
+            }
+        }
+
+        (true, automaton.is_accepting(&state))
+    }
+
+    #[test]
+    fn sequence_and_choice() {
+        let set = SchemaSet::new(vec![Schema::parse(XML).unwrap()]);
+        let song = set.content_automaton(&name("song")).unwrap();
+
+        let mut state = song.start();
+        assert_eq!(names(song.expected_next(&state)), vec!["title"]);
+        assert!(song.step(&mut state, &name("title")).is_some());
+        assert!(!song.is_accepting(&state));
+        assert_eq!(names(song.expected_next(&state)), vec!["verse", "chorus"]);
+
+        assert!(song.step(&mut state, &name("chorus")).is_some());
+        assert!(song.is_accepting(&state));
+        assert_eq!(
+            names(song.expected_next(&state)),
+            vec!["verse", "chorus", "*"]
+        );
+
+        // A failed step leaves the state as it was.
+        let before = state.clone();
+        assert!(song.step(&mut state, &name("title")).is_none());
+        assert_eq!(state, before);
+
+        assert!(matches!(
+            song.step(&mut state, &QName::new(Some("urn:x"), "extra")),
+            Some(Term::Any { .. })
+        ));
+        assert!(song.is_accepting(&state));
+        assert!(song.expected_next(&state).is_empty());
+
+        assert_eq!(
+            run(&song, &["title", "verse", "verse", "x:a"]),
+            (true, true)
+        );
+        assert_eq!(run(&song, &["title", "x:a"]), (false, false));
+    }
+
+    #[test]
+    fn counters() {
+        let set = SchemaSet::new(vec![Schema::parse(XML).unwrap()]);
+
+        let pairs = set.content_automaton(&name("pairs")).unwrap();
+        assert_eq!(run(&pairs, &["key"]), (true, false));
+        assert_eq!(run(&pairs, &["key", "value", "key"]), (true, true));
+        assert_eq!(run(&pairs, &["key", "key", "value", "key"]), (true, true));
+        assert_eq!(run(&pairs, &["key", "key", "key", "key"]), (false, false));
+
+        let long = set.content_automaton(&name("long")).unwrap();
+        let bars = vec!["bar"; 99];
+        assert_eq!(run(&long, &bars), (true, false));
+        let bars = vec!["bar"; 5000];
+        assert_eq!(run(&long, &bars), (true, true));
+        let bars = vec!["bar"; 5001];
+        assert_eq!(run(&long, &bars), (false, false));
+
+        // Nested repeats keep one configuration per way to continue, not one per way to count.
+        let nested = set.content_automaton(&name("nested")).unwrap();
+        let mut state = nested.start();
+        for _ in 0..2000 {
+            assert!(nested.step(&mut state, &name("bar")).is_some());
+            assert!(state.configurations.len() <= 2, "{:?}", state);
+        }
+        assert!(nested.is_accepting(&state));
+    }
+
+    #[test]
+    fn all() {
+        let set = SchemaSet::new(vec![Schema::parse(XML).unwrap()]);
+        let settings = set.content_automaton(&name("settings")).unwrap();
+
+        assert_eq!(run(&settings, &["mute", "solo"]), (true, true));
+        assert_eq!(run(&settings, &["solo"]), (true, true));
+        assert_eq!(run(&settings, &["mute"]), (true, false));
+        assert_eq!(run(&settings, &["solo", "solo"]), (false, false));
+
+        let mut state = settings.start();
+        settings.step(&mut state, &name("mute"));
+        assert_eq!(names(settings.expected_next(&state)), vec!["solo"]);
+
+        // Groups of more than 128 particles.
+        let elements: String = (0..130)
+            .map(|i| format!(r#"<xs:element name="e{}"/>"#, i))
+            .collect();
+        let xml = format!(
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:song"
+                elementFormDefault="qualified">
+                <xs:complexType name="wide"><xs:all>{}</xs:all></xs:complexType>
+            </xs:schema>"#,
+            elements
+        );
+        let wide_set = SchemaSet::new(vec![Schema::parse(&xml).unwrap()]);
+        let wide = wide_set.content_automaton(&name("wide")).unwrap();
+        let children: Vec<String> = (0..130).rev().map(|i| format!("e{}", i)).collect();
+        let children: Vec<&str> = children.iter().map(String::as_str).collect();
+        assert_eq!(run(&wide, &children), (true, true));
+        assert_eq!(run(&wide, &children[1..]), (true, false));
+        assert_eq!(run(&wide, &["e129", "e128", "e128"]), (false, false));
+
+        let empty = set.content_automaton(&name("empty")).unwrap();
+        assert_eq!(run(&empty, &[]), (true, true));
+        assert_eq!(run(&empty, &["solo"]), (false, false));
+    }
+}
@@ -0,0 +1,509 @@
+use std::str::FromStr;
+
+use crate::{
+    document::XSD_NAMESPACE,
+    list::List,
+    prelude::{BoundKind, QName, Restriction, RestrictionContent, SimpleType},
+};
+
+/// Whether a simple type is atomic or a list of another type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variety {
+    Atomic,
+
+    /// A list of the built-in type with the given local name.
+    List(&'static str),
+}
+
+/// The `ordered` fundamental facet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ordered {
+    False,
+    Partial,
+    Total,
+}
+
+/// The `cardinality` fundamental facet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cardinality {
+    Finite,
+    CountablyInfinite,
+}
+
+/// How whitespace in a value is normalized before it is checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
+pub enum WhiteSpace {
+    Preserve,
+
+    /// Tabs, line feeds and carriage returns are replaced by spaces.
+    Replace,
+
+    /// Like `Replace`, then runs of spaces are collapsed and leading and trailing spaces removed.
+    Collapse,
+}
+
+impl FromStr for WhiteSpace {
+    type Err = ();
+
+    /// Parse the value of a `whiteSpace` facet.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "preserve" => Ok(WhiteSpace::Preserve),
+            "replace" => Ok(WhiteSpace::Replace),
+            "collapse" => Ok(WhiteSpace::Collapse),
+            _ => Err(()),
+        }
+    }
+}
+
+impl WhiteSpace {
+    /// Normalize `value` as a processor does before checking it against a type.
+    pub fn normalize(&self, value: &str) -> String {
+        match self {
+            WhiteSpace::Preserve => value.to_string(),
+            WhiteSpace::Replace => value.replace(['\t', '\n', '\r'], " "),
+            WhiteSpace::Collapse => value
+                .split([' ', '\t', '\n', '\r'])
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+/// Fundamental facets, as defined in section 4.2 of XML Schema Part 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FundamentalFacets {
+    pub ordered: Ordered,
+    pub bounded: bool,
+    pub cardinality: Cardinality,
+    pub numeric: bool,
+}
+
+/// A primitive or derived datatype built into XML Schema 1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuiltinType {
+    /// Local name inside the XML Schema namespace, e.g. `positiveInteger`.
+    pub name: &'static str,
+
+    /// The local name of the base type. `anySimpleType` has `anyType` as base, which is not a
+    /// simple type and therefore `None`.
+    pub base: Option<&'static str>,
+    pub variety: Variety,
+    pub fundamental: FundamentalFacets,
+
+    /// Facets the type implicitly has, e.g. `maxInclusive` 127 for `byte`.
+    pub white_space: WhiteSpace,
+    pub pattern: Option<&'static str>,
+    pub min_inclusive: Option<&'static str>,
+    pub max_inclusive: Option<&'static str>,
+    pub fraction_digits: Option<u32>,
+    pub min_length: Option<u64>,
+}
+
+const TEXT: FundamentalFacets = FundamentalFacets {
+    ordered: Ordered::False,
+    bounded: false,
+    cardinality: Cardinality::CountablyInfinite,
+    numeric: false,
+};
+
+const TEMPORAL: FundamentalFacets = FundamentalFacets {
+    ordered: Ordered::Partial,
+    bounded: false,
+    cardinality: Cardinality::CountablyInfinite,
+    numeric: false,
+};
+
+const UNBOUNDED_NUMBER: FundamentalFacets = FundamentalFacets {
+    ordered: Ordered::Total,
+    bounded: false,
+    cardinality: Cardinality::CountablyInfinite,
+    numeric: true,
+};
+
+const BOUNDED_NUMBER: FundamentalFacets = FundamentalFacets {
+    ordered: Ordered::Total,
+    bounded: true,
+    cardinality: Cardinality::Finite,
+    numeric: true,
+};
+
+const FLOATING: FundamentalFacets = FundamentalFacets {
+    ordered: Ordered::Partial,
+    bounded: true,
+    cardinality: Cardinality::Finite,
+    numeric: true,
+};
+
+const BOOLEAN: FundamentalFacets = FundamentalFacets {
+    ordered: Ordered::False,
+    bounded: false,
+    cardinality: Cardinality::Finite,
+    numeric: false,
+};
+
+const fn builtin(
+    name: &'static str,
+    base: &'static str,
+    fundamental: FundamentalFacets,
+) -> BuiltinType {
+    BuiltinType {
+        name,
+        base: Some(base),
+        variety: Variety::Atomic,
+        fundamental,
+        white_space: WhiteSpace::Collapse,
+        pattern: None,
+        min_inclusive: None,
+        max_inclusive: None,
+        fraction_digits: None,
+        min_length: None,
+    }
+}
+
+const fn list(name: &'static str, item: &'static str) -> BuiltinType {
+    BuiltinType {
+        variety: Variety::List(item),
+        min_length: Some(1),
+        ..builtin(name, "anySimpleType", TEXT)
+    }
+}
+
+const fn pattern(t: BuiltinType, pattern: &'static str) -> BuiltinType {
+    BuiltinType {
+        pattern: Some(pattern),
+        ..t
+    }
+}
+
+const fn range(
+    t: BuiltinType,
+    min: Option<&'static str>,
+    max: Option<&'static str>,
+) -> BuiltinType {
+    BuiltinType {
+        min_inclusive: min,
+        max_inclusive: max,
+        fraction_digits: Some(0),
+        pattern: Some(INTEGER_PATTERN),
+        ..t
+    }
+}
+
+const INTEGER_PATTERN: &str = r"[\-+]?[0-9]+";
+
+/// All built-in simple types, base types before the types derived from them.
+pub const BUILTIN_TYPES: &[BuiltinType] = &[
+    BuiltinType {
+        base: None,
+        white_space: WhiteSpace::Preserve,
+        ..builtin("anySimpleType", "", TEXT)
+    },
+    // Primitive types
+    BuiltinType {
+        white_space: WhiteSpace::Preserve,
+        ..builtin("string", "anySimpleType", TEXT)
+    },
+    builtin("boolean", "anySimpleType", BOOLEAN),
+    builtin("decimal", "anySimpleType", UNBOUNDED_NUMBER),
+    builtin("float", "anySimpleType", FLOATING),
+    builtin("double", "anySimpleType", FLOATING),
+    builtin("duration", "anySimpleType", TEMPORAL),
+    builtin("dateTime", "anySimpleType", TEMPORAL),
+    builtin("time", "anySimpleType", TEMPORAL),
+    builtin("date", "anySimpleType", TEMPORAL),
+    builtin("gYearMonth", "anySimpleType", TEMPORAL),
+    builtin("gYear", "anySimpleType", TEMPORAL),
+    builtin("gMonthDay", "anySimpleType", TEMPORAL),
+    builtin("gDay", "anySimpleType", TEMPORAL),
+    builtin("gMonth", "anySimpleType", TEMPORAL),
+    builtin("hexBinary", "anySimpleType", TEXT),
+    builtin("base64Binary", "anySimpleType", TEXT),
+    builtin("anyURI", "anySimpleType", TEXT),
+    builtin("QName", "anySimpleType", TEXT),
+    builtin("NOTATION", "anySimpleType", TEXT),
+    // Derived from string
+    BuiltinType {
+        white_space: WhiteSpace::Replace,
+        ..builtin("normalizedString", "string", TEXT)
+    },
+    builtin("token", "normalizedString", TEXT),
+    pattern(
+        builtin("language", "token", TEXT),
+        "[a-zA-Z]{1,8}(-[a-zA-Z0-9]{1,8})*",
+    ),
+    pattern(builtin("NMTOKEN", "token", TEXT), r"\c+"),
+    list("NMTOKENS", "NMTOKEN"),
+    pattern(builtin("Name", "token", TEXT), r"\i\c*"),
+    pattern(builtin("NCName", "Name", TEXT), r"[\i-[:]][\c-[:]]*"),
+    builtin("ID", "NCName", TEXT),
+    builtin("IDREF", "NCName", TEXT),
+    list("IDREFS", "IDREF"),
+    builtin("ENTITY", "NCName", TEXT),
+    list("ENTITIES", "ENTITY"),
+    // Derived from decimal
+    range(builtin("integer", "decimal", UNBOUNDED_NUMBER), None, None),
+    range(
+        builtin("nonPositiveInteger", "integer", UNBOUNDED_NUMBER),
+        None,
+        Some("0"),
+    ),
+    range(
+        builtin("negativeInteger", "nonPositiveInteger", UNBOUNDED_NUMBER),
+        None,
+        Some("-1"),
+    ),
+    range(
+        builtin("long", "integer", BOUNDED_NUMBER),
+        Some("-9223372036854775808"),
+        Some("9223372036854775807"),
+    ),
+    range(
+        builtin("int", "long", BOUNDED_NUMBER),
+        Some("-2147483648"),
+        Some("2147483647"),
+    ),
+    range(
+        builtin("short", "int", BOUNDED_NUMBER),
+        Some("-32768"),
+        Some("32767"),
+    ),
+    range(
+        builtin("byte", "short", BOUNDED_NUMBER),
+        Some("-128"),
+        Some("127"),
+    ),
+    range(
+        builtin("nonNegativeInteger", "integer", UNBOUNDED_NUMBER),
+        Some("0"),
+        None,
+    ),
+    range(
+        builtin("unsignedLong", "nonNegativeInteger", BOUNDED_NUMBER),
+        Some("0"),
+        Some("18446744073709551615"),
+    ),
+    range(
+        builtin("unsignedInt", "unsignedLong", BOUNDED_NUMBER),
+        Some("0"),
+        Some("4294967295"),
+    ),
+    range(
+        builtin("unsignedShort", "unsignedInt", BOUNDED_NUMBER),
+        Some("0"),
+        Some("65535"),
+    ),
+    range(
+        builtin("unsignedByte", "unsignedShort", BOUNDED_NUMBER),
+        Some("0"),
+        Some("255"),
+    ),
+    range(
+        builtin("positiveInteger", "nonNegativeInteger", UNBOUNDED_NUMBER),
+        Some("1"),
+        None,
+    ),
+];
+
+impl BuiltinType {
+    /// Find a built-in simple type by its local name.
+    pub fn lookup(name: &str) -> Option<&'static BuiltinType> {
+        BUILTIN_TYPES.iter().find(|t| t.name == name)
+    }
+
+    /// Find a built-in simple type by its qualified name.
+    pub fn lookup_qname(name: &QName) -> Option<&'static BuiltinType> {
+        match name.namespace.as_deref() {
+            Some(XSD_NAMESPACE) => Self::lookup(&name.name),
+            _ => None,
+        }
+    }
+
+    pub fn qname(&self) -> QName {
+        QName::new(Some(XSD_NAMESPACE), self.name)
+    }
+
+    pub fn base_type(&self) -> Option<&'static BuiltinType> {
+        self.base.and_then(Self::lookup)
+    }
+
+    /// The item type of a list type.
+    pub fn item_type(&self) -> Option<&'static BuiltinType> {
+        match self.variety {
+            Variety::Atomic => None,
+            Variety::List(item) => Self::lookup(item),
+        }
+    }
+
+    /// The base type chain, starting with the base type and ending with `anySimpleType`.
+    pub fn ancestors(&self) -> impl Iterator<Item = &'static BuiltinType> {
+        std::iter::successors(self.base_type(), |t| t.base_type())
+    }
+
+    /// True for the 19 primitive types, which are derived from `anySimpleType` directly.
+    pub fn is_primitive(&self) -> bool {
+        self.base == Some("anySimpleType") && self.variety == Variety::Atomic
+    }
+
+    /// The primitive type this type is derived from, or the type itself if it is primitive.
+    /// `anySimpleType` and list types have no primitive type.
+    pub fn primitive(&'static self) -> Option<&'static BuiltinType> {
+        std::iter::once(self)
+            .chain(self.ancestors())
+            .find(|t| t.is_primitive())
+    }
+
+    /// True if `self` is `other` or derived from it by restriction.
+    pub fn is_derived_from(&self, other: &BuiltinType) -> bool {
+        self.name == other.name || self.ancestors().any(|t| t.name == other.name)
+    }
+
+    /// The type as a [`SimpleType`], like it would be read from `XMLSchema.xsd`.
+    pub fn simple_type(&self) -> SimpleType {
+        let mut r = SimpleType {
+            name: self.name.to_string(),
+            ..Default::default()
+        };
+
+        match self.variety {
+            Variety::List(item) => {
+                r.list = Some(List {
+                    item_type: Some(format!("xs:{}", item)),
+                    ..Default::default()
+                })
+            }
+            Variety::Atomic => {
+                let mut restriction = Restriction {
+                    base: format!("xs:{}", self.base.unwrap_or("anyType")),
+                    ..Default::default()
+                };
+
+                let bounds = [
+                    self.min_inclusive
+                        .map(|v| RestrictionContent::new_bound(BoundKind::MinInclusive, v)),
+                    self.max_inclusive
+                        .map(|v| RestrictionContent::new_bound(BoundKind::MaxInclusive, v)),
+                ];
+                let pattern = self
+                    .pattern
+                    .filter(|p| self.base_type().and_then(|b| b.pattern) != Some(*p))
+                    .map(|p| RestrictionContent::Pattern(p.to_string()));
+
+                for facet in bounds.into_iter().chain([pattern]).flatten() {
+                    restriction.content.push(facet);
+                    restriction.facet_spans.push(None);
+                }
+
+                r.restriction = Some(restriction);
+            }
+        }
+
+        r
+    }
+}
+
+/// True if `name` is a built-in type of the XML Schema namespace, simple or `anyType`.
+pub fn is_builtin_type(name: &QName) -> bool {
+    name.namespace.as_deref() == Some(XSD_NAMESPACE)
+        && (name.name == "anyType" || BuiltinType::lookup(&name.name).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn white_space() {
+        let value = " a\tb\n\n c ";
+        assert_eq!(WhiteSpace::Preserve.normalize(value), value);
+        assert_eq!(WhiteSpace::Replace.normalize(value), " a b   c ");
+        assert_eq!(WhiteSpace::Collapse.normalize(value), "a b c");
+    }
+
+    #[test]
+    fn hierarchy() {
+        assert_eq!(BUILTIN_TYPES.len(), 45);
+        assert_eq!(
+            BUILTIN_TYPES.iter().filter(|t| t.is_primitive()).count(),
+            19
+        );
+
+        // Base types come first and every base exists.
+        for (index, t) in BUILTIN_TYPES.iter().enumerate() {
+            if let Some(base) = t.base_type() {
+                assert!(BUILTIN_TYPES[..index].contains(base), "{}", t.name);
+            }
+        }
+
+        let positive = BuiltinType::lookup("positiveInteger").unwrap();
+        let names: Vec<&str> = positive.ancestors().map(|t| t.name).collect();
+        assert_eq!(
+            names,
+            vec!["nonNegativeInteger", "integer", "decimal", "anySimpleType"]
+        );
+        assert_eq!(positive.primitive().unwrap().name, "decimal");
+        assert!(positive.is_derived_from(BuiltinType::lookup("integer").unwrap()));
+        assert!(!positive.is_derived_from(BuiltinType::lookup("long").unwrap()));
+
+        let idrefs = BuiltinType::lookup("IDREFS").unwrap();
+        assert!(idrefs.primitive().is_none());
+        assert_eq!(idrefs.item_type().unwrap().name, "IDREF");
+        assert_eq!(
+            idrefs.item_type().unwrap().primitive().unwrap().name,
+            "string"
+        );
+    }
+
+    #[test]
+    fn facets() {
+        let byte = BuiltinType::lookup("byte").unwrap();
+        assert_eq!(byte.fundamental, BOUNDED_NUMBER);
+        assert_eq!(byte.max_inclusive, Some("127"));
+        assert_eq!(byte.white_space, WhiteSpace::Collapse);
+
+        let token = BuiltinType::lookup("token").unwrap();
+        assert_eq!(token.fundamental.ordered, Ordered::False);
+        assert!(!token.fundamental.numeric);
+
+        assert_eq!(
+            BuiltinType::lookup("normalizedString").unwrap().white_space,
+            WhiteSpace::Replace
+        );
+
+        let simple_type = byte.simple_type();
+        let restriction = simple_type.restriction.unwrap();
+        assert_eq!(restriction.base, "xs:short".to_string());
+        assert_eq!(
+            restriction.content,
+            vec![
+                RestrictionContent::MinInclusive(-128),
+                RestrictionContent::MaxInclusive(127)
+            ]
+        );
+
+        // Bounds that are not simple integers are kept as written.
+        let long = BuiltinType::lookup("long").unwrap().simple_type();
+        let max = long.restriction.unwrap().content[1].clone();
+        assert_eq!(
+            max,
+            RestrictionContent::Bound(BoundKind::MaxInclusive, "9223372036854775807".to_string())
+        );
+        assert_eq!(
+            RestrictionContent::MaxInclusive(127).bound(),
+            Some((BoundKind::MaxInclusive, "127".into()))
+        );
+    }
+
+    #[test]
+    fn qualified_names() {
+        let decimal = QName::new(Some(XSD_NAMESPACE), "decimal");
+        assert_eq!(BuiltinType::lookup_qname(&decimal).unwrap().name, "decimal");
+        assert!(is_builtin_type(&QName::new(Some(XSD_NAMESPACE), "anyType")));
+        assert!(!is_builtin_type(&QName::new(
+            Some(XSD_NAMESPACE),
+            "decimals"
+        )));
+        assert!(!is_builtin_type(&QName::new(None, "decimal")));
+    }
+}
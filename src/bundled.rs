@@ -0,0 +1,222 @@
+use crate::{
+    error::Error,
+    schema_resolver::{resolve_uri, ResolvedSchema, SchemaResolver},
+};
+
+/// A well-known schema document shipped with the crate.
+struct BundledSchema {
+    namespace: &'static str,
+
+    /// Locations the document is published at. The first one is used as its URI.
+    locations: &'static [&'static str],
+    content: &'static str,
+}
+
+const BUNDLED: &[BundledSchema] = &[
+    BundledSchema {
+        namespace: "http://www.w3.org/XML/1998/namespace",
+        locations: &[
+            "http://www.w3.org/2001/xml.xsd",
+            "https://www.w3.org/2001/xml.xsd",
+            "http://www.w3.org/2001/03/xml.xsd",
+            "http://www.w3.org/2009/01/xml.xsd",
+        ],
+        content: include_str!("schemas/xml.xsd"),
+    },
+    BundledSchema {
+        namespace: "http://www.w3.org/1999/xlink",
+        locations: &[
+            "http://www.w3.org/1999/xlink.xsd",
+            "https://www.w3.org/1999/xlink.xsd",
+            "http://www.w3.org/XML/2008/06/xlink.xsd",
+        ],
+        content: include_str!("schemas/xlink.xsd"),
+    },
+    BundledSchema {
+        namespace: "http://www.w3.org/2001/XMLSchema-instance",
+        locations: &[
+            "http://www.w3.org/2001/XMLSchema-instance.xsd",
+            "http://www.w3.org/2001/XMLSchema-instance",
+        ],
+        content: include_str!("schemas/XMLSchema-instance.xsd"),
+    },
+    BundledSchema {
+        namespace: "http://www.w3.org/2001/XMLSchema",
+        locations: &[
+            "http://www.w3.org/2001/XMLSchema.xsd",
+            "https://www.w3.org/2001/XMLSchema.xsd",
+        ],
+        content: include_str!("schemas/XMLSchema.xsd"),
+    },
+];
+
+/// Serves copies of `xml.xsd`, `xlink.xsd`, `XMLSchema-instance.xsd` and the datatypes of
+/// `XMLSchema.xsd` that are bundled with the crate. A document is found by one of the
+/// locations it is published at, or by the namespace of an import without a location.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BundledResolver;
+
+impl BundledResolver {
+    /// The namespaces a bundled document exists for.
+    pub fn namespaces() -> impl Iterator<Item = &'static str> {
+        BUNDLED.iter().map(|bundled| bundled.namespace)
+    }
+
+    /// The bundled document for `namespace`, whatever location it was asked for with. Used in
+    /// place of the location an import names by
+    /// [`DefaultResolver`](crate::schema_resolver::DefaultResolver).
+    pub fn by_namespace(namespace: &str) -> Option<ResolvedSchema> {
+        BUNDLED
+            .iter()
+            .find(|bundled| bundled.namespace == namespace)
+            .map(BundledSchema::resolved)
+    }
+}
+
+impl BundledSchema {
+    fn resolved(&self) -> ResolvedSchema {
+        ResolvedSchema {
+            uri: self.locations[0].to_string(),
+            content: self.content.as_bytes().to_vec(),
+            replaces: None,
+        }
+    }
+}
+
+impl SchemaResolver for BundledResolver {
+    fn resolve(
+        &self,
+        namespace: Option<&str>,
+        location: &str,
+        base: Option<&str>,
+    ) -> Result<ResolvedSchema, Error> {
+        let absolute = resolve_uri(base, location);
+        let found = match location.is_empty() {
+            true => BUNDLED
+                .iter()
+                .find(|bundled| namespace == Some(bundled.namespace)),
+            false => BUNDLED
+                .iter()
+                .find(|bundled| bundled.locations.contains(&absolute.as_str())),
+        };
+
+        match found {
+            Some(bundled) => Ok(bundled.resolved()),
+            None => Err(Error::NotFound {
+                namespace: namespace.map(|ns| ns.to_string()),
+                location: absolute,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{QName, Schema, SchemaSet, Severity};
+
+    #[test]
+    fn bundled_schemas_parse() {
+        for bundled in BUNDLED {
+            let schema = Schema::parse(bundled.content).unwrap();
+            assert_eq!(schema.target_namespace.as_deref(), Some(bundled.namespace));
+        }
+
+        let set = SchemaSet::new(
+            BUNDLED
+                .iter()
+                .map(|bundled| Schema::parse(bundled.content).unwrap())
+                .collect(),
+        );
+        assert!(set.diagnostics.is_empty(), "{:?}", set.diagnostics);
+    }
+
+    #[test]
+    fn resolve() {
+        let found = BundledResolver
+            .resolve(Some("http://www.w3.org/XML/1998/namespace"), "", None)
+            .unwrap();
+        assert_eq!(found.uri, "http://www.w3.org/2001/xml.xsd".to_string());
+
+        // An import with its own location gets that document, not the bundled copy.
+        assert!(BundledResolver
+            .resolve(
+                Some("http://www.w3.org/XML/1998/namespace"),
+                "http://www.musicxml.org/xsd/xml.xsd",
+                None,
+            )
+            .is_err());
+        assert!(BundledResolver::by_namespace("http://www.w3.org/XML/1998/namespace").is_some());
+
+        let found = BundledResolver
+            .resolve(None, "http://www.w3.org/1999/xlink.xsd", None)
+            .unwrap();
+        assert_eq!(found.uri, "http://www.w3.org/1999/xlink.xsd".to_string());
+
+        assert!(BundledResolver.resolve(None, "xlink.xsd", None).is_err());
+    }
+
+    #[test]
+    fn imports_with_location() {
+        let directory = tempfile::tempdir().unwrap();
+        std::fs::write(
+            directory.path().join("main.xsd"),
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:xlink="http://www.w3.org/1999/xlink">
+    <xs:import namespace="http://www.w3.org/1999/xlink" schemaLocation="xlink.xsd"/>
+    <xs:element name="link">
+        <xs:complexType>
+            <xs:attributeGroup ref="xlink:extendedAttrs"/>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>"#,
+        )
+        .unwrap();
+        std::fs::write(
+            directory.path().join("xlink.xsd"),
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="http://www.w3.org/1999/xlink">
+    <xs:attributeGroup name="extendedAttrs">
+        <xs:attribute name="role" type="xs:anyURI"/>
+    </xs:attributeGroup>
+</xs:schema>"#,
+        )
+        .unwrap();
+
+        let set = SchemaSet::load(&directory.path().join("main.xsd")).unwrap();
+
+        assert_eq!(set.schemas.len(), 2);
+        assert!(set.diagnostics.is_empty(), "{:?}", set.diagnostics);
+    }
+
+    #[test]
+    fn imports_without_location() {
+        let directory = tempfile::tempdir().unwrap();
+        std::fs::write(
+            directory.path().join("main.xsd"),
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:xml="http://www.w3.org/XML/1998/namespace" xmlns:xlink="http://www.w3.org/1999/xlink">
+    <xs:import namespace="http://www.w3.org/XML/1998/namespace"/>
+    <xs:import namespace="http://www.w3.org/1999/xlink" schemaLocation="missing/xlink.xsd"/>
+    <xs:element name="text">
+        <xs:complexType>
+            <xs:attribute ref="xml:lang"/>
+            <xs:attribute ref="xlink:href"/>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>"#,
+        )
+        .unwrap();
+
+        let set = SchemaSet::load(&directory.path().join("main.xsd")).unwrap();
+
+        assert_eq!(set.schemas.len(), 3);
+        assert_eq!(set.diagnostics.len(), 1, "{:?}", set.diagnostics);
+        assert_eq!(set.diagnostics[0].severity, Severity::Warning);
+        assert_eq!(set.diagnostics[0].code, "schema-replaced".to_string());
+        assert!(set.diagnostics[0].message.contains("missing/xlink.xsd"));
+        assert!(set
+            .attribute(&QName::new(
+                Some("http://www.w3.org/XML/1998/namespace"),
+                "lang"
+            ))
+            .is_some());
+    }
+}
@@ -0,0 +1,459 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use xmltree::Element;
+
+use crate::{
+    document::{self, expanded_name},
+    error::Error,
+    schema_resolver::{resolve_uri, DefaultResolver, ResolvedSchema, SchemaResolver},
+};
+
+/// Namespace of OASIS XML Catalog documents.
+pub const CATALOG_NAMESPACE: &str = "urn:oasis:names:tc:entity:xmlns:xml:catalog";
+
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// The two kinds of identifiers a catalog maps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Space {
+    Uri,
+    System,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Entry {
+    /// `uri` and `system`: maps one identifier to a URI.
+    Exact {
+        space: Space,
+        name: String,
+        uri: String,
+    },
+
+    /// `rewriteURI` and `rewriteSystem`: replaces a prefix of the identifier.
+    Rewrite {
+        space: Space,
+        start: String,
+        prefix: String,
+    },
+
+    /// `delegateURI` and `delegateSystem`: identifiers with this prefix are looked up in
+    /// another catalog only.
+    Delegate {
+        space: Space,
+        start: String,
+        catalog: String,
+    },
+
+    /// `nextCatalog`: consulted if nothing in this catalog matches.
+    Next(String),
+}
+
+/// A single OASIS XML Catalog document.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Catalog {
+    /// URI the catalog was loaded from.
+    pub uri: String,
+    entries: Vec<Entry>,
+}
+
+impl Catalog {
+    /// Parse a catalog document. Relative URIs in it are resolved against `uri`, or against the
+    /// `xml:base` of the enclosing `catalog` or `group`.
+    pub fn parse(xml: &[u8], uri: &str) -> Result<Self, Error> {
+        let (root, _) = document::parse(xml)?;
+        if root.namespace.as_deref() != Some(CATALOG_NAMESPACE) || root.name != "catalog" {
+            return Err(Error::InvalidCatalog {
+                uri: uri.to_string(),
+                message: "not an OASIS XML catalog".to_string(),
+            });
+        }
+
+        let mut r = Catalog {
+            uri: uri.to_string(),
+            entries: Vec::new(),
+        };
+        r.read_entries(&root, uri);
+
+        Ok(r)
+    }
+
+    fn read_entries(&mut self, element: &Element, base: &str) {
+        let base = match element
+            .attributes
+            .get(&expanded_name(XML_NAMESPACE, "base"))
+        {
+            Some(xml_base) => resolve_uri(Some(base), xml_base),
+            None => base.to_string(),
+        };
+        let uri = |value: &String| resolve_uri(Some(&base), value);
+
+        for child in element.children.iter().filter_map(|node| node.as_element()) {
+            // Elements from other namespaces are extensions, which we do not know.
+            if child.namespace.as_deref() != Some(CATALOG_NAMESPACE) {
+                continue;
+            }
+
+            let attribute = |name: &str| child.attributes.get(name);
+            let entry = match child.name.as_str() {
+                "group" => {
+                    self.read_entries(child, &base);
+                    None
+                }
+                "uri" | "system" => {
+                    let (space, key) = match child.name.as_str() {
+                        "uri" => (Space::Uri, "name"),
+                        _ => (Space::System, "systemId"),
+                    };
+
+                    attribute(key)
+                        .zip(attribute("uri"))
+                        .map(|(name, target)| Entry::Exact {
+                            space,
+                            name: name.clone(),
+                            uri: uri(target),
+                        })
+                }
+                "rewriteURI" | "rewriteSystem" => {
+                    let (space, key) = match child.name.as_str() {
+                        "rewriteURI" => (Space::Uri, "uriStartString"),
+                        _ => (Space::System, "systemIdStartString"),
+                    };
+
+                    attribute(key)
+                        .zip(attribute("rewritePrefix"))
+                        .map(|(start, prefix)| Entry::Rewrite {
+                            space,
+                            start: start.clone(),
+                            prefix: uri(prefix),
+                        })
+                }
+                "delegateURI" | "delegateSystem" => {
+                    let (space, key) = match child.name.as_str() {
+                        "delegateURI" => (Space::Uri, "uriStartString"),
+                        _ => (Space::System, "systemIdStartString"),
+                    };
+
+                    attribute(key)
+                        .zip(attribute("catalog"))
+                        .map(|(start, catalog)| Entry::Delegate {
+                            space,
+                            start: start.clone(),
+                            catalog: uri(catalog),
+                        })
+                }
+                "nextCatalog" => attribute("catalog").map(|catalog| Entry::Next(uri(catalog))),
+                _ => None,
+            };
+
+            self.entries.extend(entry);
+        }
+    }
+
+    /// Catalogs this one refers to via `nextCatalog` or a delegate entry.
+    fn references(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Delegate { catalog, .. } | Entry::Next(catalog) => Some(catalog),
+            _ => None,
+        })
+    }
+}
+
+/// Maps schema locations and namespaces to other locations using OASIS XML Catalogs before
+/// handing them to another resolver. This allows loading schemas that import well known
+/// documents from the web, like `http://www.w3.org/1999/xlink`, from local copies.
+///
+/// A location is first looked up as URI (`uri`, `rewriteURI`, `delegateURI`), then as system
+/// identifier (`system`, `rewriteSystem`, `delegateSystem`). If neither matches, the namespace of
+/// an import is looked up as URI. Catalogs referenced by `nextCatalog` or delegate entries are
+/// loaded together with the catalog, catalogs that cannot be loaded are ignored.
+#[derive(Debug, Default, Clone)]
+pub struct CatalogResolver<R: SchemaResolver = DefaultResolver> {
+    inner: R,
+
+    /// The catalogs passed to [`CatalogResolver::add_catalog`], in order.
+    roots: Vec<String>,
+    catalogs: HashMap<String, Catalog>,
+}
+
+impl CatalogResolver<DefaultResolver> {
+    /// Load the catalog file at `path`. Mapped and unmapped locations are read from disk or
+    /// downloaded.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let mut r = Self::new(DefaultResolver);
+        r.add_catalog(&path.to_string_lossy())?;
+
+        Ok(r)
+    }
+}
+
+impl<R: SchemaResolver> CatalogResolver<R> {
+    /// A resolver without catalogs, that uses `inner` to load catalogs and schema documents.
+    pub fn new(inner: R) -> Self {
+        CatalogResolver {
+            inner,
+            roots: Vec::new(),
+            catalogs: HashMap::new(),
+        }
+    }
+
+    /// Load the catalog at `location`, together with the catalogs it refers to. Catalogs are
+    /// consulted in the order they were added.
+    pub fn add_catalog(&mut self, location: &str) -> Result<(), Error> {
+        let document = self.inner.resolve(None, location, None)?;
+        let catalog = Catalog::parse(&document.content, &document.uri)?;
+
+        self.roots.push(catalog.uri.clone());
+        self.load_references(catalog);
+
+        Ok(())
+    }
+
+    fn load_references(&mut self, catalog: Catalog) {
+        let mut pending: Vec<String> = catalog.references().cloned().collect();
+        self.catalogs.insert(catalog.uri.clone(), catalog);
+
+        while let Some(location) = pending.pop() {
+            if self.catalogs.contains_key(&location) {
+                continue;
+            }
+
+            let catalog = self
+                .inner
+                .resolve(None, &location, None)
+                .and_then(|document| Catalog::parse(&document.content, &document.uri));
+
+            match catalog {
+                Ok(catalog) => {
+                    pending.extend(catalog.references().cloned());
+                    self.catalogs.insert(location, catalog);
+                }
+                // Unreadable catalogs are ignored, see section 8 of the specification.
+                Err(_) => {
+                    self.catalogs.insert(location.clone(), Catalog::default());
+                }
+            }
+        }
+    }
+
+    /// Look up a URI reference, e.g. a namespace name or a schema location.
+    pub fn lookup_uri(&self, uri: &str) -> Option<String> {
+        self.lookup(Space::Uri, uri)
+    }
+
+    /// Look up a system identifier.
+    pub fn lookup_system(&self, system_id: &str) -> Option<String> {
+        self.lookup(Space::System, system_id)
+    }
+
+    fn lookup(&self, space: Space, id: &str) -> Option<String> {
+        let mut visited = HashSet::new();
+        self.roots
+            .iter()
+            .find_map(|root| self.lookup_in(root, space, id, &mut visited))
+    }
+
+    fn lookup_in(
+        &self,
+        catalog: &str,
+        space: Space,
+        id: &str,
+        visited: &mut HashSet<String>,
+    ) -> Option<String> {
+        if !visited.insert(catalog.to_string()) {
+            return None;
+        }
+        let entries = &self.catalogs.get(catalog)?.entries;
+
+        let exact = entries.iter().find_map(|entry| match entry {
+            Entry::Exact {
+                space: s,
+                name,
+                uri,
+            } if *s == space && name == id => Some(uri.clone()),
+            _ => None,
+        });
+        if exact.is_some() {
+            return exact;
+        }
+
+        // The longest matching prefix wins.
+        let rewrite = entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::Rewrite {
+                    space: s,
+                    start,
+                    prefix,
+                } if *s == space && id.starts_with(start.as_str()) => Some((start, prefix)),
+                _ => None,
+            })
+            .max_by_key(|(start, _)| start.len());
+        if let Some((start, prefix)) = rewrite {
+            return Some(format!("{}{}", prefix, &id[start.len()..]));
+        }
+
+        // If delegates match, only they are consulted, longest prefix first.
+        let mut delegates: Vec<(&String, &String)> = entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::Delegate {
+                    space: s,
+                    start,
+                    catalog,
+                } if *s == space && id.starts_with(start.as_str()) => Some((start, catalog)),
+                _ => None,
+            })
+            .collect();
+        if !delegates.is_empty() {
+            delegates.sort_by_key(|(start, _)| std::cmp::Reverse(start.len()));
+            let mut delegate_visited = HashSet::new();
+            return delegates.iter().find_map(|(_, catalog)| {
+                self.lookup_in(catalog, space, id, &mut delegate_visited)
+            });
+        }
+
+        entries.iter().find_map(|entry| match entry {
+            Entry::Next(next) => self.lookup_in(next, space, id, visited),
+            _ => None,
+        })
+    }
+}
+
+impl<R: SchemaResolver> SchemaResolver for CatalogResolver<R> {
+    fn resolve(
+        &self,
+        namespace: Option<&str>,
+        location: &str,
+        base: Option<&str>,
+    ) -> Result<ResolvedSchema, Error> {
+        let mapped = if location.is_empty() {
+            None
+        } else {
+            let absolute = resolve_uri(base, location);
+            self.lookup_uri(location)
+                .or_else(|| self.lookup_uri(&absolute))
+                .or_else(|| self.lookup_system(location))
+                .or_else(|| self.lookup_system(&absolute))
+        };
+
+        match mapped.or_else(|| namespace.and_then(|ns| self.lookup_uri(ns))) {
+            Some(mapped) => self.inner.resolve(namespace, &mapped, None),
+            None => self.inner.resolve(namespace, location, base),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema_resolver::MemoryResolver;
+
+    const CATALOG: &str = r#"<?xml version="1.0"?>
+<catalog xmlns="urn:oasis:names:tc:entity:xmlns:xml:catalog">
+    <uri name="http://www.musicxml.org/xsd/xml.xsd" uri="w3c/xml.xsd"/>
+    <uri name="http://www.w3.org/1999/xlink" uri="w3c/xlink.xsd"/>
+    <system systemId="http://example.com/system.xsd" uri="system.xsd"/>
+    <rewriteURI uriStartString="http://example.com/" rewritePrefix="example/"/>
+    <rewriteURI uriStartString="http://example.com/deep/" rewritePrefix="deep/"/>
+    <group xml:base="other/">
+        <rewriteSystem systemIdStartString="http://example.org/" rewritePrefix="org/"/>
+    </group>
+    <delegateURI uriStartString="http://delegated.com/" catalog="delegate.xml"/>
+    <nextCatalog catalog="next.xml"/>
+    <nextCatalog catalog="missing.xml"/>
+</catalog>"#;
+
+    const DELEGATE: &str = r#"<catalog xmlns="urn:oasis:names:tc:entity:xmlns:xml:catalog">
+    <uri name="http://delegated.com/a.xsd" uri="a.xsd"/>
+</catalog>"#;
+
+    const NEXT: &str = r#"<catalog xmlns="urn:oasis:names:tc:entity:xmlns:xml:catalog">
+    <uri name="http://next.com/a.xsd" uri="next/a.xsd"/>
+    <uri name="http://delegated.com/b.xsd" uri="b.xsd"/>
+    <nextCatalog catalog="catalog.xml"/>
+</catalog>"#;
+
+    fn resolver() -> CatalogResolver<MemoryResolver> {
+        let mut memory = MemoryResolver::new();
+        memory.insert("catalogs/catalog.xml", CATALOG);
+        memory.insert("catalogs/delegate.xml", DELEGATE);
+        memory.insert("catalogs/next.xml", NEXT);
+        memory.insert(
+            "catalogs/w3c/xlink.xsd",
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"/>"#,
+        );
+
+        let mut r = CatalogResolver::new(memory);
+        r.add_catalog("catalogs/catalog.xml").unwrap();
+        r
+    }
+
+    #[test]
+    fn lookup() {
+        let r = resolver();
+
+        assert_eq!(
+            r.lookup_uri("http://www.musicxml.org/xsd/xml.xsd"),
+            Some("catalogs/w3c/xml.xsd".to_string())
+        );
+        assert_eq!(
+            r.lookup_system("http://example.com/system.xsd"),
+            Some("catalogs/system.xsd".to_string())
+        );
+        assert_eq!(
+            r.lookup_uri("http://example.com/system.xsd"),
+            Some("catalogs/example/system.xsd".to_string())
+        );
+        assert_eq!(
+            r.lookup_uri("http://example.com/deep/a.xsd"),
+            Some("catalogs/deep/a.xsd".to_string())
+        );
+        assert_eq!(
+            r.lookup_system("http://example.org/a/b.xsd"),
+            Some("catalogs/other/org/a/b.xsd".to_string())
+        );
+        assert_eq!(
+            r.lookup_uri("http://delegated.com/a.xsd"),
+            Some("catalogs/a.xsd".to_string())
+        );
+        assert_eq!(
+            r.lookup_uri("http://next.com/a.xsd"),
+            Some("catalogs/next/a.xsd".to_string())
+        );
+
+        // Delegated identifiers are not looked up in the next catalogs.
+        assert_eq!(r.lookup_uri("http://delegated.com/b.xsd"), None);
+        assert_eq!(r.lookup_uri("http://unknown.com/a.xsd"), None);
+    }
+
+    #[test]
+    fn resolve() {
+        let r = resolver();
+
+        let found = r
+            .resolve(
+                Some("http://www.w3.org/1999/xlink"),
+                "",
+                Some("schemas/musicxml.xsd"),
+            )
+            .unwrap();
+        assert_eq!(found.uri, "catalogs/w3c/xlink.xsd".to_string());
+
+        assert!(matches!(
+            r.resolve(None, "http://www.musicxml.org/xsd/xml.xsd", None),
+            Err(Error::NotFound { location, .. }) if location == "catalogs/w3c/xml.xsd"
+        ));
+    }
+
+    #[test]
+    fn invalid() {
+        let schema = br#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"/>"#;
+        assert!(matches!(
+            Catalog::parse(schema, "catalog.xml"),
+            Err(Error::InvalidCatalog { uri, .. }) if uri == "catalog.xml"
+        ));
+    }
+}
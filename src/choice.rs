@@ -1,9 +1,15 @@
 use crate::{
+    any::Any,
+    document::take_next_child,
     element::Element,
+    error::Error,
+    group::Group,
+    parse_context::ParseContext,
     prelude::{Occurs, Sequence},
+    source_span::SourceSpan,
 };
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub enum ChoiceItems {
     #[default]
     None,
@@ -11,43 +17,70 @@ pub enum ChoiceItems {
     Element(Element),
     Choice(Choice),
     Sequence(Sequence),
+    Group(Group),
+    Any(Any),
 }
 
-#[derive(Debug, Default)]
+impl ChoiceItems {
+    /// Local names of the particles a `choice` or `sequence` can contain.
+    pub(crate) const NAMES: &'static [&'static str] =
+        &["element", "choice", "sequence", "group", "any"];
+
+    /// Read the next particle of `element`, in document order.
+    pub(crate) fn read_next(
+        element: &mut xmltree::Element,
+        ctx: &mut ParseContext,
+    ) -> Result<Option<Self>, Error> {
+        let Some(mut child) = take_next_child(element, Self::NAMES) else {
+            return Ok(None);
+        };
+
+        let r = match child.name.as_str() {
+            "element" => ChoiceItems::Element(Element::read_with_context(&mut child, ctx)?),
+            "choice" => ChoiceItems::Choice(Choice::read_with_context(&mut child, ctx)?),
+            "sequence" => ChoiceItems::Sequence(Sequence::read_with_context(&mut child, ctx)?),
+            "group" => ChoiceItems::Group(Group::read_with_context(&mut child, ctx)?),
+            _ => ChoiceItems::Any(Any::read_with_context(&mut child, ctx)?),
+        };
+
+        Ok(Some(r))
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Choice {
     pub occurs: Vec<Occurs>,
+
+    /// The particles of the choice, in document order.
     pub elements: Vec<ChoiceItems>,
+
+    pub span: Option<SourceSpan>,
 }
 
 impl Choice {
-    pub fn read(element: &mut xmltree::Element) -> Self {
-        let mut r = Choice::default();
-
-        r.occurs = Occurs::read(element);
-
-        loop {
-            if let Some(mut element) = element.take_child("element") {
-                r.elements
-                    .push(ChoiceItems::Element(Element::read(&mut element)));
-                continue;
-            }
-
-            if let Some(mut choice) = element.take_child("choice") {
-                r.elements
-                    .push(ChoiceItems::Choice(Choice::read(&mut choice)));
-                continue;
-            }
-
-            if let Some(mut sequence) = element.take_child("sequence") {
-                r.elements
-                    .push(ChoiceItems::Sequence(Sequence::read(&mut sequence)));
-                continue;
-            }
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut xmltree::Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
 
-            break;
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(
+        element: &mut xmltree::Element,
+        ctx: &mut ParseContext,
+    ) -> Result<Self, Error> {
+        let mut r = Choice {
+            span: ctx.span(element),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope("choice", None, &r.span);
+        r.occurs = Occurs::read_with_context(element, ctx);
+
+        while let Some(item) = ChoiceItems::read_next(element, ctx)? {
+            r.elements.push(item);
         }
 
-        r
+        ctx.leave(element);
+        Ok(r)
     }
 }
 
@@ -101,7 +134,7 @@ mod tests {
 </xs:schema>        
         "#;
         let mut element = xmltree::Element::parse(xml.as_bytes()).unwrap();
-        let item = Schema::read(&mut element);
+        let item = Schema::read(&mut element).unwrap();
 
         assert_eq!(item.complex_types.len(), 1);
         assert_eq!(item.complex_types[0].name, "dynamics".to_string());
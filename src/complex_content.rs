@@ -0,0 +1,105 @@
+use crate::{
+    error::Error,
+    parse_context::ParseContext,
+    prelude::{Annotation, Extension, Restriction},
+    source_span::SourceSpan,
+};
+use xmltree::Element;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ComplexContent {
+    /// Whether character data may appear between the child elements. Overrides the `mixed`
+    /// attribute of the complex type if present.
+    pub mixed: Option<bool>,
+
+    pub annotations: Vec<Annotation>,
+    pub extensions: Vec<Extension>,
+    pub restrictions: Vec<Restriction>,
+
+    pub span: Option<SourceSpan>,
+}
+
+impl ComplexContent {
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
+
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(element: &mut Element, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let mut r = ComplexContent {
+            span: ctx.span(element),
+            mixed: element
+                .attributes
+                .remove("mixed")
+                .map(|mixed| mixed == "true" || mixed == "1"),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope("complexContent", None, &r.span);
+
+        while let Some(mut annotation) = element.take_child("annotation") {
+            r.annotations
+                .push(Annotation::read_with_context(&mut annotation, ctx)?);
+        }
+
+        while let Some(mut extension) = element.take_child("extension") {
+            r.extensions
+                .push(Extension::read_with_context(&mut extension, ctx)?);
+        }
+
+        while let Some(mut restriction) = element.take_child("restriction") {
+            r.restrictions
+                .push(Restriction::read_with_context(&mut restriction, ctx)?);
+        }
+
+        if r.extensions.len() + r.restrictions.len() > 1 {
+            return Err(ctx.invalid("exactly one extension or restriction is allowed"));
+        }
+
+        ctx.leave(element);
+        Ok(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::Schema;
+
+    #[test]
+    fn complex_content() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:complexType name="Track">
+        <xs:complexContent>
+            <xs:extension base="Lane">
+                <xs:sequence>
+                    <xs:element name="Channel" type="Channel" minOccurs="0"/>
+                </xs:sequence>
+                <xs:attribute name="contentType" type="xs:string"/>
+            </xs:extension>
+        </xs:complexContent>
+    </xs:complexType>
+    <xs:complexType name="Restricted">
+        <xs:complexContent mixed="true">
+            <xs:restriction base="Track">
+                <xs:attribute name="contentType" use="prohibited"/>
+            </xs:restriction>
+        </xs:complexContent>
+    </xs:complexType>
+</xs:schema>
+"#;
+
+        let item = Schema::parse(xml).unwrap();
+
+        let content = &item.complex_types[0].complex_content[0];
+        assert_eq!(content.mixed, None);
+        assert_eq!(content.extensions[0].base, "Lane".to_string());
+        assert_eq!(content.extensions[0].sequences[0].elements.len(), 1);
+        assert_eq!(content.extensions[0].attributes.len(), 1);
+
+        let content = &item.complex_types[1].complex_content[0];
+        assert_eq!(content.mixed, Some(true));
+        assert_eq!(content.restrictions[0].base, "Track".to_string());
+        assert_eq!(content.restrictions[0].attributes.len(), 1);
+    }
+}
@@ -1,63 +1,178 @@
 use crate::{
+    all::All,
     choice::Choice,
+    complex_content::ComplexContent,
+    derivation::DerivationSet,
+    error::Error,
     group::Group,
-    prelude::{Annotation, Attribute, AttributeGroup, Sequence, SimpleContent},
+    parse_context::ParseContext,
+    prelude::{Annotation, Any, Attribute, AttributeGroup, Sequence, SimpleContent},
+    source_span::SourceSpan,
 };
 use xmltree::Element;
 
-#[derive(Debug, Default)]
+/// How a type is derived from its base type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DerivationMethod {
+    Extension,
+    Restriction,
+
+    /// Simple types defined by `xs:list` or `xs:union`. Their base type is
+    /// `xs:anySimpleType`.
+    List,
+    Union,
+}
+
+impl std::fmt::Display for DerivationMethod {
+    /// The keyword used for the method in `block` and `final`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let keyword = match self {
+            DerivationMethod::Extension => "extension",
+            DerivationMethod::Restriction => "restriction",
+            DerivationMethod::List => "list",
+            DerivationMethod::Union => "union",
+        };
+        f.write_str(keyword)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct ComplexType {
     pub name: String,
 
+    /// Abstract types cannot be used as the type of an element in an instance.
+    pub r#abstract: bool,
+
+    /// Whether character data may appear between the child elements.
+    pub mixed: bool,
+
+    /// Derivation methods by which derived types may not replace this one in an instance.
+    /// `None` if the attribute is not given, then `blockDefault` of the schema applies.
+    pub block: Option<DerivationSet>,
+
+    /// Derivation methods that may not be used to derive from this type. `None` if the
+    /// attribute is not given, then `finalDefault` of the schema applies.
+    pub r#final: Option<DerivationSet>,
+
     pub annotations: Vec<Annotation>,
     pub attributes: Vec<Attribute>,
     pub attribute_groups: Vec<AttributeGroup>,
+    pub any_attribute: Option<Any>,
     pub simple_content: Vec<SimpleContent>,
+    pub complex_content: Vec<ComplexContent>,
     pub choices: Vec<Choice>,
     pub sequences: Vec<Sequence>,
+    pub all: Option<All>,
 
     pub groups: Vec<Group>,
+
+    pub span: Option<SourceSpan>,
 }
 
 impl ComplexType {
-    pub fn read(element: &mut Element) -> Self {
-        let mut r = ComplexType::default();
+    /// How the type is derived and the qualified name of its base type. `None` if the type has
+    /// neither `simpleContent` nor `complexContent`, i.e. it restricts `xs:anyType`.
+    pub fn derivation(&self) -> Option<(DerivationMethod, &str)> {
+        let extensions = self
+            .simple_content
+            .iter()
+            .flat_map(|c| c.extensions.iter())
+            .chain(
+                self.complex_content
+                    .iter()
+                    .flat_map(|c| c.extensions.iter()),
+            )
+            .map(|e| (DerivationMethod::Extension, e.base.as_str()));
+        let restrictions = self
+            .simple_content
+            .iter()
+            .flat_map(|c| c.restrictions.iter())
+            .chain(
+                self.complex_content
+                    .iter()
+                    .flat_map(|c| c.restrictions.iter()),
+            )
+            .map(|r| (DerivationMethod::Restriction, r.base.as_str()));
+
+        extensions.chain(restrictions).next()
+    }
 
-        if element.attributes.contains_key("name") {
-            r.name = element.attributes["name"].to_string();
-        }
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
+
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(element: &mut Element, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let mut r = ComplexType {
+            span: ctx.span(element),
+            name: element.attributes.remove("name").unwrap_or_default(),
+            r#abstract: element
+                .attributes
+                .remove("abstract")
+                .is_some_and(|v| v == "true" || v == "1"),
+            mixed: element
+                .attributes
+                .remove("mixed")
+                .is_some_and(|v| v == "true" || v == "1"),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope("complexType", Some(&r.name), &r.span);
+        r.block = DerivationSet::read(element, "block", ctx)?;
+        r.r#final = DerivationSet::read(element, "final", ctx)?;
 
         while let Some(mut annotation) = element.take_child("annotation") {
-            r.annotations.push(Annotation::read(&mut annotation));
+            r.annotations
+                .push(Annotation::read_with_context(&mut annotation, ctx)?);
         }
 
         while let Some(mut attribute) = element.take_child("attribute") {
-            r.attributes.push(Attribute::read(&mut attribute));
+            r.attributes
+                .push(Attribute::read_with_context(&mut attribute, ctx)?);
         }
 
         while let Some(mut attribute_group) = element.take_child("attributeGroup") {
-            r.attribute_groups
-                .push(AttributeGroup::read(&mut attribute_group));
+            r.attribute_groups.push(AttributeGroup::read_with_context(
+                &mut attribute_group,
+                ctx,
+            )?);
+        }
+
+        if let Some(mut any_attribute) = element.take_child("anyAttribute") {
+            r.any_attribute = Some(Any::read_with_context(&mut any_attribute, ctx)?);
         }
 
         while let Some(mut simple_content) = element.take_child("simpleContent") {
             r.simple_content
-                .push(SimpleContent::read(&mut simple_content));
+                .push(SimpleContent::read_with_context(&mut simple_content, ctx)?);
+        }
+
+        while let Some(mut complex_content) = element.take_child("complexContent") {
+            r.complex_content.push(ComplexContent::read_with_context(
+                &mut complex_content,
+                ctx,
+            )?);
         }
 
         while let Some(mut choice) = element.take_child("choice") {
-            r.choices.push(Choice::read(&mut choice));
+            r.choices.push(Choice::read_with_context(&mut choice, ctx)?);
         }
 
         while let Some(mut sequence) = element.take_child("sequence") {
-            r.sequences.push(Sequence::read(&mut sequence));
+            r.sequences
+                .push(Sequence::read_with_context(&mut sequence, ctx)?);
+        }
+
+        if let Some(mut all) = element.take_child("all") {
+            r.all = Some(All::read_with_context(&mut all, ctx)?);
         }
 
         while let Some(mut group) = element.take_child("group") {
-            r.groups.push(Group::read(&mut group));
+            r.groups.push(Group::read_with_context(&mut group, ctx)?);
         }
 
-        r
+        ctx.leave(element);
+        Ok(r)
     }
 }
 
@@ -87,7 +202,7 @@ mod tests {
 </xs:schema>        
         "#;
         let mut element = Element::parse(xml.as_bytes()).unwrap();
-        let item = Schema::read(&mut element);
+        let item = Schema::read(&mut element).unwrap();
 
         assert_eq!(item.complex_types.len(), 1);
         assert_eq!(item.complex_types[0].name, "accidental-text".to_string());
@@ -0,0 +1,854 @@
+use std::{borrow::Cow, cmp::Ordering, collections::HashSet};
+
+use crate::{
+    builtins::BuiltinType,
+    choice::ChoiceItems,
+    diagnostic::{Diagnostic, Severity},
+    particle_checks::element_terms,
+    pattern::Pattern,
+    prelude::{
+        All, AttributeGroup, BoundKind, Choice, ComplexType, Element, Group, Occurs, QName,
+        Restriction, RestrictionContent, Schema, Sequence,
+    },
+    schema_set::{ComponentId, DeclarationId, LocalScope, SchemaSet},
+    source_span::SourceSpan,
+    value::{compare_values, ValueValidator},
+};
+
+impl Schema {
+    /// Check this schema document on its own, see [`SchemaSet::check`]. References to
+    /// components of imported or included documents are reported as unresolved.
+    pub fn check(&self) -> Vec<Diagnostic> {
+        SchemaSet::new(vec![self.clone()]).check()
+    }
+}
+
+impl SchemaSet {
+    /// Check the schema component constraints of XML Schema Part 1 and 2 that the set
+    /// violates. Every diagnostic carries the code of the violated constraint, e.g.
+    /// `cos-ct-extends.1.1`.
+    ///
+    /// The result includes [`SchemaSet::diagnostics`], i.e. unresolved references and problems
+    /// found while loading, the diagnostics of [`SchemaSet::derivation_graph`] and
+    /// [`SchemaSet::substitution_groups`] and those found while computing the attribute uses
+    /// and content models of every complex type, named or anonymous, including those of
+    /// [`SchemaSet::check_particles_of`]. In addition it reports duplicate global names,
+    /// circular model and attribute groups, `minOccurs` greater than `maxOccurs` and facets
+    /// that contradict each other or the base type.
+    pub fn check(&self) -> Vec<Diagnostic> {
+        let mut r = self.diagnostics.clone();
+
+        r.append(&mut self.derivation_graph().diagnostics);
+        let mut groups = self.substitution_groups();
+        r.append(&mut groups.diagnostics);
+
+        let mut check = ConstraintCheck {
+            set: self,
+            diagnostics: Vec::new(),
+        };
+        check.duplicates();
+        let mut local = HashSet::new();
+        for (index, schema) in self.schemas.iter().enumerate() {
+            let id = |i| ComponentId {
+                schema: index,
+                index: i,
+            };
+            let named = schema
+                .complex_types
+                .iter()
+                .enumerate()
+                .map(|(i, t)| (index, t, LocalScope::Type(id(i))));
+            let anonymous = schema.elements.iter().enumerate().flat_map(|(i, e)| {
+                e.complex_types.iter().map(move |t| {
+                    let scope = LocalScope::AnonymousType(DeclarationId::Global(id(i)));
+                    (index, t, scope)
+                })
+            });
+
+            // The anonymous types of local element declarations are found in the content
+            // models of the types checked before them.
+            let mut types: Vec<(usize, &ComplexType, LocalScope)> =
+                named.chain(anonymous).collect();
+            let mut next = 0;
+            while let Some((schema, t, scope)) = types.get(next).cloned() {
+                next += 1;
+
+                check
+                    .diagnostics
+                    .append(&mut self.attribute_uses_of(schema, t).1);
+                let (model, mut diagnostics) = self.content_model_of(schema, t, scope);
+                check.diagnostics.append(&mut diagnostics);
+                check.diagnostics.append(&mut self.particle_diagnostics(
+                    &groups,
+                    &format!("complexType[{}]", t.name),
+                    &model,
+                ));
+
+                let mut elements = Vec::new();
+                model
+                    .particle
+                    .iter()
+                    .for_each(|p| element_terms(p, &mut elements));
+                for e in elements {
+                    if !matches!(e.id, DeclarationId::Local { .. }) || !local.insert(e.id.clone()) {
+                        continue;
+                    }
+
+                    for t in e.declaration.complex_types.iter() {
+                        types.push((e.schema, t, LocalScope::AnonymousType(e.id.clone())));
+                    }
+                }
+            }
+
+            check.circular_groups(index);
+            check.circular_attribute_groups(index);
+            check.occurs(schema);
+            check.facets(index);
+        }
+        r.append(&mut check.diagnostics);
+
+        // Several paths may lead to the same problem, e.g. a circular group used by two types.
+        let mut unique: Vec<Diagnostic> = Vec::new();
+        for d in r {
+            if !unique.contains(&d) {
+                unique.push(d);
+            }
+        }
+
+        unique
+    }
+}
+
+struct ConstraintCheck<'a> {
+    set: &'a SchemaSet,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> ConstraintCheck<'a> {
+    /// Global components with the same name in the same symbol space. Simple and complex types
+    /// share one symbol space.
+    fn duplicates(&mut self) {
+        let mut seen: HashSet<(&str, QName)> = HashSet::new();
+
+        for schema in self.set.schemas.iter() {
+            let namespace = schema.target_namespace.as_deref();
+            let mut components: Vec<(&str, &str, &str, &Option<SourceSpan>)> = Vec::new();
+
+            components.extend(
+                schema
+                    .simple_types
+                    .iter()
+                    .map(|t| ("type", "simpleType", t.name.as_str(), &t.span)),
+            );
+            components.extend(
+                schema
+                    .complex_types
+                    .iter()
+                    .map(|t| ("type", "complexType", t.name.as_str(), &t.span)),
+            );
+            components.extend(
+                schema
+                    .elements
+                    .iter()
+                    .map(|e| ("element", "element", e.name.as_str(), &e.span)),
+            );
+            components.extend(schema.attributes.iter().filter_map(|a| {
+                a.name
+                    .as_deref()
+                    .map(|name| ("attribute", "attribute", name, &a.span))
+            }));
+            components.extend(
+                schema
+                    .groups
+                    .iter()
+                    .map(|g| ("group", "group", g.name.as_str(), &g.span)),
+            );
+            components.extend(
+                schema
+                    .attribute_groups
+                    .iter()
+                    .map(|g| ("attributeGroup", "attributeGroup", g.name.as_str(), &g.span)),
+            );
+
+            for (space, component, name, span) in components {
+                if !seen.insert((space, QName::new(namespace, name))) {
+                    self.report(
+                        "sch-props-correct.2",
+                        format!("'{}' is defined more than once", name),
+                        format!("schema/{}[{}]", component, name),
+                        span.clone(),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Model group definitions that contain a reference to themselves.
+    fn circular_groups(&mut self, schema: usize) {
+        'groups: for group in self.set.schemas[schema].groups.iter() {
+            let start = QName::new(
+                self.set.schemas[schema].target_namespace.as_deref(),
+                &group.name,
+            );
+
+            let mut visited = HashSet::new();
+            let mut pending = vec![(schema, group)];
+            while let Some((schema, group)) = pending.pop() {
+                let mut references = Vec::new();
+                group_references(group, &mut references);
+
+                for reference in references {
+                    let Some(name) = self.set.schemas[schema].resolve_qname(reference) else {
+                        continue;
+                    };
+
+                    if name == start {
+                        self.report(
+                            "mg-props-correct.2",
+                            format!("group '{}' contains itself", group.name),
+                            format!("schema/group[{}]", start.name),
+                            group.span.clone(),
+                        );
+                        continue 'groups;
+                    }
+
+                    if let Some(id) = self.set.lookup(&name, |t| &t.groups) {
+                        if visited.insert(id) {
+                            pending
+                                .push((id.schema, &self.set.schemas[id.schema].groups[id.index]));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Attribute group definitions that contain a reference to themselves.
+    fn circular_attribute_groups(&mut self, schema: usize) {
+        'groups: for group in self.set.schemas[schema].attribute_groups.iter() {
+            let start = QName::new(
+                self.set.schemas[schema].target_namespace.as_deref(),
+                &group.name,
+            );
+
+            let mut visited = HashSet::new();
+            let mut pending: Vec<(usize, &AttributeGroup)> = vec![(schema, group)];
+            while let Some((schema, g)) = pending.pop() {
+                for reference in g
+                    .attribute_groups
+                    .iter()
+                    .filter_map(|g| g.reference.as_deref())
+                {
+                    let Some(name) = self.set.schemas[schema].resolve_qname(reference) else {
+                        continue;
+                    };
+
+                    if name == start {
+                        self.report(
+                            "src-attribute_group.3",
+                            format!("attribute group '{}' refers to itself", group.name),
+                            format!("schema/attributeGroup[{}]", start.name),
+                            group.span.clone(),
+                        );
+                        continue 'groups;
+                    }
+
+                    if let Some(id) = self.set.lookup(&name, |t| &t.attribute_groups) {
+                        if visited.insert(id) {
+                            pending.push((
+                                id.schema,
+                                &self.set.schemas[id.schema].attribute_groups[id.index],
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// `minOccurs` must not be greater than `maxOccurs`.
+    fn occurs(&mut self, schema: &'a Schema) {
+        let mut particles = Vec::new();
+
+        for t in schema.complex_types.iter() {
+            complex_type_particles("", t, &mut particles);
+        }
+        for g in schema.groups.iter() {
+            group_particles(g, &mut particles);
+        }
+        for e in schema.elements.iter() {
+            element_particles("", e, &mut particles);
+        }
+
+        for (component, occurs, span) in particles {
+            if let (min, Some(max)) = Occurs::range(occurs) {
+                if min > max {
+                    self.report(
+                        "p-props-correct.2.1",
+                        format!("minOccurs {} is greater than maxOccurs {}", min, max),
+                        format!("schema/{}", component),
+                        span.clone(),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Bounds, enumerations and patterns of the global simple types defined by restriction.
+    fn facets(&mut self, schema: usize) {
+        let document = &self.set.schemas[schema];
+        let values = ValueValidator::new(self.set);
+
+        for t in document.simple_types.iter() {
+            let Some(restriction) = &t.restriction else {
+                continue;
+            };
+            let path = format!("schema/simpleType[{}]/restriction", t.name);
+            let base = document.resolve_qname(&restriction.base);
+
+            for (i, facet) in restriction.content.iter().enumerate() {
+                let RestrictionContent::Pattern(pattern) = facet else {
+                    continue;
+                };
+                if let Err(e) = Pattern::new(pattern) {
+                    let span = restriction.facet_spans.get(i).cloned().flatten();
+                    self.report("invalid-pattern", e.to_string(), path.clone(), span);
+                }
+            }
+
+            // Bounds are compared in the value space of the built-in type the base derives from.
+            let (min, max) = own_bounds(restriction);
+            let (base_min, base_max, builtin) = match &base {
+                Some(base) => self.bounds(base),
+                None => (None, None, None),
+            };
+            let compare = |a: &str, b: &str| builtin.and_then(|t| compare_values(t, a, b));
+
+            if let (Some(min), Some(max)) = (&min, &max) {
+                if compare(min, max) == Some(Ordering::Greater) {
+                    self.report(
+                        "minInclusive-less-than-equal-to-maxInclusive",
+                        format!("minInclusive {} is greater than maxInclusive {}", min, max),
+                        path.clone(),
+                        restriction.span.clone(),
+                    );
+                }
+            }
+
+            if let (Some(min), Some(base_min)) = (&min, &base_min) {
+                if compare(min, base_min) == Some(Ordering::Less) {
+                    self.report(
+                        "minInclusive-valid",
+                        format!(
+                            "minInclusive {} is less than the minimum {} of the base type",
+                            min, base_min
+                        ),
+                        path.clone(),
+                        restriction.span.clone(),
+                    );
+                }
+            }
+            if let (Some(max), Some(base_max)) = (&max, &base_max) {
+                if compare(max, base_max) == Some(Ordering::Greater) {
+                    self.report(
+                        "maxInclusive-valid",
+                        format!(
+                            "maxInclusive {} is greater than the maximum {} of the base type",
+                            max, base_max
+                        ),
+                        path.clone(),
+                        restriction.span.clone(),
+                    );
+                }
+            }
+
+            for value in enumerations(restriction) {
+                let valid = base
+                    .as_ref()
+                    .is_none_or(|base| values.validate(base, value).is_ok());
+
+                if !valid {
+                    self.report(
+                        "enumeration-valid-restriction",
+                        format!(
+                            "enumeration value '{}' is not valid for the base type '{}'",
+                            value, restriction.base
+                        ),
+                        path.clone(),
+                        restriction.span.clone(),
+                    );
+                }
+            }
+        }
+    }
+
+    /// The nearest `minInclusive` and `maxInclusive` of the simple type `name` and its bases,
+    /// and the built-in type `name` derives from.
+    fn bounds(
+        &self,
+        name: &QName,
+    ) -> (
+        Option<Cow<'a, str>>,
+        Option<Cow<'a, str>>,
+        Option<&'static BuiltinType>,
+    ) {
+        let mut r = (None, None, None);
+        let mut current = Some(name.clone());
+        let mut visited = HashSet::new();
+
+        while let Some(name) = current.take() {
+            if !visited.insert(name.clone()) {
+                break;
+            }
+
+            let (min, max) = match BuiltinType::lookup_qname(&name) {
+                Some(builtin) => {
+                    current = builtin.base_type().map(|b| b.qname());
+                    r.2 = r.2.or(Some(builtin));
+                    (
+                        builtin.min_inclusive.map(Cow::Borrowed),
+                        builtin.max_inclusive.map(Cow::Borrowed),
+                    )
+                }
+                None => {
+                    let Some(id) = self.set.lookup(&name, |t| &t.simple_types) else {
+                        break;
+                    };
+                    let document = &self.set.schemas[id.schema];
+                    let Some(restriction) = &document.simple_types[id.index].restriction else {
+                        break;
+                    };
+
+                    current = document.resolve_qname(&restriction.base);
+                    own_bounds(restriction)
+                }
+            };
+
+            r.0 = r.0.or(min);
+            r.1 = r.1.or(max);
+        }
+
+        r
+    }
+
+    fn report(&mut self, code: &str, message: String, path: String, span: Option<SourceSpan>) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            code: code.to_string(),
+            message,
+            path,
+            span,
+        });
+    }
+}
+
+fn own_bounds(restriction: &Restriction) -> (Option<Cow<'_, str>>, Option<Cow<'_, str>>) {
+    let mut r = (None, None);
+    for (kind, value) in restriction
+        .content
+        .iter()
+        .filter_map(RestrictionContent::bound)
+    {
+        match kind {
+            BoundKind::MinInclusive => r.0 = Some(value),
+            BoundKind::MaxInclusive => r.1 = Some(value),
+            _ => {}
+        }
+    }
+
+    r
+}
+
+fn enumerations(restriction: &Restriction) -> impl Iterator<Item = &str> {
+    restriction.content.iter().filter_map(|facet| match facet {
+        RestrictionContent::Enumeration(value) => Some(value.as_str()),
+        _ => None,
+    })
+}
+
+/// The qualified names of the groups referenced inside the group definition `group`.
+fn group_references<'g>(group: &'g Group, r: &mut Vec<&'g str>) {
+    let mut items: Vec<&ChoiceItems> = Vec::new();
+    group
+        .sequences
+        .iter()
+        .for_each(|s| items.extend(s.particles.iter()));
+    group
+        .choices
+        .iter()
+        .for_each(|c| items.extend(c.elements.iter()));
+
+    while let Some(item) = items.pop() {
+        match item {
+            ChoiceItems::Group(g) => r.push(g.reference.as_str()),
+            ChoiceItems::Sequence(s) => items.extend(s.particles.iter()),
+            ChoiceItems::Choice(c) => items.extend(c.elements.iter()),
+            _ => {}
+        }
+    }
+}
+
+/// The component a particle belongs to, its occurrence attributes and its position.
+type ParticleOccurs<'a> = (String, &'a [Occurs], &'a Option<SourceSpan>);
+
+fn complex_type_particles<'a>(path: &str, t: &'a ComplexType, r: &mut Vec<ParticleOccurs<'a>>) {
+    let path = format!("{}complexType[{}]", path, t.name);
+
+    let extensions = t.complex_content.iter().flat_map(|c| c.extensions.iter());
+    let restrictions = t.complex_content.iter().flat_map(|c| c.restrictions.iter());
+
+    let sequences = t
+        .sequences
+        .iter()
+        .chain(extensions.clone().flat_map(|e| e.sequences.iter()))
+        .chain(restrictions.clone().flat_map(|e| e.sequences.iter()));
+    let choices = t
+        .choices
+        .iter()
+        .chain(extensions.clone().flat_map(|e| e.choices.iter()))
+        .chain(restrictions.clone().flat_map(|e| e.choices.iter()));
+    let groups = t
+        .groups
+        .iter()
+        .chain(extensions.clone().flat_map(|e| e.groups.iter()))
+        .chain(restrictions.clone().flat_map(|e| e.groups.iter()));
+    let all = t
+        .all
+        .iter()
+        .chain(extensions.flat_map(|e| e.all.iter()))
+        .chain(restrictions.flat_map(|e| e.all.iter()));
+
+    sequences.for_each(|s| sequence_particles(&path, s, r));
+    choices.for_each(|c| choice_particles(&path, c, r));
+    groups.for_each(|g| r.push((path.clone(), &g.occurs, &g.span)));
+    all.for_each(|a| all_particles(&path, a, r));
+}
+
+fn group_particles<'a>(g: &'a Group, r: &mut Vec<ParticleOccurs<'a>>) {
+    let path = format!("group[{}]", g.name);
+
+    g.sequences
+        .iter()
+        .for_each(|s| sequence_particles(&path, s, r));
+    g.choices.iter().for_each(|c| choice_particles(&path, c, r));
+    g.all.iter().for_each(|a| all_particles(&path, a, r));
+}
+
+fn sequence_particles<'a>(path: &str, s: &'a Sequence, r: &mut Vec<ParticleOccurs<'a>>) {
+    r.push((path.to_string(), &s.occurs, &s.span));
+    s.particles.iter().for_each(|p| item_particles(path, p, r));
+}
+
+fn choice_particles<'a>(path: &str, c: &'a Choice, r: &mut Vec<ParticleOccurs<'a>>) {
+    r.push((path.to_string(), &c.occurs, &c.span));
+    c.elements.iter().for_each(|p| item_particles(path, p, r));
+}
+
+fn all_particles<'a>(path: &str, a: &'a All, r: &mut Vec<ParticleOccurs<'a>>) {
+    r.push((path.to_string(), &a.occurs, &a.span));
+    for e in a.elements.iter() {
+        r.push((path.to_string(), &e.occurs, &e.span));
+        element_particles(path, e, r);
+    }
+}
+
+fn item_particles<'a>(path: &str, item: &'a ChoiceItems, r: &mut Vec<ParticleOccurs<'a>>) {
+    match item {
+        ChoiceItems::Element(e) => {
+            r.push((path.to_string(), &e.occurs, &e.span));
+            element_particles(path, e, r);
+        }
+        ChoiceItems::Choice(c) => choice_particles(path, c, r),
+        ChoiceItems::Sequence(s) => sequence_particles(path, s, r),
+        ChoiceItems::Group(g) => r.push((path.to_string(), &g.occurs, &g.span)),
+        ChoiceItems::Any(a) => r.push((path.to_string(), &a.occurs, &a.span)),
+        ChoiceItems::None => {}
+    }
+}
+
+/// The particles of the anonymous complex type of `e`.
+fn element_particles<'a>(path: &str, e: &'a Element, r: &mut Vec<ParticleOccurs<'a>>) {
+    let path = match path {
+        "" => format!("element[{}]/", e.name),
+        _ => format!("{}/element[{}]/", path, e.name),
+    };
+
+    e.complex_types
+        .iter()
+        .for_each(|t| complex_type_particles(&path, t, r));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(xml: &str) -> Vec<(String, String)> {
+        Schema::parse(xml)
+            .unwrap()
+            .check()
+            .into_iter()
+            .map(|d| (d.code, d.path))
+            .collect()
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|(code, path)| (code.to_string(), path.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn valid() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="midi-128">
+        <xs:restriction base="xs:positiveInteger">
+            <xs:minInclusive value="1"/>
+            <xs:maxInclusive value="128"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="yes-no">
+        <xs:restriction base="xs:token">
+            <xs:enumeration value="yes"/>
+            <xs:enumeration value="no"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="yes">
+        <xs:restriction base="yes-no">
+            <xs:enumeration value="yes"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:group name="g">
+        <xs:sequence minOccurs="0" maxOccurs="unbounded">
+            <xs:element name="a" type="midi-128" minOccurs="2" maxOccurs="3"/>
+        </xs:sequence>
+    </xs:group>
+    <xs:complexType name="t">
+        <xs:group ref="g"/>
+    </xs:complexType>
+    <xs:element name="t" type="t"/>
+</xs:schema>"#;
+
+        assert_eq!(codes(xml), vec![]);
+    }
+
+    #[test]
+    fn duplicates_and_cycles() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="t">
+        <xs:restriction base="xs:string"/>
+    </xs:simpleType>
+    <xs:complexType name="t"/>
+    <xs:element name="t" type="t"/>
+    <xs:element name="t" type="t"/>
+    <xs:attribute name="t" type="xs:string"/>
+
+    <xs:group name="a">
+        <xs:sequence>
+            <xs:group ref="b"/>
+        </xs:sequence>
+    </xs:group>
+    <xs:group name="b">
+        <xs:choice>
+            <xs:sequence>
+                <xs:group ref="a"/>
+            </xs:sequence>
+        </xs:choice>
+    </xs:group>
+
+    <xs:attributeGroup name="c">
+        <xs:attributeGroup ref="c"/>
+    </xs:attributeGroup>
+
+    <xs:complexType name="u">
+        <xs:sequence>
+            <xs:element name="x" type="xs:string" minOccurs="2" maxOccurs="1"/>
+        </xs:sequence>
+    </xs:complexType>
+    <xs:element name="v">
+        <xs:complexType>
+            <xs:choice minOccurs="3" maxOccurs="2"/>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>"#;
+
+        assert_eq!(
+            codes(xml),
+            pairs(&[
+                ("sch-props-correct.2", "schema/complexType[t]"),
+                ("sch-props-correct.2", "schema/element[t]"),
+                ("mg-props-correct.2", "schema/group[a]"),
+                ("mg-props-correct.2", "schema/group[b]"),
+                ("src-attribute_group.3", "schema/attributeGroup[c]"),
+                ("p-props-correct.2.1", "schema/complexType[u]"),
+                ("p-props-correct.2.1", "schema/element[v]/complexType[]"),
+            ])
+        );
+    }
+
+    #[test]
+    fn facets() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="reversed">
+        <xs:restriction base="xs:integer">
+            <xs:minInclusive value="10"/>
+            <xs:maxInclusive value="1"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="reversed-dates">
+        <xs:restriction base="xs:date">
+            <xs:minInclusive value="2025-01-01"/>
+            <xs:maxInclusive value="2024-12-31"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="byte-ish">
+        <xs:restriction base="xs:unsignedByte">
+            <xs:maxInclusive value="300"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="small">
+        <xs:restriction base="xs:integer">
+            <xs:minInclusive value="1"/>
+            <xs:maxInclusive value="16"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="smaller">
+        <xs:restriction base="small">
+            <xs:minInclusive value="0"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="numbers">
+        <xs:restriction base="small">
+            <xs:enumeration value="2"/>
+            <xs:enumeration value="17"/>
+            <xs:enumeration value="two"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="flag">
+        <xs:restriction base="xs:boolean">
+            <xs:enumeration value="true"/>
+            <xs:enumeration value="yes"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="yes-no">
+        <xs:restriction base="xs:token">
+            <xs:enumeration value="yes"/>
+            <xs:enumeration value="no"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="maybe">
+        <xs:restriction base="yes-no">
+            <xs:enumeration value="maybe"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="vowel-free">
+        <xs:restriction base="xs:string">
+            <xs:pattern value="[a-z-[aeiou]]+"/>
+            <xs:pattern value="[a-z"/>
+        </xs:restriction>
+    </xs:simpleType>
+</xs:schema>"#;
+
+        // Both '17' and 'two' are reported for `numbers`.
+        assert_eq!(
+            codes(xml),
+            pairs(&[
+                (
+                    "minInclusive-less-than-equal-to-maxInclusive",
+                    "schema/simpleType[reversed]/restriction",
+                ),
+                (
+                    "minInclusive-less-than-equal-to-maxInclusive",
+                    "schema/simpleType[reversed-dates]/restriction",
+                ),
+                (
+                    "maxInclusive-valid",
+                    "schema/simpleType[byte-ish]/restriction"
+                ),
+                (
+                    "minInclusive-valid",
+                    "schema/simpleType[smaller]/restriction"
+                ),
+                (
+                    "enumeration-valid-restriction",
+                    "schema/simpleType[numbers]/restriction",
+                ),
+                (
+                    "enumeration-valid-restriction",
+                    "schema/simpleType[numbers]/restriction",
+                ),
+                (
+                    "enumeration-valid-restriction",
+                    "schema/simpleType[flag]/restriction",
+                ),
+                (
+                    "enumeration-valid-restriction",
+                    "schema/simpleType[maybe]/restriction",
+                ),
+                (
+                    "invalid-pattern",
+                    "schema/simpleType[vowel-free]/restriction"
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn collected() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:complexType name="base" final="extension"/>
+    <xs:complexType name="derived">
+        <xs:complexContent>
+            <xs:extension base="base"/>
+        </xs:complexContent>
+    </xs:complexType>
+    <xs:element name="e" type="missing"/>
+</xs:schema>"#;
+
+        let r: Vec<String> = codes(xml).into_iter().map(|(code, _)| code).collect();
+        assert!(r.contains(&"src-resolve".to_string()), "{:?}", r);
+        assert!(r.contains(&"cos-ct-extends.1.1".to_string()), "{:?}", r);
+    }
+
+    #[test]
+    fn local_anonymous_types() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:group name="items">
+        <xs:sequence>
+            <xs:element name="item">
+                <xs:complexType>
+                    <xs:attribute name="id"/>
+                    <xs:attribute name="id"/>
+                </xs:complexType>
+            </xs:element>
+        </xs:sequence>
+    </xs:group>
+    <xs:element name="list">
+        <xs:complexType>
+            <xs:sequence>
+                <xs:element name="entry">
+                    <xs:complexType>
+                        <xs:choice>
+                            <xs:element name="a"/>
+                            <xs:sequence>
+                                <xs:element name="a"/>
+                                <xs:element name="b"/>
+                            </xs:sequence>
+                        </xs:choice>
+                    </xs:complexType>
+                </xs:element>
+                <xs:group ref="items"/>
+            </xs:sequence>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>"#;
+
+        let r: Vec<String> = codes(xml).into_iter().map(|(code, _)| code).collect();
+        assert_eq!(r, vec!["cos-nonambig", "ct-props-correct.4"]);
+    }
+}
@@ -0,0 +1,732 @@
+use std::collections::HashSet;
+
+use crate::{
+    choice::ChoiceItems,
+    diagnostic::{Diagnostic, Severity},
+    prelude::{All, Any, Choice, ComplexType, Element, Group, Occurs, QName, Sequence},
+    schema_set::{ComponentId, DeclarationId, LocalScope, SchemaSet},
+    source_span::SourceSpan,
+};
+
+/// The element content of a complex type, with group references expanded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentModel<'a> {
+    /// Character data may appear between the child elements.
+    pub mixed: bool,
+
+    /// `None` if the type has no element content, i.e. it is empty or has simple content.
+    pub particle: Option<Particle<'a>>,
+}
+
+/// A term together with how often it may occur.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Particle<'a> {
+    pub min_occurs: u32,
+
+    /// `None` for `unbounded`.
+    pub max_occurs: Option<u32>,
+    pub term: Term<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term<'a> {
+    Element(ElementTerm<'a>),
+    Any {
+        wildcard: &'a Any,
+
+        /// Index of the schema document the wildcard is written in. `##targetNamespace` refers
+        /// to its target namespace.
+        schema: usize,
+    },
+    Sequence(Vec<Particle<'a>>),
+    Choice(Vec<Particle<'a>>),
+    All(Vec<Particle<'a>>),
+}
+
+/// An element declaration inside a content model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementTerm<'a> {
+    pub name: QName,
+
+    /// The named type of the element. `None` if the type is anonymous, see
+    /// [`Element::complex_types`] of `declaration`, or not given at all. Global elements
+    /// without a type of their own have the type of their substitution group head.
+    pub r#type: Option<QName>,
+
+    /// The element declaration: the local declaration, or the global one for references.
+    pub declaration: &'a Element,
+
+    /// Index of the schema document `declaration` is written in.
+    pub schema: usize,
+
+    /// Identifies `declaration`. Terms that come from the same declaration, e.g. because a group
+    /// is used twice, have the same id.
+    pub id: DeclarationId,
+}
+
+impl<'a> ElementTerm<'a> {
+    /// The term for the global element `name`. Elements without a type of their own have the
+    /// type of the head of their substitution group.
+    pub(crate) fn global(set: &'a SchemaSet, name: &QName) -> Option<Self> {
+        let id = set.lookup(name, |t| &t.elements)?;
+        let declaration = &set.schemas[id.schema].elements[id.index];
+
+        let mut r#type = None;
+        let mut current = id;
+        let mut visited = HashSet::new();
+        while visited.insert(current) {
+            let e = &set.schemas[current.schema].elements[current.index];
+            if e.has_anonymous_type() {
+                break;
+            }
+            if !e.r#type.is_empty() {
+                r#type = set.schemas[current.schema].resolve_qname(&e.r#type);
+                break;
+            }
+
+            let Some(head) = e
+                .substitution_group
+                .as_deref()
+                .and_then(|g| g.split_whitespace().next())
+                .and_then(|head| set.schemas[current.schema].resolve_qname(head))
+                .and_then(|head| set.lookup(&head, |t| &t.elements))
+            else {
+                break;
+            };
+            current = head;
+        }
+
+        Some(ElementTerm {
+            name: name.clone(),
+            r#type,
+            declaration,
+            schema: id.schema,
+            id: DeclarationId::Global(id),
+        })
+    }
+}
+
+impl SchemaSet {
+    /// The effective content model of the global complex type `name`, together with the
+    /// problems found while computing it. `None` if there is no such type.
+    pub fn content_model(&self, name: &QName) -> Option<(ContentModel<'_>, Vec<Diagnostic>)> {
+        let id = self.lookup(name, |t| &t.complex_types)?;
+        Some(self.content_model_of(
+            id.schema,
+            &self.schemas[id.schema].complex_types[id.index],
+            LocalScope::Type(id),
+        ))
+    }
+
+    /// The effective content model of a complex type defined in the schema document with the
+    /// index `schema`. This works for anonymous types as well. `scope` identifies the type,
+    /// [`LocalScope::Type`] or [`LocalScope::AnonymousType`]. The [`ElementTerm::id`] of the
+    /// local element declarations of the type are relative to it.
+    ///
+    /// Group references are expanded and the content of the base type is prepended on
+    /// extension. Particles that cannot match anything (`maxOccurs="0"`), empty sequences and
+    /// groups with a single particle are removed, and sequences directly inside sequences, or
+    /// choices inside choices, are merged into their parent where this does not change what
+    /// they accept.
+    pub fn content_model_of<'a>(
+        &'a self,
+        schema: usize,
+        complex_type: &'a ComplexType,
+        scope: LocalScope,
+    ) -> (ContentModel<'a>, Vec<Diagnostic>) {
+        let mut builder = ContentBuilder {
+            set: self,
+            path: format!("complexType[{}]", complex_type.name),
+            scopes: Vec::new(),
+            diagnostics: Vec::new(),
+        };
+
+        let mixed = complex_type
+            .complex_content
+            .iter()
+            .find_map(|c| c.mixed)
+            .unwrap_or(complex_type.mixed);
+        let particle = builder.effective(schema, complex_type, scope, &mut HashSet::new());
+
+        (ContentModel { mixed, particle }, builder.diagnostics)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Compositor {
+    Sequence,
+    Choice,
+    All,
+}
+
+struct ContentBuilder<'a> {
+    set: &'a SchemaSet,
+    path: String,
+
+    /// The types and groups being expanded, the innermost last, with the number of local
+    /// element declarations found in each so far.
+    scopes: Vec<(LocalScope, usize)>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> ContentBuilder<'a> {
+    /// `types` holds the global types being expanded, to detect circular derivations.
+    /// Anonymous types cannot be the base of another type.
+    fn effective(
+        &mut self,
+        schema: usize,
+        complex_type: &'a ComplexType,
+        scope: LocalScope,
+        types: &mut HashSet<ComponentId>,
+    ) -> Option<Particle<'a>> {
+        if !complex_type.simple_content.is_empty() {
+            return None;
+        }
+
+        let id = match scope {
+            LocalScope::Type(id) => Some(id),
+            _ => None,
+        };
+        if id.is_some_and(|id| !types.insert(id)) {
+            self.report(
+                "ct-props-correct.3",
+                format!(
+                    "complex type '{}' is derived from itself",
+                    complex_type.name
+                ),
+                complex_type.span.clone(),
+            );
+            return None;
+        }
+
+        self.scopes.push((scope, 0));
+        let r = match complex_type.complex_content.first() {
+            Some(content) => {
+                if let Some(extension) = content.extensions.first() {
+                    let base = self.set.schemas[schema]
+                        .resolve_qname(&extension.base)
+                        .and_then(|base| self.set.lookup(&base, |t| &t.complex_types))
+                        .and_then(|id| {
+                            let base_type = &self.set.schemas[id.schema].complex_types[id.index];
+                            self.effective(id.schema, base_type, LocalScope::Type(id), types)
+                        });
+                    let local = self.local(
+                        schema,
+                        &extension.sequences,
+                        &extension.choices,
+                        &extension.groups,
+                        &extension.all,
+                    );
+
+                    match (base, local) {
+                        (Some(base), Some(local)) => {
+                            self.model_group(Compositor::Sequence, (1, Some(1)), vec![base, local])
+                        }
+                        (base, local) => base.or(local),
+                    }
+                } else if let Some(restriction) = content.restrictions.first() {
+                    self.local(
+                        schema,
+                        &restriction.sequences,
+                        &restriction.choices,
+                        &restriction.groups,
+                        &restriction.all,
+                    )
+                } else {
+                    None
+                }
+            }
+            None => self.local(
+                schema,
+                &complex_type.sequences,
+                &complex_type.choices,
+                &complex_type.groups,
+                &complex_type.all,
+            ),
+        };
+
+        self.scopes.pop();
+        if let Some(id) = id {
+            types.remove(&id);
+        }
+        r
+    }
+
+    /// The particle written directly in a type, an extension or a restriction. There is usually
+    /// at most one, several are wrapped in a sequence.
+    fn local(
+        &mut self,
+        schema: usize,
+        sequences: &'a [Sequence],
+        choices: &'a [Choice],
+        groups: &'a [Group],
+        all: &'a Option<All>,
+    ) -> Option<Particle<'a>> {
+        let mut particles = Vec::new();
+        particles.extend(sequences.iter().filter_map(|s| self.sequence(schema, s)));
+        particles.extend(choices.iter().filter_map(|c| self.choice(schema, c)));
+        particles.extend(
+            groups
+                .iter()
+                .filter_map(|g| self.group(schema, g, &mut Vec::new())),
+        );
+        particles.extend(all.iter().filter_map(|a| self.all(schema, a)));
+
+        self.model_group(Compositor::Sequence, (1, Some(1)), particles)
+    }
+
+    fn sequence(&mut self, schema: usize, s: &'a Sequence) -> Option<Particle<'a>> {
+        self.sequence_in(schema, s, Occurs::range(&s.occurs), &mut Vec::new())
+    }
+
+    fn sequence_in(
+        &mut self,
+        schema: usize,
+        s: &'a Sequence,
+        range: (u32, Option<u32>),
+        groups: &mut Vec<ComponentId>,
+    ) -> Option<Particle<'a>> {
+        let particles = s
+            .particles
+            .iter()
+            .filter_map(|p| self.item(schema, p, groups))
+            .collect();
+
+        self.model_group(Compositor::Sequence, range, particles)
+    }
+
+    fn choice(&mut self, schema: usize, c: &'a Choice) -> Option<Particle<'a>> {
+        self.choice_in(schema, c, Occurs::range(&c.occurs), &mut Vec::new())
+    }
+
+    fn choice_in(
+        &mut self,
+        schema: usize,
+        c: &'a Choice,
+        range: (u32, Option<u32>),
+        groups: &mut Vec<ComponentId>,
+    ) -> Option<Particle<'a>> {
+        let particles = c
+            .elements
+            .iter()
+            .filter_map(|p| self.item(schema, p, groups))
+            .collect();
+
+        self.model_group(Compositor::Choice, range, particles)
+    }
+
+    fn all(&mut self, schema: usize, a: &'a All) -> Option<Particle<'a>> {
+        self.all_in(schema, a, Occurs::range(&a.occurs))
+    }
+
+    fn all_in(
+        &mut self,
+        schema: usize,
+        a: &'a All,
+        range: (u32, Option<u32>),
+    ) -> Option<Particle<'a>> {
+        let particles = a
+            .elements
+            .iter()
+            .filter_map(|e| self.element(schema, e))
+            .collect();
+
+        self.model_group(Compositor::All, range, particles)
+    }
+
+    fn item(
+        &mut self,
+        schema: usize,
+        item: &'a ChoiceItems,
+        groups: &mut Vec<ComponentId>,
+    ) -> Option<Particle<'a>> {
+        match item {
+            ChoiceItems::None => None,
+            ChoiceItems::Element(e) => self.element(schema, e),
+            ChoiceItems::Choice(c) => self.choice_in(schema, c, Occurs::range(&c.occurs), groups),
+            ChoiceItems::Sequence(s) => {
+                self.sequence_in(schema, s, Occurs::range(&s.occurs), groups)
+            }
+            ChoiceItems::Group(g) => self.group(schema, g, groups),
+            ChoiceItems::Any(any) => {
+                let (min_occurs, max_occurs) = Occurs::range(&any.occurs);
+                (max_occurs != Some(0)).then_some(Particle {
+                    min_occurs,
+                    max_occurs,
+                    term: Term::Any {
+                        wildcard: any,
+                        schema,
+                    },
+                })
+            }
+        }
+    }
+
+    /// Expand a group reference. `groups` holds the references being expanded, to detect
+    /// groups that contain themselves.
+    fn group(
+        &mut self,
+        schema: usize,
+        g: &'a Group,
+        groups: &mut Vec<ComponentId>,
+    ) -> Option<Particle<'a>> {
+        // Unresolved references are reported when the set is built.
+        let id = self.set.schemas[schema]
+            .resolve_qname(&g.reference)
+            .and_then(|name| self.set.lookup(&name, |t| &t.groups))?;
+
+        if groups.contains(&id) {
+            self.report(
+                "mg-props-correct.2",
+                format!("group '{}' contains itself", g.reference),
+                g.span.clone(),
+            );
+            return None;
+        }
+
+        let definition = &self.set.schemas[id.schema].groups[id.index];
+        let range = Occurs::range(&g.occurs);
+
+        groups.push(id);
+        self.scopes.push((LocalScope::Group(id), 0));
+        let r = if let Some(s) = definition.sequences.first() {
+            self.sequence_in(id.schema, s, range, groups)
+        } else if let Some(c) = definition.choices.first() {
+            self.choice_in(id.schema, c, range, groups)
+        } else if let Some(a) = &definition.all {
+            self.all_in(id.schema, a, range)
+        } else {
+            None
+        };
+        self.scopes.pop();
+        groups.pop();
+
+        r
+    }
+
+    fn element(&mut self, schema: usize, e: &'a Element) -> Option<Particle<'a>> {
+        // Local declarations are counted whether they are used or not, so that a declaration
+        // has the same id in every content model it appears in.
+        let id = match &e.reference {
+            Some(_) => None,
+            None => self.scopes.last_mut().map(|(scope, count)| {
+                *count += 1;
+                DeclarationId::Local {
+                    scope: Box::new(scope.clone()),
+                    index: *count - 1,
+                }
+            }),
+        };
+
+        let (min_occurs, max_occurs) = Occurs::range(&e.occurs);
+        if max_occurs == Some(0) {
+            return None;
+        }
+
+        let term = match &e.reference {
+            Some(reference) => {
+                let name = self.set.schemas[schema].resolve_qname(reference)?;
+                ElementTerm::global(self.set, &name)?
+            }
+            None => {
+                let document = &self.set.schemas[schema];
+                let qualified = match e.form.as_deref() {
+                    Some(form) => form == "qualified",
+                    None => document.element_form_default.as_deref() == Some("qualified"),
+                };
+                let namespace = match qualified {
+                    true => document.target_namespace.as_deref(),
+                    false => None,
+                };
+
+                ElementTerm {
+                    name: QName::new(namespace, &e.name),
+                    r#type: Some(&e.r#type)
+                        .filter(|t| !t.is_empty())
+                        .and_then(|t| document.resolve_qname(t)),
+                    declaration: e,
+                    schema,
+                    id: id?,
+                }
+            }
+        };
+
+        Some(Particle {
+            min_occurs,
+            max_occurs,
+            term: Term::Element(term),
+        })
+    }
+
+    /// Build a model group particle and remove what is pointless about it.
+    fn model_group(
+        &mut self,
+        compositor: Compositor,
+        (min_occurs, max_occurs): (u32, Option<u32>),
+        particles: Vec<Particle<'a>>,
+    ) -> Option<Particle<'a>> {
+        if max_occurs == Some(0) {
+            return None;
+        }
+
+        // A sequence inside a sequence, or a choice inside a choice, that occurs exactly once
+        // can be replaced by its particles.
+        let mut flat = Vec::new();
+        for p in particles {
+            match p.term {
+                Term::Sequence(inner)
+                    if compositor == Compositor::Sequence
+                        && p.min_occurs == 1
+                        && p.max_occurs == Some(1) =>
+                {
+                    flat.extend(inner)
+                }
+                Term::Choice(inner)
+                    if compositor == Compositor::Choice
+                        && p.min_occurs == 1
+                        && p.max_occurs == Some(1) =>
+                {
+                    flat.extend(inner)
+                }
+                term => flat.push(Particle { term, ..p }),
+            }
+        }
+
+        // An empty choice cannot be satisfied, unless it is optional.
+        if flat.is_empty() && (compositor != Compositor::Choice || min_occurs == 0) {
+            return None;
+        }
+
+        if flat.len() == 1 && compositor != Compositor::All {
+            let single = &flat[0];
+            if min_occurs == 1 && max_occurs == Some(1) {
+                return flat.pop();
+            }
+
+            if single.min_occurs == 1 && single.max_occurs == Some(1) {
+                let single = flat.pop().unwrap();
+                return Some(Particle {
+                    min_occurs,
+                    max_occurs,
+                    term: single.term,
+                });
+            }
+        }
+
+        let term = match compositor {
+            Compositor::Sequence => Term::Sequence(flat),
+            Compositor::Choice => Term::Choice(flat),
+            Compositor::All => Term::All(flat),
+        };
+
+        Some(Particle {
+            min_occurs,
+            max_occurs,
+            term,
+        })
+    }
+
+    fn report(&mut self, code: &str, message: String, span: Option<SourceSpan>) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            code: code.to_string(),
+            message,
+            path: self.path.clone(),
+            span,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{document::XSD_NAMESPACE, prelude::Schema};
+
+    const XML: &str = r###"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:t" xmlns="urn:t" elementFormDefault="qualified">
+    <xs:element name="footnote" type="xs:string"/>
+
+    <xs:group name="editorial">
+        <xs:sequence>
+            <xs:element ref="footnote" minOccurs="0"/>
+            <xs:element name="level" type="xs:string" minOccurs="0" form="unqualified"/>
+        </xs:sequence>
+    </xs:group>
+    <xs:group name="unpitched-or-rest">
+        <xs:choice>
+            <xs:element name="unpitched"/>
+            <xs:element name="rest"/>
+        </xs:choice>
+    </xs:group>
+    <xs:group name="loop">
+        <xs:sequence>
+            <xs:group ref="loop"/>
+        </xs:sequence>
+    </xs:group>
+
+    <xs:complexType name="note">
+        <xs:sequence>
+            <xs:choice>
+                <xs:element name="pitch" type="xs:string"/>
+                <xs:group ref="unpitched-or-rest"/>
+            </xs:choice>
+            <xs:element name="duration" type="xs:positiveInteger"/>
+            <xs:sequence>
+                <xs:element name="tie" maxOccurs="2"/>
+            </xs:sequence>
+            <xs:element name="unused" maxOccurs="0"/>
+            <xs:sequence minOccurs="0"/>
+            <xs:group ref="editorial"/>
+            <xs:any namespace="##other" minOccurs="0"/>
+        </xs:sequence>
+    </xs:complexType>
+
+    <xs:complexType name="timed-note" mixed="true">
+        <xs:complexContent>
+            <xs:extension base="note">
+                <xs:sequence>
+                    <xs:element name="time" type="xs:decimal"/>
+                </xs:sequence>
+            </xs:extension>
+        </xs:complexContent>
+    </xs:complexType>
+
+    <xs:complexType name="rest-note">
+        <xs:complexContent mixed="false">
+            <xs:restriction base="timed-note">
+                <xs:group ref="unpitched-or-rest" maxOccurs="unbounded"/>
+            </xs:restriction>
+        </xs:complexContent>
+    </xs:complexType>
+
+    <xs:complexType name="point">
+        <xs:all>
+            <xs:element name="x" type="xs:decimal"/>
+            <xs:element name="y" type="xs:decimal"/>
+        </xs:all>
+    </xs:complexType>
+
+    <xs:complexType name="looping">
+        <xs:group ref="loop"/>
+    </xs:complexType>
+
+    <xs:complexType name="empty">
+        <xs:attribute name="id"/>
+    </xs:complexType>
+</xs:schema>
+"###;
+
+    fn set() -> SchemaSet {
+        SchemaSet::new(vec![Schema::parse(XML).unwrap()])
+    }
+
+    /// A compact notation of a particle, like `(a, b?)` for sequences and `(a | b)` for choices.
+    fn show(p: &Particle) -> String {
+        let occurs = match (p.min_occurs, p.max_occurs) {
+            (1, Some(1)) => String::new(),
+            (0, Some(1)) => "?".to_string(),
+            (0, None) => "*".to_string(),
+            (1, None) => "+".to_string(),
+            (min, Some(max)) => format!("{{{},{}}}", min, max),
+            (min, None) => format!("{{{},}}", min),
+        };
+        let join = |particles: &[Particle], separator| {
+            let parts: Vec<String> = particles.iter().map(show).collect();
+            format!("({})", parts.join(separator))
+        };
+
+        let term = match &p.term {
+            Term::Element(e) => e.name.to_string(),
+            Term::Any { wildcard, .. } => {
+                format!("any[{}]", wildcard.namespace.as_deref().unwrap_or(""))
+            }
+            Term::Sequence(particles) => join(particles, ", "),
+            Term::Choice(particles) => join(particles, " | "),
+            Term::All(particles) => join(particles, " & "),
+        };
+
+        term + &occurs
+    }
+
+    fn content(set: &SchemaSet, name: &str) -> (bool, Option<String>, Vec<Diagnostic>) {
+        let (model, diagnostics) = set.content_model(&QName::new(Some("urn:t"), name)).unwrap();
+        (model.mixed, model.particle.as_ref().map(show), diagnostics)
+    }
+
+    #[test]
+    fn groups() {
+        let set = set();
+
+        let (mixed, particle, diagnostics) = content(&set, "note");
+        assert!(!mixed);
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(
+            particle.unwrap(),
+            "(({urn:t}pitch | {urn:t}unpitched | {urn:t}rest), {urn:t}duration, {urn:t}tie{1,2}, \
+             {urn:t}footnote?, level?, any[##other]?)"
+        );
+    }
+
+    #[test]
+    fn derivation() {
+        let set = set();
+
+        let (mixed, particle, _) = content(&set, "timed-note");
+        assert!(mixed);
+        let particle = particle.unwrap();
+        assert!(particle.starts_with("(({urn:t}pitch"), "{}", particle);
+        assert!(
+            particle.ends_with("any[##other]?, {urn:t}time)"),
+            "{}",
+            particle
+        );
+
+        let (mixed, particle, _) = content(&set, "rest-note");
+        assert!(!mixed);
+        assert_eq!(particle.unwrap(), "({urn:t}unpitched | {urn:t}rest)+");
+    }
+
+    #[test]
+    fn all_and_empty() {
+        let set = set();
+
+        let (_, particle, _) = content(&set, "point");
+        assert_eq!(particle.unwrap(), "({urn:t}x & {urn:t}y)");
+
+        let (_, particle, diagnostics) = content(&set, "empty");
+        assert_eq!(particle, None);
+        assert!(diagnostics.is_empty());
+
+        let (_, particle, diagnostics) = content(&set, "looping");
+        assert_eq!(particle, None);
+        assert_eq!(diagnostics[0].code, "mg-props-correct.2".to_string());
+    }
+
+    #[test]
+    fn element_terms() {
+        let set = set();
+        let (model, _) = set
+            .content_model(&QName::new(Some("urn:t"), "note"))
+            .unwrap();
+
+        let Some(Particle {
+            term: Term::Sequence(particles),
+            ..
+        }) = &model.particle
+        else {
+            panic!("Expected sequence");
+        };
+
+        match &particles[1].term {
+            Term::Element(e) => {
+                assert_eq!(
+                    e.r#type,
+                    Some(QName::new(Some(XSD_NAMESPACE), "positiveInteger"))
+                );
+                assert_eq!(e.declaration.name, "duration".to_string());
+                assert_eq!(e.schema, 0);
+            }
+            t => panic!("Expected element, got {:?}", t),
+        }
+    }
+}
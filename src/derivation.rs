@@ -0,0 +1,692 @@
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+use crate::{
+    builtins::{BuiltinType, BUILTIN_TYPES},
+    diagnostic::{Diagnostic, Severity},
+    document::XSD_NAMESPACE,
+    error::Error,
+    parse_context::ParseContext,
+    prelude::{DerivationMethod, QName},
+    schema_set::SchemaSet,
+    source_span::SourceSpan,
+};
+
+/// The derivation methods named by `block`, `final`, `blockDefault` or `finalDefault`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DerivationSet {
+    pub extension: bool,
+    pub restriction: bool,
+    pub list: bool,
+    pub union: bool,
+
+    /// Only meaningful for `block` of element declarations.
+    pub substitution: bool,
+}
+
+impl DerivationSet {
+    /// Every derivation method, i.e. `#all`.
+    pub fn all() -> Self {
+        DerivationSet {
+            extension: true,
+            restriction: true,
+            list: true,
+            union: true,
+            substitution: true,
+        }
+    }
+
+    /// Parse a whitespace separated list of methods or `#all`. Returns `None` for unknown
+    /// tokens.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut r = DerivationSet::default();
+
+        for token in value.split_whitespace() {
+            match token {
+                "#all" => r = Self::all(),
+                "extension" => r.extension = true,
+                "restriction" => r.restriction = true,
+                "list" => r.list = true,
+                "union" => r.union = true,
+                "substitution" => r.substitution = true,
+                _ => return None,
+            }
+        }
+
+        Some(r)
+    }
+
+    pub fn contains(&self, method: DerivationMethod) -> bool {
+        match method {
+            DerivationMethod::Extension => self.extension,
+            DerivationMethod::Restriction => self.restriction,
+            DerivationMethod::List => self.list,
+            DerivationMethod::Union => self.union,
+        }
+    }
+
+    pub fn union(&self, other: &DerivationSet) -> DerivationSet {
+        DerivationSet {
+            extension: self.extension || other.extension,
+            restriction: self.restriction || other.restriction,
+            list: self.list || other.list,
+            union: self.union || other.union,
+            substitution: self.substitution || other.substitution,
+        }
+    }
+
+    /// Take the attribute `name` out of `element` and parse it. `None` if it is not present.
+    pub(crate) fn read(
+        element: &mut xmltree::Element,
+        name: &str,
+        ctx: &ParseContext,
+    ) -> Result<Option<Self>, Error> {
+        match element.attributes.remove(name) {
+            Some(value) => Self::parse(&value)
+                .map(Some)
+                .ok_or_else(|| ctx.invalid(&format!("invalid {} value '{}'", name, value))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A global type definition inside a [`DerivationGraph`].
+#[derive(Debug, Default, Clone)]
+struct TypeNode {
+    base: Option<(QName, DerivationMethod)>,
+
+    /// The derivation from `base` is not allowed by the `final` of the base type.
+    final_violated: bool,
+
+    simple: bool,
+
+    r#final: DerivationSet,
+    block: DerivationSet,
+
+    /// The named item type of a list.
+    item: Option<QName>,
+
+    /// The named member types of a union.
+    members: Vec<QName>,
+
+    path: String,
+    span: Option<SourceSpan>,
+}
+
+/// The base type relations between the global simple and complex types of a [`SchemaSet`],
+/// including the built-in types and `xs:anyType`.
+///
+/// Simple types defined by list or union are derived from `xs:anySimpleType` by
+/// [`DerivationMethod::List`] or [`DerivationMethod::Union`], complex types without
+/// `simpleContent` or `complexContent` are restrictions of `xs:anyType`. References
+/// that cannot be resolved are left out, they are reported by [`SchemaSet::diagnostics`].
+#[derive(Debug, Default, Clone)]
+pub struct DerivationGraph {
+    nodes: BTreeMap<QName, TypeNode>,
+    subtypes: BTreeMap<QName, Vec<QName>>,
+
+    /// Circular derivations and derivations that are forbidden by `final`.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl SchemaSet {
+    /// Build the derivation graph of all global type definitions.
+    pub fn derivation_graph(&self) -> DerivationGraph {
+        DerivationGraph::new(self)
+    }
+}
+
+impl DerivationGraph {
+    pub fn new(set: &SchemaSet) -> Self {
+        let mut r = DerivationGraph::default();
+
+        r.nodes.insert(any_type(), TypeNode::default());
+        for builtin in BUILTIN_TYPES {
+            let base = builtin
+                .base_type()
+                .map(|b| b.qname())
+                .unwrap_or_else(any_type);
+            r.nodes.insert(
+                builtin.qname(),
+                TypeNode {
+                    base: Some((base, DerivationMethod::Restriction)),
+                    simple: true,
+                    ..Default::default()
+                },
+            );
+        }
+
+        // The first definition of a name wins, like in the symbol tables.
+        let mut defined = Vec::new();
+        for schema in set.schemas.iter() {
+            let namespace = schema.target_namespace.as_deref();
+            let resolve = |qname: &str| schema.resolve_qname(qname);
+
+            for t in schema.simple_types.iter() {
+                let name = QName::new(namespace, &t.name);
+                if r.nodes.contains_key(&name) {
+                    continue;
+                }
+
+                let any_simple_type = QName::new(Some(XSD_NAMESPACE), "anySimpleType");
+                let base = match (&t.restriction, &t.list) {
+                    (Some(restriction), _) => {
+                        resolve(&restriction.base).map(|base| (base, DerivationMethod::Restriction))
+                    }
+                    (None, Some(_)) => Some((any_simple_type, DerivationMethod::List)),
+                    (None, None) => Some((any_simple_type, DerivationMethod::Union)),
+                };
+                let item = t
+                    .list
+                    .as_ref()
+                    .and_then(|l| l.item_type.as_deref())
+                    .and_then(resolve);
+                let members = t
+                    .union
+                    .iter()
+                    .flat_map(|u| u.types.iter())
+                    .filter(|member| !member.is_empty())
+                    .filter_map(|member| resolve(member))
+                    .collect();
+
+                defined.push(name.clone());
+                r.nodes.insert(
+                    name,
+                    TypeNode {
+                        base,
+                        r#final: t.r#final.unwrap_or(schema.final_default),
+                        item,
+                        members,
+                        simple: true,
+                        path: format!("schema/simpleType[{}]", t.name),
+                        span: t.span.clone(),
+                        ..Default::default()
+                    },
+                );
+            }
+
+            for t in schema.complex_types.iter() {
+                let name = QName::new(namespace, &t.name);
+                if r.nodes.contains_key(&name) {
+                    continue;
+                }
+
+                let base = match t.derivation() {
+                    Some((method, base)) => resolve(base).map(|base| (base, method)),
+                    None => Some((any_type(), DerivationMethod::Restriction)),
+                };
+
+                defined.push(name.clone());
+                r.nodes.insert(
+                    name,
+                    TypeNode {
+                        base,
+                        r#final: t.r#final.unwrap_or(schema.final_default),
+                        block: t.block.unwrap_or(schema.block_default),
+                        path: format!("schema/complexType[{}]", t.name),
+                        span: t.span.clone(),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+
+        for name in defined.iter() {
+            r.check(name);
+        }
+
+        for (name, node) in r.nodes.iter() {
+            if let Some((base, _)) = &node.base {
+                r.subtypes
+                    .entry(base.clone())
+                    .or_default()
+                    .push(name.clone());
+            }
+        }
+
+        r
+    }
+
+    /// Drop unresolved and circular base types of `name` and check the `final` of its base,
+    /// item and member types.
+    fn check(&mut self, name: &QName) {
+        let node = &self.nodes[name];
+        let item = node.item.iter().map(|item| (item, DerivationMethod::List));
+        let members = node.members.iter().map(|m| (m, DerivationMethod::Union));
+        let forbidden: Vec<(QName, DerivationMethod)> = item
+            .chain(members)
+            .filter(|(t, method)| {
+                self.nodes
+                    .get(*t)
+                    .is_some_and(|node| node.r#final.contains(*method))
+            })
+            .map(|(t, method)| (t.clone(), method))
+            .collect();
+        for (t, method) in forbidden {
+            let code = match method {
+                DerivationMethod::List => "st-props-correct.4.2.1",
+                _ => "st-props-correct.4.2.2",
+            };
+            self.report(
+                name,
+                code,
+                format!("'{}' does not allow derivation by {}", t, method),
+            );
+        }
+
+        let Some((base, method)) = self.nodes[name].base.clone() else {
+            return;
+        };
+
+        let Some(base_node) = self.nodes.get(&base) else {
+            self.nodes.get_mut(name).unwrap().base = None;
+            return;
+        };
+
+        if base_node.r#final.contains(method) {
+            let simple = self.is_simple(name);
+            let code = match method {
+                _ if simple => "st-props-correct.3",
+                DerivationMethod::Extension => "cos-ct-extends.1.1",
+                _ => "derivation-ok-restriction.1",
+            };
+
+            self.report(
+                name,
+                code,
+                format!("'{}' does not allow derivation by {}", base, method),
+            );
+            self.nodes.get_mut(name).unwrap().final_violated = true;
+        }
+
+        let mut current = Some(base);
+        let mut visited = HashSet::new();
+        while let Some(ancestor) = current {
+            if &ancestor == name {
+                let code = if self.is_simple(name) {
+                    "st-props-correct.2"
+                } else {
+                    "ct-props-correct.3"
+                };
+                self.report(name, code, format!("'{}' is derived from itself", name));
+                self.nodes.get_mut(name).unwrap().base = None;
+                return;
+            }
+
+            if !visited.insert(ancestor.clone()) {
+                return;
+            }
+
+            current = self.base_type(&ancestor).cloned();
+        }
+    }
+
+    fn report(&mut self, name: &QName, code: &str, message: String) {
+        let node = &self.nodes[name];
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            code: code.to_string(),
+            message,
+            path: node.path.clone(),
+            span: node.span.clone(),
+        });
+    }
+
+    /// True if `name` is a type of the graph.
+    pub fn contains(&self, name: &QName) -> bool {
+        self.nodes.contains_key(name)
+    }
+
+    /// True if `name` is a simple type definition, including the built-in ones.
+    pub fn is_simple(&self, name: &QName) -> bool {
+        self.nodes.get(name).is_some_and(|node| node.simple)
+    }
+
+    /// The derivation methods by which derived types may not replace `name` in an instance.
+    pub fn block(&self, name: &QName) -> DerivationSet {
+        self.nodes
+            .get(name)
+            .map(|node| node.block)
+            .unwrap_or_default()
+    }
+
+    /// The base type of `name`. `None` for `xs:anyType` and for unknown types.
+    pub fn base_type(&self, name: &QName) -> Option<&QName> {
+        self.nodes
+            .get(name)
+            .and_then(|node| node.base.as_ref())
+            .map(|(base, _)| base)
+    }
+
+    /// How `name` is derived from its base type.
+    pub fn derivation_method(&self, name: &QName) -> Option<DerivationMethod> {
+        self.nodes
+            .get(name)
+            .and_then(|node| node.base.as_ref())
+            .map(|(_, method)| *method)
+    }
+
+    /// The base type chain of `name`, starting with its base type and usually ending with
+    /// `xs:anyType`.
+    pub fn ancestors<'a>(&'a self, name: &QName) -> impl Iterator<Item = &'a QName> + 'a {
+        std::iter::successors(self.base_type(name), move |n| self.base_type(n))
+    }
+
+    /// Every type derived from `name`, directly or indirectly, breadth first.
+    pub fn descendants(&self, name: &QName) -> Vec<&QName> {
+        let mut r = Vec::new();
+        let mut queue = VecDeque::from([name]);
+
+        while let Some(current) = queue.pop_front() {
+            for subtype in self.subtypes.get(current).into_iter().flatten() {
+                r.push(subtype);
+                queue.push_back(subtype);
+            }
+        }
+
+        r
+    }
+
+    /// True if `derived` is `base` or derived from it without a step that uses a method of
+    /// `blocked` or of the `block` of `base`, or that is forbidden by `final`. A type is also
+    /// derived from a union, if it is derived from one of the member types.
+    ///
+    /// `blocked` is usually the `block` of an element declaration with type `base`, e.g. when
+    /// checking `xsi:type`.
+    pub fn is_derived_from(&self, derived: &QName, base: &QName, blocked: DerivationSet) -> bool {
+        let Some(target) = self.nodes.get(base) else {
+            return false;
+        };
+
+        self.derives(
+            derived,
+            base,
+            &blocked.union(&target.block),
+            &mut HashSet::new(),
+        )
+    }
+
+    fn derives<'a>(
+        &'a self,
+        derived: &QName,
+        base: &'a QName,
+        blocked: &DerivationSet,
+        unions: &mut HashSet<&'a QName>,
+    ) -> bool {
+        let mut current = derived;
+        loop {
+            if current == base {
+                return true;
+            }
+
+            let Some((next, method)) = self.nodes.get(current).and_then(|node| {
+                node.base
+                    .as_ref()
+                    .filter(|_| !node.final_violated)
+                    .map(|(next, method)| (next, *method))
+            }) else {
+                break;
+            };
+
+            // A list or union is a restriction of `xs:anySimpleType` as far as `block` is
+            // concerned.
+            let method = match method {
+                DerivationMethod::List | DerivationMethod::Union => DerivationMethod::Restriction,
+                method => method,
+            };
+            if blocked.contains(method) {
+                break;
+            }
+
+            current = next;
+        }
+
+        unions.insert(base)
+            && self.nodes[base]
+                .members
+                .iter()
+                .any(|member| self.derives(derived, member, blocked, unions))
+    }
+
+    /// The primitive type `name` is derived from. This works for complex types with simple
+    /// content as well, e.g. `xs:string` for an extension of `xs:token`.
+    pub fn primitive_type(&self, name: &QName) -> Option<&'static BuiltinType> {
+        std::iter::once(name)
+            .chain(self.ancestors(name))
+            .find_map(BuiltinType::lookup_qname)
+            .and_then(|builtin| builtin.primitive())
+    }
+}
+
+fn any_type() -> QName {
+    QName::new(Some(XSD_NAMESPACE), "anyType")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Schema;
+
+    const XML: &str = r##"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:t" xmlns="urn:t" blockDefault="restriction">
+    <xs:complexType name="Referenceable" abstract="true">
+        <xs:attribute name="id" type="xs:ID"/>
+    </xs:complexType>
+    <xs:complexType name="Nameable" abstract="true">
+        <xs:complexContent>
+            <xs:extension base="Referenceable">
+                <xs:attribute name="name" type="xs:string"/>
+            </xs:extension>
+        </xs:complexContent>
+    </xs:complexType>
+    <xs:complexType name="Lane" abstract="true" block="">
+        <xs:complexContent>
+            <xs:extension base="Nameable"/>
+        </xs:complexContent>
+    </xs:complexType>
+    <xs:complexType name="Track" final="#all">
+        <xs:complexContent>
+            <xs:extension base="Lane">
+                <xs:attribute name="loaded" type="xs:boolean"/>
+            </xs:extension>
+        </xs:complexContent>
+    </xs:complexType>
+    <xs:complexType name="Channel">
+        <xs:complexContent>
+            <xs:restriction base="Lane"/>
+        </xs:complexContent>
+    </xs:complexType>
+    <xs:complexType name="SubTrack">
+        <xs:complexContent>
+            <xs:extension base="Track"/>
+        </xs:complexContent>
+    </xs:complexType>
+    <xs:complexType name="Label">
+        <xs:simpleContent>
+            <xs:extension base="xs:token"/>
+        </xs:simpleContent>
+    </xs:complexType>
+    <xs:simpleType name="midi-16384">
+        <xs:restriction base="xs:positiveInteger">
+            <xs:minInclusive value="1"/>
+            <xs:maxInclusive value="16384"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="number-or-normal">
+        <xs:union memberTypes="xs:decimal"/>
+    </xs:simpleType>
+    <xs:simpleType name="numbers" final="list union">
+        <xs:list itemType="xs:decimal"/>
+    </xs:simpleType>
+    <xs:simpleType name="lists">
+        <xs:list itemType="numbers"/>
+    </xs:simpleType>
+    <xs:simpleType name="number-or-numbers">
+        <xs:union memberTypes="xs:decimal numbers"/>
+    </xs:simpleType>
+    <xs:simpleType name="a">
+        <xs:restriction base="b"/>
+    </xs:simpleType>
+    <xs:simpleType name="b">
+        <xs:restriction base="a"/>
+    </xs:simpleType>
+</xs:schema>
+"##;
+
+    fn t(name: &str) -> QName {
+        QName::new(Some("urn:t"), name)
+    }
+
+    fn xs(name: &str) -> QName {
+        QName::new(Some(XSD_NAMESPACE), name)
+    }
+
+    fn graph() -> DerivationGraph {
+        SchemaSet::new(vec![Schema::parse(XML).unwrap()]).derivation_graph()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(DerivationSet::parse("#all"), Some(DerivationSet::all()));
+        assert_eq!(
+            DerivationSet::parse(" extension  restriction "),
+            Some(DerivationSet {
+                extension: true,
+                restriction: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(DerivationSet::parse(""), Some(DerivationSet::default()));
+        assert_eq!(DerivationSet::parse("extend"), None);
+
+        let schema = Schema::parse(XML).unwrap();
+        assert!(schema.block_default.restriction);
+        assert!(schema.complex_types[0].r#abstract);
+        assert_eq!(
+            schema.complex_types[2].block,
+            Some(DerivationSet::default())
+        );
+        assert_eq!(schema.complex_types[3].r#final, Some(DerivationSet::all()));
+
+        let xml = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:element name="e" block="nothing"/>
+</xs:schema>"#;
+        assert!(matches!(
+            Schema::parse(xml),
+            Err(Error::InvalidSchema { path, .. }) if path == "schema/element[e]"
+        ));
+    }
+
+    #[test]
+    fn hierarchy() {
+        let graph = graph();
+
+        assert_eq!(graph.base_type(&t("Track")), Some(&t("Lane")));
+        assert_eq!(
+            graph.derivation_method(&t("Track")),
+            Some(DerivationMethod::Extension)
+        );
+        assert_eq!(
+            graph.derivation_method(&t("Channel")),
+            Some(DerivationMethod::Restriction)
+        );
+        assert_eq!(
+            graph.ancestors(&t("Track")).collect::<Vec<_>>(),
+            vec![
+                &t("Lane"),
+                &t("Nameable"),
+                &t("Referenceable"),
+                &xs("anyType")
+            ]
+        );
+        assert_eq!(
+            graph.descendants(&t("Referenceable")),
+            vec![
+                &t("Nameable"),
+                &t("Lane"),
+                &t("Channel"),
+                &t("Track"),
+                &t("SubTrack")
+            ]
+        );
+        assert_eq!(graph.base_type(&xs("anyType")), None);
+        assert_eq!(
+            graph.derivation_method(&t("number-or-normal")),
+            Some(DerivationMethod::Union)
+        );
+        assert_eq!(
+            graph.derivation_method(&t("numbers")),
+            Some(DerivationMethod::List)
+        );
+        assert_eq!(
+            graph.ancestors(&t("number-or-normal")).collect::<Vec<_>>(),
+            vec![&xs("anySimpleType"), &xs("anyType")]
+        );
+
+        assert!(graph.is_simple(&t("midi-16384")));
+        assert!(graph.is_simple(&xs("int")));
+        assert!(!graph.is_simple(&t("Label")));
+
+        assert_eq!(
+            graph.primitive_type(&t("midi-16384")).unwrap().name,
+            "decimal"
+        );
+        assert_eq!(graph.primitive_type(&t("Label")).unwrap().name, "string");
+        assert_eq!(graph.primitive_type(&t("Track")), None);
+    }
+
+    #[test]
+    fn derived_from() {
+        let graph = graph();
+        let none = DerivationSet::default();
+        let extension = DerivationSet {
+            extension: true,
+            ..Default::default()
+        };
+
+        assert!(graph.is_derived_from(&t("Track"), &t("Track"), none));
+        assert!(graph.is_derived_from(&t("Track"), &t("Lane"), none));
+        assert!(graph.is_derived_from(&t("Track"), &xs("anyType"), none));
+        assert!(!graph.is_derived_from(&t("Lane"), &t("Track"), none));
+        assert!(!graph.is_derived_from(&t("Track"), &t("Lane"), extension));
+
+        // Referenceable blocks restriction through blockDefault, Lane does not.
+        assert!(graph.is_derived_from(&t("Channel"), &t("Lane"), none));
+        assert!(!graph.is_derived_from(&t("Channel"), &t("Referenceable"), none));
+        assert!(graph.is_derived_from(&t("Track"), &t("Referenceable"), none));
+
+        // Track is final, so SubTrack is not a valid derivation.
+        assert!(!graph.is_derived_from(&t("SubTrack"), &t("Track"), none));
+
+        assert!(graph.is_derived_from(&t("midi-16384"), &xs("integer"), none));
+        assert!(graph.is_derived_from(&xs("int"), &t("number-or-normal"), none));
+        assert!(!graph.is_derived_from(&xs("string"), &t("number-or-normal"), none));
+    }
+
+    #[test]
+    fn diagnostics() {
+        let graph = graph();
+
+        let codes: Vec<&str> = graph.diagnostics.iter().map(|d| d.code.as_str()).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "st-props-correct.4.2.1",
+                "st-props-correct.4.2.2",
+                "st-props-correct.2",
+                "cos-ct-extends.1.1"
+            ]
+        );
+        assert_eq!(
+            graph.diagnostics[0].path,
+            "schema/simpleType[lists]".to_string()
+        );
+        assert_eq!(
+            graph.diagnostics[3].path,
+            "schema/complexType[SubTrack]".to_string()
+        );
+        assert_eq!(
+            graph.ancestors(&t("a")).count() + graph.ancestors(&t("b")).count(),
+            1
+        );
+    }
+}
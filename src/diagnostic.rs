@@ -0,0 +1,45 @@
+use std::fmt::Display;
+
+use crate::source_span::SourceSpan;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Severity {
+    /// Something was skipped or interpreted leniently, the result is still usable.
+    Warning,
+
+    /// The schema is wrong at this point, the result may be incomplete.
+    Error,
+}
+
+/// A problem found while processing a schema, that did not stop the processing.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+
+    /// A short, stable identifier for the kind of problem, e.g. `unparsable-facet-value`.
+    pub code: String,
+    pub message: String,
+
+    /// The component the problem was found in, e.g. `schema/simpleType[midi-16]/restriction`.
+    pub path: String,
+    pub span: Option<SourceSpan>,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+
+        if let Some(span) = &self.span {
+            write!(f, "{}: ", span)?;
+        }
+
+        write!(
+            f,
+            "{}[{}]: {} ({})",
+            severity, self.code, self.message, self.path
+        )
+    }
+}
@@ -0,0 +1,214 @@
+use std::{collections::HashMap, io::Read};
+
+use xml::{
+    common::{Position as _, TextPosition},
+    namespace::Namespace,
+    reader::{EventReader, XmlEvent},
+};
+use xmltree::{Element, XMLNode};
+
+use crate::source_span::Position;
+
+/// Namespace of the XML Schema vocabulary itself.
+pub const XSD_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema";
+
+/// Build an attribute key for a namespace qualified attribute.
+///
+/// `xmltree` keys attributes by their local name only, which loses the namespace of attributes
+/// like `vc:minVersion` or `xml:lang`. Qualified attributes are therefore stored using the
+/// `{namespace}local` notation, unqualified attributes keep their plain name.
+pub fn expanded_name(namespace: &str, local_name: &str) -> String {
+    format!("{{{}}}{}", namespace, local_name)
+}
+
+/// Resolve a qualified name like `xs:decimal` against the namespaces in scope of `element`
+/// and return it in `{namespace}local` notation. Returns `None` for undeclared prefixes.
+pub fn resolve_qname(element: &Element, qname: &str) -> Option<String> {
+    let (prefix, local_name) = match qname.split_once(':') {
+        Some((prefix, local_name)) => (prefix, local_name),
+        None => ("", qname),
+    };
+
+    let namespace = element
+        .namespaces
+        .as_ref()
+        .and_then(|namespaces| namespaces.get(prefix));
+
+    match namespace {
+        Some(namespace) if !namespace.is_empty() => Some(expanded_name(namespace, local_name)),
+        _ if prefix.is_empty() => Some(local_name.to_string()),
+        _ => None,
+    }
+}
+
+/// Where the elements of a document start and end, recorded by [`parse`] next to the tree.
+///
+/// Elements are moved out of their parents while a schema is read, so they are identified by
+/// the buffer of their name instead of their address. The buffer moves with the element and is
+/// distinct for every element of the tree, because XML names are never empty.
+#[derive(Debug, Default, Clone)]
+pub struct Positions {
+    positions: HashMap<usize, (Position, Position)>,
+}
+
+impl Positions {
+    /// The start and end position of `element`.
+    pub fn get(&self, element: &Element) -> Option<(Position, Position)> {
+        self.positions.get(&Self::key(element)).copied()
+    }
+
+    /// Like [`Positions::get`], but forget the position, so that it cannot be found for an
+    /// element that reuses the buffer after `element` is dropped.
+    pub fn take(&mut self, element: &Element) -> Option<(Position, Position)> {
+        self.positions.remove(&Self::key(element))
+    }
+
+    fn key(element: &Element) -> usize {
+        element.name.as_ptr() as usize
+    }
+}
+
+/// Take the first child element with one of the given local names. Unlike
+/// `Element::take_child` this keeps children of different kinds in document order.
+pub(crate) fn take_next_child(element: &mut Element, names: &[&str]) -> Option<Element> {
+    let index = element.children.iter().position(|node| match node {
+        XMLNode::Element(child) => names.contains(&child.name.as_str()),
+        _ => false,
+    })?;
+
+    match element.children.remove(index) {
+        XMLNode::Element(child) => Some(child),
+        _ => None,
+    }
+}
+
+/// Parse a document into an element tree.
+///
+/// Unlike `xmltree::Element::parse` this keeps the namespace of qualified attributes and
+/// records the position of every element, see [`Positions`].
+pub(crate) fn parse<R: Read>(xml: R) -> Result<(Element, Positions), xml::reader::Error> {
+    let mut reader = EventReader::new(xml);
+    let mut stack: Vec<(Element, Position)> = Vec::new();
+    let mut positions = Positions::default();
+
+    loop {
+        match reader.next()? {
+            XmlEvent::StartElement {
+                name,
+                attributes,
+                namespace,
+            } => {
+                let mut attr_map = HashMap::new();
+                for attr in attributes {
+                    let key = match attr.name.namespace {
+                        Some(ns) => expanded_name(&ns, &attr.name.local_name),
+                        None => attr.name.local_name,
+                    };
+                    attr_map.insert(key, attr.value);
+                }
+
+                let element = Element {
+                    prefix: name.prefix,
+                    namespace: name.namespace,
+                    namespaces: to_namespaces(namespace),
+                    name: name.local_name,
+                    attributes: attr_map,
+                    children: Vec::new(),
+                };
+                stack.push((element, to_position(reader.position())));
+            }
+            XmlEvent::EndElement { .. } => {
+                // The reader guarantees balanced tags, so there is always an open element here.
+                let (element, start) = stack.pop().expect("unbalanced end element");
+                positions.positions.insert(
+                    Positions::key(&element),
+                    (start, to_position(reader.position())),
+                );
+
+                match stack.last_mut() {
+                    Some((parent, _)) => parent.children.push(XMLNode::Element(element)),
+                    None => return Ok((element, positions)),
+                }
+            }
+            XmlEvent::Characters(s) => push_node(&mut stack, XMLNode::Text(s)),
+            XmlEvent::CData(s) => push_node(&mut stack, XMLNode::CData(s)),
+            XmlEvent::Comment(s) => push_node(&mut stack, XMLNode::Comment(s)),
+            XmlEvent::ProcessingInstruction { name, data } => {
+                push_node(&mut stack, XMLNode::ProcessingInstruction(name, data))
+            }
+            XmlEvent::EndDocument => unreachable!("document ended before the root element"),
+            _ => {}
+        }
+    }
+}
+
+fn to_position(position: TextPosition) -> Position {
+    Position {
+        line: position.row + 1,
+        column: position.column + 1,
+    }
+}
+
+fn to_namespaces(namespace: Namespace) -> Option<Namespace> {
+    if namespace.is_essentially_empty() {
+        None
+    } else {
+        Some(namespace)
+    }
+}
+
+fn push_node(stack: &mut [(Element, Position)], node: XMLNode) {
+    if let Some((parent, _)) = stack.last_mut() {
+        parent.children.push(node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qualified_attributes() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:vc="http://www.w3.org/2007/XMLSchema-versioning">
+    <xs:element name="a" vc:minVersion="1.1" type="xs:decimal"/>
+</xs:schema>
+"#;
+
+        let (mut root, _) = parse(xml.as_bytes()).unwrap();
+        let element = root.take_child("element").unwrap();
+
+        assert_eq!(element.attributes["name"], "a".to_string());
+        assert_eq!(
+            element.attributes["{http://www.w3.org/2007/XMLSchema-versioning}minVersion"],
+            "1.1".to_string()
+        );
+        assert_eq!(
+            resolve_qname(&element, "xs:decimal"),
+            Some("{http://www.w3.org/2001/XMLSchema}decimal".to_string())
+        );
+        assert_eq!(resolve_qname(&element, "foo:bar"), None);
+    }
+
+    #[test]
+    fn positions() {
+        let xml = "<a>\n  <b>\n    <c/>\n  </b>\n</a>";
+
+        let (mut root, mut positions) = parse(xml.as_bytes()).unwrap();
+        let mut b = root.take_child("b").unwrap();
+        let c = b.take_child("c").unwrap();
+        assert!(b.attributes.is_empty());
+
+        let (start, end) = positions.take(&b).unwrap();
+        assert_eq!(start, Position { line: 2, column: 3 });
+        assert_eq!(end, Position { line: 4, column: 3 });
+
+        let (start, end) = positions.get(&c).unwrap();
+        assert_eq!(start, Position { line: 3, column: 5 });
+        assert_eq!(start, end);
+
+        assert!(positions.take(&c).is_some());
+        assert!(positions.take(&c).is_none());
+        assert!(positions.get(&Element::new("c")).is_none());
+    }
+}
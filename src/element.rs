@@ -1,37 +1,145 @@
-use crate::prelude::{Annotation, ComplexType, Occurs};
+use xmltree::XMLNode;
 
-#[derive(Debug, Default)]
+use crate::{
+    derivation::DerivationSet,
+    error::Error,
+    identity_constraint::IdentityConstraint,
+    parse_context::ParseContext,
+    prelude::{Annotation, ComplexType, Occurs, SimpleType},
+    source_span::SourceSpan,
+};
+
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Element {
     pub name: String,
     pub r#type: String,
+    pub reference: Option<String>,
+
+    /// `qualified` or `unqualified`, overrides `elementFormDefault` for a local element.
+    pub form: Option<String>,
+
+    /// The qualified name of the head element of the substitution group this element belongs
+    /// to. XSD 1.1 allows a whitespace separated list of heads.
+    pub substitution_group: Option<String>,
+
+    /// Abstract elements cannot appear in an instance, only members of their substitution
+    /// group can.
+    pub r#abstract: bool,
+
+    /// Instances may set `xsi:nil` to leave the element empty, even if its type requires
+    /// content.
+    pub nillable: bool,
+
+    /// The value of an element with simple content that is empty in an instance.
+    pub default: Option<String>,
+
+    /// Like `default`, but a non-empty value must also be equal to it.
+    pub fixed: Option<String>,
+
+    /// `extension`, `restriction` and `substitution` forbid the matching replacements of the
+    /// element in an instance. `None` if the attribute is not given, then `blockDefault` of
+    /// the schema applies.
+    pub block: Option<DerivationSet>,
+
+    /// Forbids using the element as head of a substitution group of elements whose type is
+    /// derived by the given methods. `None` if the attribute is not given, then
+    /// `finalDefault` of the schema applies.
+    pub r#final: Option<DerivationSet>,
 
     pub annotations: Vec<Annotation>,
     pub occurs: Vec<Occurs>,
     pub complex_types: Vec<ComplexType>,
+
+    /// The anonymous simple type of the element, `<xs:simpleType>` inside it.
+    pub simple_type: Option<Box<SimpleType>>,
+
+    /// `<xs:unique>`, `<xs:key>` and `<xs:keyref>`, in document order.
+    pub identity_constraints: Vec<IdentityConstraint>,
+
+    pub span: Option<SourceSpan>,
 }
 
 impl Element {
-    pub fn read(element: &mut xmltree::Element) -> Self {
-        let mut r = Element::default();
+    /// True if the type of the element is defined inside it, by `<xs:complexType>` or
+    /// `<xs:simpleType>`.
+    pub fn has_anonymous_type(&self) -> bool {
+        !self.complex_types.is_empty() || self.simple_type.is_some()
+    }
 
-        r.occurs = Occurs::read(element);
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut xmltree::Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
+
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(
+        element: &mut xmltree::Element,
+        ctx: &mut ParseContext,
+    ) -> Result<Self, Error> {
+        let mut r = Element {
+            span: ctx.span(element),
+            name: element.attributes.remove("name").unwrap_or_default(),
+            r#type: element.attributes.remove("type").unwrap_or_default(),
+            reference: element.attributes.remove("ref"),
+            form: element.attributes.remove("form"),
+            substitution_group: element.attributes.remove("substitutionGroup"),
+            r#abstract: element
+                .attributes
+                .remove("abstract")
+                .is_some_and(|v| v == "true" || v == "1"),
+            nillable: element
+                .attributes
+                .remove("nillable")
+                .is_some_and(|v| v == "true" || v == "1"),
+            default: element.attributes.remove("default"),
+            fixed: element.attributes.remove("fixed"),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope(
+            "element",
+            Some(r.reference.as_deref().unwrap_or(&r.name)),
+            &r.span,
+        );
+        r.occurs = Occurs::read_with_context(element, ctx);
+        r.block = DerivationSet::read(element, "block", ctx)?;
+        r.r#final = DerivationSet::read(element, "final", ctx)?;
 
         while let Some(mut annotation) = element.take_child("annotation") {
-            r.annotations.push(Annotation::read(&mut annotation));
+            r.annotations
+                .push(Annotation::read_with_context(&mut annotation, ctx)?);
         }
 
         while let Some(mut complex_type) = element.take_child("complexType") {
-            r.complex_types.push(ComplexType::read(&mut complex_type));
+            if !r.r#type.is_empty() || !r.complex_types.is_empty() {
+                return Err(ctx.invalid("only one of type or an anonymous type is allowed"));
+            }
+
+            r.complex_types
+                .push(ComplexType::read_with_context(&mut complex_type, ctx)?);
         }
 
-        if element.attributes.contains_key("name") {
-            r.name = element.attributes["name"].to_string();
+        if let Some(mut simple_type) = element.take_child("simpleType") {
+            if !r.r#type.is_empty() || r.has_anonymous_type() {
+                return Err(ctx.invalid("only one of type or an anonymous type is allowed"));
+            }
+
+            r.simple_type = Some(Box::new(SimpleType::read_with_context(
+                &mut simple_type,
+                ctx,
+            )?));
         }
 
-        if element.attributes.contains_key("type") {
-            r.r#type = element.attributes["type"].to_string();
+        while let Some(index) = element.children.iter().position(|c| {
+            c.as_element()
+                .is_some_and(|c| matches!(c.name.as_str(), "unique" | "key" | "keyref"))
+        }) {
+            if let XMLNode::Element(mut constraint) = element.children.remove(index) {
+                r.identity_constraints
+                    .push(IdentityConstraint::read_with_context(&mut constraint, ctx)?);
+            }
         }
 
-        r
+        ctx.leave(element);
+        Ok(r)
     }
 }
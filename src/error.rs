@@ -0,0 +1,79 @@
+use std::{fmt::Display, path::PathBuf};
+
+/// Everything that can go wrong while loading a schema.
+#[derive(Debug)]
+pub enum Error {
+    /// A schema file could not be read from disk.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    /// A schema could not be downloaded.
+    Http { url: String, source: reqwest::Error },
+
+    /// A resolver has no schema document for the given location.
+    NotFound {
+        namespace: Option<String>,
+        location: String,
+    },
+
+    /// The document is not well-formed XML.
+    Xml(xml::reader::Error),
+
+    /// The document is well-formed XML, but not a valid schema. `path` names the component the
+    /// problem was found in, e.g. `schema/simpleType[font-size]`.
+    InvalidSchema { path: String, message: String },
+
+    /// The document at `uri` is well-formed XML, but not an OASIS XML catalog.
+    InvalidCatalog { uri: String, message: String },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io { path, source } => {
+                write!(f, "Could not read '{}'. {}", path.display(), source)
+            }
+            Error::Http { url, source } => write!(f, "Could not download '{}'. {}", url, source),
+            Error::NotFound {
+                namespace: Some(namespace),
+                location,
+            } => write!(
+                f,
+                "No schema document found for '{}' (namespace '{}')",
+                location, namespace
+            ),
+            Error::NotFound {
+                namespace: None,
+                location,
+            } => write!(f, "No schema document found for '{}'", location),
+            Error::Xml(source) => write!(f, "Could not parse xml. {}", source),
+            Error::InvalidSchema { path, message } => {
+                write!(f, "Invalid schema at {}: {}", path, message)
+            }
+            Error::InvalidCatalog { uri, message } => {
+                write!(f, "Invalid catalog '{}': {}", uri, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Http { source, .. } => Some(source),
+            Error::Xml(source) => Some(source),
+            Error::NotFound { .. } | Error::InvalidSchema { .. } | Error::InvalidCatalog { .. } => {
+                None
+            }
+        }
+    }
+}
+
+impl From<xml::reader::Error> for Error {
+    fn from(value: xml::reader::Error) -> Self {
+        Error::Xml(value)
+    }
+}
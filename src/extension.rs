@@ -1,31 +1,78 @@
-use crate::prelude::{Attribute, AttributeGroup};
+use crate::{
+    all::All,
+    error::Error,
+    parse_context::ParseContext,
+    prelude::{Any, Attribute, AttributeGroup, Choice, Group, Sequence},
+    source_span::SourceSpan,
+};
 use xmltree::Element;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Extension {
     pub base: String,
 
     pub attributes: Vec<Attribute>,
     pub attribute_groups: Vec<AttributeGroup>,
+    pub any_attribute: Option<Any>,
+
+    /// Content added to the content model of the base type, only inside `complexContent`.
+    pub sequences: Vec<Sequence>,
+    pub choices: Vec<Choice>,
+    pub groups: Vec<Group>,
+    pub all: Option<All>,
+
+    pub span: Option<SourceSpan>,
 }
 
 impl Extension {
-    pub fn read(element: &mut Element) -> Self {
-        let mut r = Extension::default();
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
+
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(element: &mut Element, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let mut r = Extension {
+            span: ctx.span(element),
+            base: element.attributes.remove("base").unwrap_or_default(),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope("extension", Some(&r.base), &r.span);
+
+        while let Some(mut sequence) = element.take_child("sequence") {
+            r.sequences
+                .push(Sequence::read_with_context(&mut sequence, ctx)?);
+        }
 
-        if element.attributes.contains_key("base") {
-            r.base = element.attributes["base"].to_string();
+        while let Some(mut choice) = element.take_child("choice") {
+            r.choices.push(Choice::read_with_context(&mut choice, ctx)?);
+        }
+
+        while let Some(mut group) = element.take_child("group") {
+            r.groups.push(Group::read_with_context(&mut group, ctx)?);
+        }
+
+        if let Some(mut all) = element.take_child("all") {
+            r.all = Some(All::read_with_context(&mut all, ctx)?);
         }
 
         while let Some(mut attribute) = element.take_child("attribute") {
-            r.attributes.push(Attribute::read(&mut attribute));
+            r.attributes
+                .push(Attribute::read_with_context(&mut attribute, ctx)?);
         }
 
         while let Some(mut attribute_group) = element.take_child("attributeGroup") {
-            r.attribute_groups
-                .push(AttributeGroup::read(&mut attribute_group));
+            r.attribute_groups.push(AttributeGroup::read_with_context(
+                &mut attribute_group,
+                ctx,
+            )?);
+        }
+
+        if let Some(mut any_attribute) = element.take_child("anyAttribute") {
+            r.any_attribute = Some(Any::read_with_context(&mut any_attribute, ctx)?);
         }
 
-        r
+        ctx.leave(element);
+        Ok(r)
     }
 }
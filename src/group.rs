@@ -1,21 +1,119 @@
-use crate::prelude::Occurs;
+use crate::{
+    all::All,
+    choice::Choice,
+    error::Error,
+    parse_context::ParseContext,
+    prelude::{Annotation, Occurs, Sequence},
+    source_span::SourceSpan,
+};
 
-#[derive(Debug, Default, PartialEq, PartialOrd, Clone)]
+/// Either a named model group definition (`<xs:group name="editorial">`) or a reference to one
+/// (`<xs:group ref="editorial"/>`).
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct Group {
+    pub name: String,
     pub reference: String,
     pub occurs: Vec<Occurs>,
+
+    pub annotations: Vec<Annotation>,
+    pub sequences: Vec<Sequence>,
+    pub choices: Vec<Choice>,
+    pub all: Option<All>,
+
+    pub span: Option<SourceSpan>,
 }
 
 impl Group {
-    pub fn read(element: &mut xmltree::Element) -> Self {
-        let mut r = Group::default();
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut xmltree::Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
 
-        if element.attributes.contains_key("ref") {
-            r.reference = element.attributes["ref"].to_string();
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(
+        element: &mut xmltree::Element,
+        ctx: &mut ParseContext,
+    ) -> Result<Self, Error> {
+        let mut r = Group {
+            span: ctx.span(element),
+            name: element.attributes.remove("name").unwrap_or_default(),
+            reference: element.attributes.remove("ref").unwrap_or_default(),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope(
+            "group",
+            Some(if r.name.is_empty() {
+                &r.reference
+            } else {
+                &r.name
+            }),
+            &r.span,
+        );
+        r.occurs = Occurs::read_with_context(element, ctx);
+
+        while let Some(mut annotation) = element.take_child("annotation") {
+            r.annotations
+                .push(Annotation::read_with_context(&mut annotation, ctx)?);
         }
 
-        r.occurs = Occurs::read(element);
+        while let Some(mut sequence) = element.take_child("sequence") {
+            r.sequences
+                .push(Sequence::read_with_context(&mut sequence, ctx)?);
+        }
+
+        while let Some(mut choice) = element.take_child("choice") {
+            r.choices.push(Choice::read_with_context(&mut choice, ctx)?);
+        }
+
+        if let Some(mut all) = element.take_child("all") {
+            r.all = Some(All::read_with_context(&mut all, ctx)?);
+        }
 
-        r
+        ctx.leave(element);
+        Ok(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{choice::ChoiceItems, schema::Schema};
+
+    #[test]
+    fn group_definition() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+	<xs:group name="editorial-voice-direction">
+		<xs:annotation>
+			<xs:documentation>The editorial-voice-direction group supports the common combination of editorial and voice information for a direction element.</xs:documentation>
+		</xs:annotation>
+		<xs:sequence>
+			<xs:group ref="footnote" minOccurs="0"/>
+			<xs:group ref="level" minOccurs="0"/>
+			<xs:group ref="voice" minOccurs="0"/>
+		</xs:sequence>
+	</xs:group>
+	<xs:group name="full-note">
+		<xs:choice>
+			<xs:element name="pitch" type="pitch"/>
+			<xs:group ref="unpitched-or-rest"/>
+		</xs:choice>
+	</xs:group>
+</xs:schema>
+"#;
+
+        let item = Schema::parse(xml).unwrap();
+
+        assert_eq!(item.groups.len(), 2);
+        assert_eq!(item.groups[0].name, "editorial-voice-direction".to_string());
+        assert_eq!(item.groups[0].annotations.len(), 1);
+        assert_eq!(item.groups[0].sequences[0].groups.len(), 3);
+
+        assert_eq!(item.groups[1].choices[0].elements.len(), 2);
+        match &item.groups[1].choices[0].elements[1] {
+            ChoiceItems::Group(group) => {
+                assert_eq!(group.reference, "unpitched-or-rest".to_string());
+            }
+            _ => panic!("Expected group"),
+        }
     }
 }
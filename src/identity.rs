@@ -0,0 +1,537 @@
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use crate::{
+    element::Element,
+    identity_constraint::{IdentityConstraint, IdentityConstraintKind, XPath},
+    qname::QName,
+    schema_set::SchemaSet,
+    source_span::Position,
+};
+
+/// The values of the fields of the elements selected by a key or unique constraint.
+type Table = HashSet<Vec<String>>;
+
+/// Evaluates the identity constraints of an instance document while it is validated, see
+/// [`crate::prelude::Validator::validate_stream`]. Apart from the key tables, only the open
+/// elements are kept.
+pub(crate) struct IdentityTables<'a> {
+    set: &'a SchemaSet,
+
+    /// The names of the open elements, the innermost last.
+    names: Vec<QName>,
+
+    /// The constraints of the open elements.
+    scopes: Vec<Scope>,
+
+    /// Per open element, the key and unique tables of the element and its closed descendants,
+    /// which its keyrefs may refer to.
+    tables: Vec<HashMap<QName, Table>>,
+
+    /// The compiled constraints, `None` if a selector or field does not compile, which
+    /// [`SchemaSet::check`] reports. Constraint names are unique within a namespace.
+    compiled: HashMap<QName, Option<Rc<Compiled>>>,
+}
+
+/// A problem with the identity constraints, reported by the validator.
+pub(crate) struct Problem {
+    pub code: &'static str,
+    pub message: String,
+
+    /// The path of the element the problem belongs to, `None` for the element being closed.
+    pub path: Option<String>,
+    pub start: Position,
+}
+
+struct Compiled {
+    kind: IdentityConstraintKind,
+    name: QName,
+    refer: Option<QName>,
+    selector: XPath,
+    fields: Vec<XPath>,
+}
+
+/// An identity constraint of an open element.
+struct Scope {
+    constraint: Rc<Compiled>,
+
+    /// The depth of the element the constraint is declared in.
+    depth: usize,
+
+    /// The selected elements that are still open, the innermost last.
+    selected: Vec<Selected>,
+    table: Table,
+
+    /// The values of a keyref.
+    references: Vec<Reference>,
+}
+
+struct Selected {
+    depth: usize,
+    start: Position,
+
+    /// The path of the element, kept for keyrefs which are checked later.
+    path: Option<String>,
+    fields: Vec<Field>,
+}
+
+#[derive(Clone, Default)]
+struct Field {
+    value: Option<String>,
+
+    /// The number of nodes the field selected.
+    matches: usize,
+
+    /// The depths of the selected elements whose value is not known yet.
+    pending: Vec<usize>,
+}
+
+struct Reference {
+    values: Vec<String>,
+    path: String,
+    start: Position,
+}
+
+impl<'a> IdentityTables<'a> {
+    pub fn new(set: &'a SchemaSet) -> Self {
+        IdentityTables {
+            set,
+            names: Vec::new(),
+            scopes: Vec::new(),
+            tables: Vec::new(),
+            compiled: HashMap::new(),
+        }
+    }
+
+    /// Whether a constraint applies to the open elements.
+    pub fn is_active(&self) -> bool {
+        !self.scopes.is_empty()
+    }
+
+    /// Whether an element is selected, so its attributes might be fields.
+    pub fn is_selecting(&self) -> bool {
+        self.scopes.iter().any(|s| !s.selected.is_empty())
+    }
+
+    /// Open an element. `declaration` is its element declaration and the index of the schema
+    /// document it is written in, if it has one. `path` are the steps to the element.
+    pub fn start(
+        &mut self,
+        name: &QName,
+        declaration: Option<(usize, &'a Element)>,
+        path: &[String],
+        start: Position,
+    ) {
+        self.names.push(name.clone());
+        self.tables.push(HashMap::new());
+        let depth = self.names.len() - 1;
+
+        if let Some((schema, element)) = declaration {
+            for constraint in element.identity_constraints.iter() {
+                if let Some(compiled) = self.compile(schema, constraint) {
+                    self.scopes.push(Scope {
+                        constraint: compiled,
+                        depth,
+                        selected: Vec::new(),
+                        table: Table::new(),
+                        references: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        for scope in self.scopes.iter_mut() {
+            let constraint = &scope.constraint;
+            if constraint.selector.matches(&self.names[scope.depth + 1..]) {
+                let keyref = constraint.kind == IdentityConstraintKind::KeyRef;
+                scope.selected.push(Selected {
+                    depth,
+                    start,
+                    path: keyref.then(|| path.concat()),
+                    fields: vec![Field::default(); constraint.fields.len()],
+                });
+            }
+
+            for selected in scope.selected.iter_mut() {
+                let relative = &self.names[selected.depth + 1..];
+                for (field, xpath) in selected.fields.iter_mut().zip(&constraint.fields) {
+                    if xpath.matches(relative) {
+                        field.matches += 1;
+                        field.pending.push(depth);
+                    }
+                }
+            }
+        }
+    }
+
+    /// An attribute of the element just opened, with its normalized value.
+    pub fn attribute(&mut self, name: &QName, value: &str) {
+        for scope in self.scopes.iter_mut() {
+            for selected in scope.selected.iter_mut() {
+                let relative = &self.names[selected.depth + 1..];
+                let fields = selected.fields.iter_mut().zip(&scope.constraint.fields);
+                for (field, xpath) in fields {
+                    if xpath.matches_attribute(relative, name) {
+                        field.matches += 1;
+                        field.value = Some(value.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Close the innermost element, whose normalized value is `value`, and return the
+    /// problems found with the elements it selected and the constraints declared in it.
+    pub fn end(&mut self, value: &str) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let Some(depth) = self.names.len().checked_sub(1) else {
+            return problems;
+        };
+
+        for scope in self.scopes.iter_mut() {
+            for selected in scope.selected.iter_mut() {
+                for field in selected.fields.iter_mut() {
+                    if field.pending.last() == Some(&depth) {
+                        field.pending.pop();
+                        field.value = Some(value.to_string());
+                    }
+                }
+            }
+
+            if scope.selected.last().is_some_and(|s| s.depth == depth) {
+                if let Some(selected) = scope.selected.pop() {
+                    scope.complete(selected, &mut problems);
+                }
+            }
+        }
+
+        let mut tables = self.tables.pop().unwrap_or_default();
+        let first = self
+            .scopes
+            .iter()
+            .rposition(|s| s.depth < depth)
+            .map_or(0, |i| i + 1);
+        let closed: Vec<Scope> = self.scopes.drain(first..).collect();
+
+        for scope in closed.iter() {
+            if scope.constraint.kind != IdentityConstraintKind::KeyRef {
+                tables
+                    .entry(scope.constraint.name.clone())
+                    .or_default()
+                    .extend(scope.table.iter().cloned());
+            }
+        }
+
+        for scope in closed.iter() {
+            let (Some(refer), IdentityConstraintKind::KeyRef) =
+                (&scope.constraint.refer, scope.constraint.kind)
+            else {
+                continue;
+            };
+
+            let table = tables.get(refer);
+            for reference in scope.references.iter() {
+                if table.is_none_or(|t| !t.contains(&reference.values)) {
+                    problems.push(Problem {
+                        code: "cvc-identity-constraint.4.3",
+                        message: format!(
+                            "{} of keyref '{}' does not match a value of '{}'",
+                            tuple(&reference.values),
+                            scope.constraint.name.name,
+                            refer.name
+                        ),
+                        path: Some(reference.path.clone()),
+                        start: reference.start,
+                    });
+                }
+            }
+        }
+
+        if let Some(parent) = self.tables.last_mut() {
+            for (name, table) in tables {
+                parent.entry(name).or_default().extend(table);
+            }
+        }
+        self.names.pop();
+
+        problems
+    }
+
+    fn compile(
+        &mut self,
+        schema: usize,
+        constraint: &'a IdentityConstraint,
+    ) -> Option<Rc<Compiled>> {
+        let set = self.set;
+        let document = &set.schemas[schema];
+
+        self.compiled
+            .entry(QName::new(
+                document.target_namespace.as_deref(),
+                &constraint.name,
+            ))
+            .or_insert_with(|| {
+                let namespaces = &constraint.namespaces;
+                let fields = constraint
+                    .fields
+                    .iter()
+                    .map(|f| XPath::new(f, true, namespaces))
+                    .collect::<Result<Vec<_>, _>>()
+                    .ok()?;
+
+                Some(Rc::new(Compiled {
+                    kind: constraint.kind,
+                    name: QName::new(document.target_namespace.as_deref(), &constraint.name),
+                    refer: match &constraint.refer {
+                        Some(refer) => Some(document.resolve_qname(refer)?),
+                        None => None,
+                    },
+                    selector: XPath::new(&constraint.selector, false, namespaces).ok()?,
+                    fields,
+                }))
+            })
+            .clone()
+    }
+}
+
+impl Scope {
+    /// Add the values of an element that was closed to the table of the constraint.
+    fn complete(&mut self, selected: Selected, problems: &mut Vec<Problem>) {
+        let constraint = &self.constraint;
+        let name = &constraint.name.name;
+        let mut report = |code, message| {
+            problems.push(Problem {
+                code,
+                message,
+                path: None,
+                start: selected.start,
+            })
+        };
+
+        if let Some(index) = selected.fields.iter().position(|f| f.matches > 1) {
+            report(
+                "cvc-identity-constraint.3",
+                format!(
+                    "field {} of '{}' selects more than one node",
+                    index + 1,
+                    name
+                ),
+            );
+            return;
+        }
+
+        let values: Option<Vec<String>> = selected.fields.into_iter().map(|f| f.value).collect();
+        let Some(values) = values else {
+            if constraint.kind == IdentityConstraintKind::Key {
+                report(
+                    "cvc-identity-constraint.4.2.1",
+                    format!("the element has no value for every field of key '{}'", name),
+                );
+            }
+            return;
+        };
+
+        match constraint.kind {
+            IdentityConstraintKind::KeyRef => self.references.push(Reference {
+                values,
+                path: selected.path.unwrap_or_default(),
+                start: selected.start,
+            }),
+            kind => {
+                let message = format!("duplicate {} of '{}'", tuple(&values), name);
+                if !self.table.insert(values) {
+                    let code = match kind {
+                        IdentityConstraintKind::Key => "cvc-identity-constraint.4.2.2",
+                        _ => "cvc-identity-constraint.4.1",
+                    };
+                    report(code, message);
+                }
+            }
+        }
+    }
+}
+
+/// `value 'a'` or `values ('a', 'b')` for messages.
+fn tuple(values: &[String]) -> String {
+    match values {
+        [value] => format!("value '{}'", value),
+        _ => format!(
+            "values ({})",
+            values
+                .iter()
+                .map(|v| format!("'{}'", v))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::{Schema, SchemaSet};
+
+    const XSD: &str = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:d="urn:daw" targetNamespace="urn:daw" elementFormDefault="qualified">
+    <xs:element name="project">
+        <xs:complexType>
+            <xs:sequence>
+                <xs:element name="track" minOccurs="0" maxOccurs="unbounded">
+                    <xs:complexType>
+                        <xs:sequence>
+                            <xs:element name="channel" minOccurs="0">
+                                <xs:complexType>
+                                    <xs:attribute name="id" type="xs:token"/>
+                                </xs:complexType>
+                            </xs:element>
+                        </xs:sequence>
+                        <xs:attribute name="id" type="xs:token"/>
+                        <xs:attribute name="name" type="xs:string"/>
+                    </xs:complexType>
+                </xs:element>
+                <xs:element name="lane" minOccurs="0" maxOccurs="unbounded">
+                    <xs:complexType>
+                        <xs:sequence>
+                            <xs:element name="target" type="xs:token" minOccurs="0"/>
+                        </xs:sequence>
+                        <xs:attribute name="track" type="xs:token"/>
+                    </xs:complexType>
+                </xs:element>
+            </xs:sequence>
+        </xs:complexType>
+        <xs:key name="trackId">
+            <xs:selector xpath="d:track | d:track/d:channel"/>
+            <xs:field xpath="@id"/>
+        </xs:key>
+        <xs:keyref name="laneTrack" refer="d:trackId">
+            <xs:selector xpath=".//d:lane"/>
+            <xs:field xpath="@track"/>
+        </xs:keyref>
+        <xs:keyref name="laneTarget" refer="d:trackId">
+            <xs:selector xpath="d:lane"/>
+            <xs:field xpath="d:target"/>
+        </xs:keyref>
+        <xs:unique name="trackName">
+            <xs:selector xpath="d:track"/>
+            <xs:field xpath="@name"/>
+        </xs:unique>
+    </xs:element>
+</xs:schema>
+"#;
+
+    fn validate(xml: &str) -> Vec<(String, String, u64)> {
+        let set = SchemaSet::new(vec![Schema::parse(XSD).unwrap()]);
+        assert!(set.check().is_empty(), "{:?}", set.check());
+
+        set.validate(xml)
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.code, d.path, d.span.unwrap().start.line))
+            .collect()
+    }
+
+    #[test]
+    fn keys_and_references() {
+        let xml = r#"<project xmlns="urn:daw">
+    <track id="t1" name="Bass"><channel id=" c1 "/></track>
+    <track id="t2"/>
+    <lane track="t1"/>
+    <lane track="c1"><target>t2</target></lane>
+    <lane/>
+</project>"#;
+        assert_eq!(validate(xml), vec![]);
+    }
+
+    #[test]
+    fn violations() {
+        let xml = r#"<project xmlns="urn:daw">
+    <track id="t1" name="Bass"/>
+    <track id="t1" name="Bass"/>
+    <track name="Lead"/>
+    <lane track="t1"/>
+    <lane track="t9"><target>t8</target></lane>
+</project>"#;
+
+        let diagnostics = validate(xml);
+        let codes: Vec<(&str, &str, u64)> = diagnostics
+            .iter()
+            .map(|(code, path, line)| (code.as_str(), path.as_str(), *line))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                ("cvc-identity-constraint.4.2.2", "/project/track[2]", 3),
+                ("cvc-identity-constraint.4.1", "/project/track[2]", 3),
+                ("cvc-identity-constraint.4.2.1", "/project/track[3]", 4),
+                ("cvc-identity-constraint.4.3", "/project/lane[2]", 6),
+                ("cvc-identity-constraint.4.3", "/project/lane[2]", 6),
+            ]
+        );
+    }
+
+    #[test]
+    fn prefixes_in_scope() {
+        // The prefix is only declared on the element declaration.
+        let xsd = XSD
+            .replace(
+                r#"<xs:element name="project">"#,
+                r#"<xs:element name="project" xmlns:p="urn:daw">"#,
+            )
+            .replace(r#"xpath="d:track""#, r#"xpath="p:track""#);
+        let set = SchemaSet::new(vec![Schema::parse(&xsd).unwrap()]);
+        assert!(set.check().is_empty(), "{:?}", set.check());
+
+        let xml = r#"<project xmlns="urn:daw">
+    <track id="t1" name="Bass"/>
+    <track id="t2" name="Bass"/>
+</project>"#;
+        let codes: Vec<String> = set
+            .validate(xml)
+            .unwrap()
+            .into_iter()
+            .map(|d| d.code)
+            .collect();
+        assert_eq!(codes, vec!["cvc-identity-constraint.4.1".to_string()]);
+    }
+
+    #[test]
+    fn schema_checks() {
+        let xsd = XSD
+            .replace(
+                r#"refer="d:trackId">
+            <xs:selector xpath=".//d:lane"/>"#,
+                r#"refer="d:laneTarget">
+            <xs:selector xpath=".//d:lane"/>"#,
+            )
+            .replace(
+                r#"<xs:field xpath="d:target"/>"#,
+                r#"<xs:field xpath="d:target/@x:id"/>"#,
+            )
+            .replace(r#"name="trackName""#, r#"name="trackId""#);
+        let set = SchemaSet::new(vec![Schema::parse(&xsd).unwrap()]);
+        let diagnostics = set.check();
+        let codes: Vec<(&str, &str)> = diagnostics
+            .iter()
+            .map(|d| (d.code.as_str(), d.path.as_str()))
+            .collect();
+
+        assert_eq!(
+            codes,
+            vec![
+                (
+                    "c-fields-xpath",
+                    "schema/element[project]/keyref[laneTarget]"
+                ),
+                (
+                    "sch-props-correct.2",
+                    "schema/element[project]/unique[trackId]"
+                ),
+                (
+                    "c-props-correct.1",
+                    "schema/element[project]/keyref[laneTrack]"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    error::Error,
+    parse_context::ParseContext,
+    prelude::{Annotation, QName},
+    source_span::SourceSpan,
+    value::{is_name_char, is_name_start_char},
+};
+
+/// The kind of an identity constraint.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IdentityConstraintKind {
+    /// `<xs:unique>`: the selected elements have distinct values, if they have all fields.
+    #[default]
+    Unique,
+
+    /// `<xs:key>`: the selected elements have all fields, with distinct values.
+    Key,
+
+    /// `<xs:keyref>`: the values of the selected elements are values of a key or unique
+    /// constraint.
+    KeyRef,
+}
+
+/// An identity constraint of an element declaration, `<xs:unique>`, `<xs:key>` or
+/// `<xs:keyref>`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct IdentityConstraint {
+    pub kind: IdentityConstraintKind,
+    pub name: String,
+
+    /// The qualified name of the key or unique constraint a keyref refers to.
+    pub refer: Option<String>,
+
+    /// The XPath expression of `<xs:selector>`, which selects the elements the constraint
+    /// applies to, relative to the element the constraint is declared in.
+    pub selector: String,
+
+    /// The XPath expressions of the `<xs:field>` elements, relative to a selected element.
+    pub fields: Vec<String>,
+
+    /// The namespaces in scope of the constraint, which resolve the prefixes of `selector` and
+    /// `fields`. They include the ones declared on the enclosing element declaration.
+    pub namespaces: BTreeMap<String, String>,
+
+    pub annotations: Vec<Annotation>,
+    pub span: Option<SourceSpan>,
+}
+
+impl IdentityConstraint {
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut xmltree::Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
+
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(
+        element: &mut xmltree::Element,
+        ctx: &mut ParseContext,
+    ) -> Result<Self, Error> {
+        let kind = match element.name.as_str() {
+            "key" => IdentityConstraintKind::Key,
+            "keyref" => IdentityConstraintKind::KeyRef,
+            _ => IdentityConstraintKind::Unique,
+        };
+        let mut r = IdentityConstraint {
+            kind,
+            span: ctx.span(element),
+            name: element.attributes.remove("name").unwrap_or_default(),
+            refer: element.attributes.remove("refer"),
+            namespaces: element
+                .namespaces
+                .as_ref()
+                .map(|namespaces| namespaces.0.clone())
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope(&element.name, Some(&r.name), &r.span);
+
+        while let Some(mut annotation) = element.take_child("annotation") {
+            r.annotations
+                .push(Annotation::read_with_context(&mut annotation, ctx)?);
+        }
+
+        let mut selector = element
+            .take_child("selector")
+            .ok_or_else(|| ctx.invalid("selector is required"))?;
+        r.selector = selector.attributes.remove("xpath").unwrap_or_default();
+
+        while let Some(mut field) = element.take_child("field") {
+            r.fields
+                .push(field.attributes.remove("xpath").unwrap_or_default());
+        }
+        if r.fields.is_empty() {
+            return Err(ctx.invalid("at least one field is required"));
+        }
+
+        if kind == IdentityConstraintKind::KeyRef && r.refer.is_none() {
+            return Err(ctx.invalid("refer is required"));
+        }
+
+        ctx.leave(element);
+        Ok(r)
+    }
+}
+
+/// An XPath expression of the subset XML Schema allows for selectors and fields: paths of
+/// child steps separated by `|`, each optionally starting with `.//` and, for fields, ending
+/// with an attribute step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XPath {
+    paths: Vec<PathExpr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PathExpr {
+    /// The path starts with `.//`, i.e. it may start at any descendant.
+    descendants: bool,
+    steps: Vec<NameTest>,
+    attribute: Option<NameTest>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum NameTest {
+    /// `*`
+    Any,
+
+    /// `prefix:*`
+    Namespace(String),
+    Name(QName),
+}
+
+impl NameTest {
+    fn parse(test: &str, namespaces: &BTreeMap<String, String>) -> Result<Self, String> {
+        if test == "*" {
+            return Ok(NameTest::Any);
+        }
+
+        let (prefix, local) = match test.split_once(':') {
+            Some((prefix, local)) => (Some(prefix), local),
+            None => (None, test),
+        };
+        let namespace = match prefix {
+            Some(prefix) => {
+                check_name(prefix)?;
+                Some(
+                    namespaces
+                        .get(prefix)
+                        .ok_or_else(|| format!("prefix '{}' is not declared", prefix))?,
+                )
+            }
+            None => None,
+        };
+
+        match (namespace, local) {
+            (Some(namespace), "*") => Ok(NameTest::Namespace(namespace.clone())),
+            (namespace, local) => {
+                check_name(local)?;
+                Ok(NameTest::Name(QName::new(
+                    namespace.map(|n| n.as_str()),
+                    local,
+                )))
+            }
+        }
+    }
+
+    fn matches(&self, name: &QName) -> bool {
+        match self {
+            NameTest::Any => true,
+            NameTest::Namespace(namespace) => name.namespace.as_deref() == Some(namespace),
+            NameTest::Name(n) => n == name,
+        }
+    }
+}
+
+fn check_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c != ':' && is_name_start_char(c))
+        && chars.all(|c| c != ':' && is_name_char(c));
+
+    match valid {
+        true => Ok(()),
+        false => Err(format!("'{}' is not a name test", name)),
+    }
+}
+
+impl XPath {
+    /// Compile the XPath expression of a selector, or of a field if `field` is true. Prefixes
+    /// are resolved with `namespaces`, names without a prefix have no namespace.
+    pub fn new(
+        xpath: &str,
+        field: bool,
+        namespaces: &BTreeMap<String, String>,
+    ) -> Result<Self, String> {
+        let paths = xpath
+            .split('|')
+            .map(|path| PathExpr::parse(path, field, namespaces))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(XPath { paths })
+    }
+
+    /// Whether the expression selects the element reached from the context element through
+    /// the child elements named `path`.
+    pub fn matches(&self, path: &[QName]) -> bool {
+        self.paths
+            .iter()
+            .any(|p| p.attribute.is_none() && p.matches(path))
+    }
+
+    /// Whether the expression selects the attribute `attribute` of the element reached through
+    /// `path`.
+    pub fn matches_attribute(&self, path: &[QName], attribute: &QName) -> bool {
+        self.paths.iter().any(|p| {
+            p.attribute
+                .as_ref()
+                .is_some_and(|test| test.matches(attribute))
+                && p.matches(path)
+        })
+    }
+}
+
+impl PathExpr {
+    fn parse(
+        path: &str,
+        field: bool,
+        namespaces: &BTreeMap<String, String>,
+    ) -> Result<Self, String> {
+        let mut path = path.trim();
+        let descendants = match path.strip_prefix(".//") {
+            Some(rest) => {
+                path = rest;
+                true
+            }
+            None => false,
+        };
+
+        let mut r = PathExpr {
+            descendants,
+            steps: Vec::new(),
+            attribute: None,
+        };
+
+        let steps: Vec<&str> = path.split('/').map(str::trim).collect();
+        for (index, step) in steps.iter().enumerate() {
+            if r.attribute.is_some() {
+                return Err("an attribute step must be the last step".to_string());
+            }
+
+            if let Some(test) = step
+                .strip_prefix('@')
+                .or_else(|| step.strip_prefix("attribute::"))
+            {
+                if !field {
+                    return Err("a selector cannot select attributes".to_string());
+                }
+                r.attribute = Some(NameTest::parse(test.trim(), namespaces)?);
+            } else if *step == "." {
+                if index > 0 {
+                    return Err("'.' is only allowed as first step".to_string());
+                }
+            } else if step.is_empty() {
+                return Err("empty step".to_string());
+            } else {
+                let test = step.strip_prefix("child::").unwrap_or(step).trim();
+                r.steps.push(NameTest::parse(test, namespaces)?);
+            }
+        }
+
+        Ok(r)
+    }
+
+    fn matches(&self, path: &[QName]) -> bool {
+        if path.len() < self.steps.len() || (!self.descendants && path.len() > self.steps.len()) {
+            return false;
+        }
+
+        let tail = &path[path.len() - self.steps.len()..];
+        self.steps
+            .iter()
+            .zip(tail)
+            .all(|(test, name)| test.matches(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::Schema;
+
+    #[test]
+    fn identity_constraints() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:element name="project">
+        <xs:key name="trackId">
+            <xs:annotation><xs:documentation>Tracks are referenced by id.</xs:documentation></xs:annotation>
+            <xs:selector xpath=".//track"/>
+            <xs:field xpath="@id"/>
+        </xs:key>
+        <xs:keyref name="laneTrack" refer="trackId">
+            <xs:selector xpath="lanes/lane"/>
+            <xs:field xpath="@track"/>
+        </xs:keyref>
+        <xs:unique name="laneName">
+            <xs:selector xpath="lanes/lane"/>
+            <xs:field xpath="name"/>
+            <xs:field xpath="@kind"/>
+        </xs:unique>
+    </xs:element>
+</xs:schema>
+"#;
+
+        let schema = Schema::parse(xml).unwrap();
+        let constraints = &schema.elements[0].identity_constraints;
+        assert_eq!(constraints.len(), 3);
+
+        let key = &constraints[0];
+        assert_eq!(key.kind, IdentityConstraintKind::Key);
+        assert_eq!(key.name, "trackId");
+        assert_eq!(key.selector, ".//track");
+        assert_eq!(key.fields, vec!["@id"]);
+        assert_eq!(key.annotations.len(), 1);
+
+        assert_eq!(constraints[1].kind, IdentityConstraintKind::KeyRef);
+        assert_eq!(constraints[1].refer.as_deref(), Some("trackId"));
+        assert_eq!(constraints[2].kind, IdentityConstraintKind::Unique);
+        assert_eq!(constraints[2].fields.len(), 2);
+
+        let missing = xml.replace(r#"<xs:selector xpath=".//track"/>"#, "");
+        assert!(Schema::parse(&missing).is_err());
+    }
+
+    #[test]
+    fn xpath() {
+        let mut namespaces = BTreeMap::new();
+        namespaces.insert("d".to_string(), "urn:daw".to_string());
+
+        let name = |n: &str| QName::new(None, n);
+        let daw = |n: &str| QName::new(Some("urn:daw"), n);
+
+        let selector = XPath::new("lanes/lane | .//d:track", false, &namespaces).unwrap();
+        assert!(selector.matches(&[name("lanes"), name("lane")]));
+        assert!(!selector.matches(&[name("x"), name("lanes"), name("lane")]));
+        assert!(selector.matches(&[daw("track")]));
+        assert!(selector.matches(&[name("x"), daw("y"), daw("track")]));
+        assert!(!selector.matches(&[name("track")]));
+
+        let any = XPath::new("./d:*/*", false, &namespaces).unwrap();
+        assert!(any.matches(&[daw("a"), name("b")]));
+        assert!(!any.matches(&[name("a"), name("b")]));
+
+        let field = XPath::new("child::name/attribute::lang", true, &namespaces).unwrap();
+        assert!(field.matches_attribute(&[name("name")], &name("lang")));
+        assert!(!field.matches(&[name("name")]));
+
+        let itself = XPath::new(".", true, &namespaces).unwrap();
+        assert!(itself.matches(&[]));
+
+        for (xpath, field) in [
+            ("@id", false),
+            ("a//b", false),
+            ("a/@id/b", true),
+            ("x:a", false),
+            ("a b", false),
+            ("", false),
+            ("a/.", false),
+        ] {
+            assert!(XPath::new(xpath, field, &namespaces).is_err(), "{}", xpath);
+        }
+    }
+}
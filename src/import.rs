@@ -1,22 +1,34 @@
 use xmltree::Element;
 
-#[derive(Debug, Default)]
+use crate::{error::Error, parse_context::ParseContext, source_span::SourceSpan};
+
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Import {
     pub namespace: String,
     pub schema_location: String,
+
+    pub span: Option<SourceSpan>,
 }
 
 impl Import {
-    pub fn read(element: &mut Element) -> Self {
-        let mut r = Import::default();
-        if element.attributes.contains_key("namespace") {
-            r.namespace = element.attributes["namespace"].clone();
-        }
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
 
-        if element.attributes.contains_key("schemaLocation") {
-            r.schema_location = element.attributes["schemaLocation"].clone();
-        }
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(element: &mut Element, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let r = Import {
+            span: ctx.span(element),
+            namespace: element.attributes.remove("namespace").unwrap_or_default(),
+            schema_location: element
+                .attributes
+                .remove("schemaLocation")
+                .unwrap_or_default(),
+        };
+        let ctx = &mut *ctx.scope("import", Some(&r.namespace), &r.span);
 
-        r
+        ctx.leave(element);
+        Ok(r)
     }
 }
@@ -0,0 +1,32 @@
+use xmltree::Element;
+
+use crate::{error::Error, parse_context::ParseContext, source_span::SourceSpan};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Include {
+    pub schema_location: String,
+
+    pub span: Option<SourceSpan>,
+}
+
+impl Include {
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
+
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(element: &mut Element, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let r = Include {
+            span: ctx.span(element),
+            schema_location: element
+                .attributes
+                .remove("schemaLocation")
+                .unwrap_or_default(),
+        };
+        let ctx = &mut *ctx.scope("include", Some(&r.schema_location), &r.span);
+
+        ctx.leave(element);
+        Ok(r)
+    }
+}
@@ -1,38 +1,135 @@
+mod all;
 mod annotation;
+mod any;
 mod attribute;
 mod attribute_group;
+mod attribute_uses;
+mod automaton;
+mod builtins;
+mod bundled;
+mod catalog;
 mod choice;
+mod complex_content;
 mod complex_type;
+mod constraints;
+mod content_model;
+mod derivation;
+mod diagnostic;
+mod document;
 mod element;
+mod error;
 mod extension;
 mod group;
+mod identity;
+mod identity_constraint;
 mod import;
+mod include;
+mod list;
 mod occurs_attributes;
+mod parse_context;
+mod parse_options;
+mod particle_checks;
+mod pattern;
+mod psvi;
+mod qname;
 mod restriction;
 mod schema;
+mod schema_resolver;
+mod schema_set;
 mod sequence;
 mod simple_content;
 mod simple_type;
+mod source_span;
+mod substitution;
 mod union;
+mod validator;
+mod value;
+mod version_control;
 
 pub mod prelude {
+    pub use crate::all::All;
+    pub use crate::any::Any;
+    pub use crate::any::NamespaceConstraint;
+    pub use crate::any::ProcessContents;
     pub use crate::attribute::Attribute;
     pub use crate::attribute_group::AttributeGroup;
+    pub use crate::attribute_uses::AttributeUse;
+    pub use crate::attribute_uses::AttributeUses;
+    pub use crate::attribute_uses::AttributeWildcard;
     pub use crate::attribute::AttributeMeta;
+    pub use crate::automaton::ContentAutomaton;
+    pub use crate::automaton::ContentState;
+    pub use crate::builtins::BuiltinType;
+    pub use crate::builtins::Cardinality;
+    pub use crate::builtins::FundamentalFacets;
+    pub use crate::builtins::Ordered;
+    pub use crate::builtins::Variety;
+    pub use crate::builtins::WhiteSpace;
+    pub use crate::builtins::BUILTIN_TYPES;
+    pub use crate::bundled::BundledResolver;
+    pub use crate::catalog::Catalog;
+    pub use crate::catalog::CatalogResolver;
     pub use crate::choice::Choice;
+    pub use crate::choice::ChoiceItems;
+    pub use crate::complex_content::ComplexContent;
     pub use crate::complex_type::ComplexType;
+    pub use crate::complex_type::DerivationMethod;
+    pub use crate::content_model::ContentModel;
+    pub use crate::content_model::ElementTerm;
+    pub use crate::content_model::Particle;
+    pub use crate::content_model::Term;
+    pub use crate::derivation::DerivationGraph;
+    pub use crate::derivation::DerivationSet;
+    pub use crate::diagnostic::Diagnostic;
+    pub use crate::diagnostic::Severity;
     pub use crate::element::Element;
+    pub use crate::error::Error;
     pub use crate::extension::Extension;
     pub use crate::group::Group;
+    pub use crate::identity_constraint::IdentityConstraint;
+    pub use crate::identity_constraint::IdentityConstraintKind;
+    pub use crate::identity_constraint::XPath;
     pub use crate::import::Import;
+    pub use crate::include::Include;
+    pub use crate::list::List;
     pub use crate::occurs_attributes::Occurs;
+    pub use crate::parse_context::{ParseContext, Scope};
+    pub use crate::parse_options::ParseOptions;
+    pub use crate::pattern::Pattern;
+    pub use crate::pattern::PatternError;
+    pub use crate::psvi::PsviEvent;
+    pub use crate::psvi::TypedAttribute;
+    pub use crate::psvi::TypedElement;
+    pub use crate::psvi::Validity;
+    pub use crate::qname::QName;
+    pub use crate::restriction::BoundKind;
     pub use crate::restriction::Restriction;
     pub use crate::restriction::RestrictionContent;
     pub use crate::schema::Schema;
+    pub use crate::schema_resolver::resolve_uri;
+    pub use crate::schema_resolver::DefaultResolver;
+    pub use crate::schema_resolver::FileSystemResolver;
+    pub use crate::schema_resolver::HttpResolver;
+    pub use crate::schema_resolver::MemoryResolver;
+    pub use crate::schema_resolver::ResolvedSchema;
+    pub use crate::schema_resolver::SchemaResolver;
+    pub use crate::schema_set::ComponentId;
+    pub use crate::schema_set::DeclarationId;
+    pub use crate::schema_set::LocalScope;
+    pub use crate::schema_set::SchemaSet;
+    pub use crate::schema_set::SymbolTable;
     pub use crate::sequence::Sequence;
     pub use crate::simple_content::SimpleContent;
     pub use crate::simple_type::SimpleType;
+    pub use crate::source_span::Position;
+    pub use crate::source_span::SourceSpan;
+    pub use crate::substitution::SubstitutionGroups;
     pub use crate::union::Union;
+    pub use crate::validator::ValidationStream;
+    pub use crate::validator::Validator;
+    pub use crate::value::SimpleValue;
+    pub use crate::value::ValueError;
+    pub use crate::value::ValueValidator;
 
     pub use crate::annotation::Annotation;
     pub use crate::annotation::AnnotationContent;
@@ -0,0 +1,103 @@
+use xmltree::Element;
+
+use crate::{
+    error::Error, parse_context::ParseContext, prelude::SimpleType, source_span::SourceSpan,
+};
+
+/// A whitespace separated list of values of a simple type.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct List {
+    /// The qualified name of the item type, if it is not defined inline.
+    pub item_type: Option<String>,
+
+    /// The anonymous item type.
+    pub simple_type: Option<Box<SimpleType>>,
+
+    pub span: Option<SourceSpan>,
+}
+
+impl List {
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
+
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(element: &mut Element, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let mut r = List {
+            span: ctx.span(element),
+            item_type: element.attributes.remove("itemType"),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope("list", r.item_type.as_deref(), &r.span);
+
+        if let Some(mut simple_type) = element.take_child("simpleType") {
+            if r.item_type.is_some() {
+                return Err(
+                    ctx.invalid("itemType and an anonymous simpleType must not both be present")
+                );
+            }
+
+            r.simple_type = Some(Box::new(SimpleType::read_with_context(
+                &mut simple_type,
+                ctx,
+            )?));
+        }
+
+        ctx.leave(element);
+        Ok(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{error::Error, schema::Schema};
+
+    #[test]
+    fn list() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="NMTOKENS">
+        <xs:list itemType="xs:NMTOKEN"/>
+    </xs:simpleType>
+    <xs:simpleType name="sizes">
+        <xs:list>
+            <xs:simpleType>
+                <xs:restriction base="xs:positiveInteger"/>
+            </xs:simpleType>
+        </xs:list>
+    </xs:simpleType>
+</xs:schema>
+"#;
+
+        let item = Schema::parse(xml).unwrap();
+
+        let list = item.simple_types[0].list.as_ref().unwrap();
+        assert_eq!(list.item_type, Some("xs:NMTOKEN".to_string()));
+        assert!(list.simple_type.is_none());
+
+        let list = item.simple_types[1].list.as_ref().unwrap();
+        let item_type = list.simple_type.as_ref().unwrap();
+        assert_eq!(
+            item_type.restriction.as_ref().unwrap().base,
+            "xs:positiveInteger".to_string()
+        );
+
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="sizes">
+        <xs:list itemType="xs:integer">
+            <xs:simpleType>
+                <xs:restriction base="xs:positiveInteger"/>
+            </xs:simpleType>
+        </xs:list>
+    </xs:simpleType>
+</xs:schema>
+"#;
+
+        assert!(matches!(
+            Schema::parse(xml),
+            Err(Error::InvalidSchema { .. })
+        ));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::{diagnostic::Severity, parse_context::ParseContext};
+
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
 pub enum Occurs {
     MinOccurs(String),
@@ -8,14 +10,137 @@ impl Occurs {
     pub fn read(element: &mut xmltree::Element) -> Vec<Self> {
         let mut r: Vec<Occurs> = Vec::new();
 
-        if element.attributes.contains_key("minOccurs") {
-            r.push(Self::MinOccurs(element.attributes["minOccurs"].to_string()));
+        if let Some(min) = element.attributes.remove("minOccurs") {
+            r.push(Self::MinOccurs(min));
+        }
+
+        if let Some(max) = element.attributes.remove("maxOccurs") {
+            r.push(Self::MaxOccurs(max));
+        }
+
+        r
+    }
+
+    /// Like [`Occurs::read`], but values that are not non-negative integers or too large for
+    /// [`Occurs::range`] are reported to the given context.
+    pub fn read_with_context(element: &mut xmltree::Element, ctx: &mut ParseContext) -> Vec<Self> {
+        let r = Self::read(element);
+
+        for o in r.iter() {
+            let (name, value) = match o {
+                Occurs::MinOccurs(min) => ("minOccurs", min),
+                Occurs::MaxOccurs(max) if max.trim() == "unbounded" => continue,
+                Occurs::MaxOccurs(max) => ("maxOccurs", max),
+            };
+
+            match number(value) {
+                None => ctx.report(
+                    Severity::Error,
+                    "invalid-occurs-value",
+                    format!(
+                        "{} value '{}' is not a non-negative integer, 1 is used",
+                        name, value
+                    ),
+                    None,
+                ),
+                Some(u32::MAX) if value.trim().parse::<u32>().is_err() => ctx.report(
+                    Severity::Warning,
+                    "invalid-occurs-value",
+                    format!(
+                        "{} value '{}' is too large, {} is used",
+                        name,
+                        value,
+                        u32::MAX
+                    ),
+                    None,
+                ),
+                Some(_) => {}
+            }
         }
 
-        if element.attributes.contains_key("maxOccurs") {
-            r.push(Self::MaxOccurs(element.attributes["maxOccurs"].to_string()));
+        r
+    }
+
+    /// `minOccurs` and `maxOccurs` as numbers. The maximum is `None` for `unbounded`. Missing
+    /// and unparsable values default to 1, values too large for a `u32` saturate.
+    pub fn range(occurs: &[Occurs]) -> (u32, Option<u32>) {
+        let mut r = (1, Some(1));
+
+        for o in occurs {
+            match o {
+                Occurs::MinOccurs(min) => r.0 = number(min).unwrap_or(1),
+                Occurs::MaxOccurs(max) if max.trim() == "unbounded" => r.1 = None,
+                Occurs::MaxOccurs(max) => r.1 = Some(number(max).unwrap_or(1)),
+            }
         }
 
         r
     }
 }
+
+/// Parse a non-negative integer, saturating at `u32::MAX`.
+fn number(value: &str) -> Option<u32> {
+    let value = value.trim();
+    let digits = value.strip_prefix('+').unwrap_or(value);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    Some(digits.parse().unwrap_or(u32::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{ParseOptions, Schema};
+
+    #[test]
+    fn range() {
+        let occurs = |min: &str, max: &str| {
+            vec![
+                Occurs::MinOccurs(min.to_string()),
+                Occurs::MaxOccurs(max.to_string()),
+            ]
+        };
+
+        assert_eq!(Occurs::range(&[]), (1, Some(1)));
+        assert_eq!(Occurs::range(&occurs(" 0 ", "unbounded")), (0, None));
+        assert_eq!(
+            Occurs::range(&occurs("+2", "99999999999")),
+            (2, Some(u32::MAX))
+        );
+        assert_eq!(Occurs::range(&occurs("-1", "many")), (1, Some(1)));
+    }
+
+    #[test]
+    fn diagnostics() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:complexType name="notes">
+        <xs:sequence minOccurs="-1">
+            <xs:element name="note" maxOccurs="99999999999"/>
+        </xs:sequence>
+    </xs:complexType>
+</xs:schema>
+"#;
+
+        let (_, diagnostics) =
+            Schema::parse_with_diagnostics(xml, &ParseOptions::default()).unwrap();
+
+        let found: Vec<(&str, Severity)> = diagnostics
+            .iter()
+            .map(|d| (d.path.as_str(), d.severity))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("schema/complexType[notes]/sequence", Severity::Error),
+                (
+                    "schema/complexType[notes]/sequence/element[note]",
+                    Severity::Warning
+                ),
+            ]
+        );
+        assert!(diagnostics.iter().all(|d| d.code == "invalid-occurs-value"));
+    }
+}
@@ -0,0 +1,333 @@
+use std::ops::{Deref, DerefMut};
+
+use xmltree::Element;
+
+use crate::{
+    diagnostic::{Diagnostic, Severity},
+    document::{Positions, XSD_NAMESPACE},
+    error::Error,
+    parse_options::ParseOptions,
+    source_span::{Position, SourceSpan},
+};
+
+/// State shared by all `read` functions while a schema is parsed.
+#[derive(Debug, Default)]
+pub struct ParseContext {
+    pub options: ParseOptions,
+
+    /// URI (or path) of the document being parsed. Copied into every [`SourceSpan`].
+    pub document: Option<String>,
+
+    /// Problems the parser worked around. In strict mode this includes everything the parser
+    /// skipped, see [`ParseOptions::strict`].
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// Where the elements of the document being parsed are.
+    pub(crate) positions: Positions,
+
+    path: Vec<(String, Option<SourceSpan>)>,
+}
+
+impl ParseContext {
+    pub fn new(options: ParseOptions) -> Self {
+        ParseContext {
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Called by a `read` function once it knows which component it reads.
+    pub fn enter(&mut self, component: &str, name: Option<&str>, span: &Option<SourceSpan>) {
+        let segment = match name {
+            Some(name) if !name.is_empty() => format!("{}[{}]", component, name),
+            _ => component.to_string(),
+        };
+
+        self.path.push((segment, span.clone()));
+    }
+
+    /// Like [`ParseContext::enter`], but the component is also left when the returned guard is
+    /// dropped. A `read` function that fails before it calls [`ParseContext::leave`] then
+    /// still leaves the path of the context as it found it.
+    pub fn scope(
+        &mut self,
+        component: &str,
+        name: Option<&str>,
+        span: &Option<SourceSpan>,
+    ) -> Scope<'_> {
+        let depth = self.path.len();
+        self.enter(component, name, span);
+        Scope { ctx: self, depth }
+    }
+
+    /// Take the position recorded for `element` while the document was parsed. Elements not
+    /// produced by [`crate::prelude::Schema::parse`] have no position.
+    pub fn span(&mut self, element: &Element) -> Option<SourceSpan> {
+        let (start, end) = self.positions.take(element)?;
+        Some(self.to_span(start, end))
+    }
+
+    /// Add a diagnostic for the component currently read. Without an explicit `span` the
+    /// location of the component is used.
+    pub fn report(
+        &mut self,
+        severity: Severity,
+        code: &str,
+        message: String,
+        span: Option<SourceSpan>,
+    ) {
+        let span = span.or_else(|| self.path.last().and_then(|(_, span)| span.clone()));
+
+        self.diagnostics.push(Diagnostic {
+            severity,
+            code: code.to_string(),
+            message,
+            path: self.path_string(),
+            span,
+        });
+    }
+
+    /// Build an [`Error::InvalidSchema`] for the component currently read.
+    pub fn invalid(&self, message: &str) -> Error {
+        Error::InvalidSchema {
+            path: self.path_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Called by a `read` function after it took everything it understands out of `element`.
+    /// In strict mode, whatever is left over is reported.
+    pub fn leave(&mut self, element: &Element) {
+        if self.options.strict {
+            let mut attributes: Vec<&String> = element
+                .attributes
+                .keys()
+                .filter(|k| !k.starts_with('{'))
+                .collect();
+            attributes.sort();
+            for attribute in attributes {
+                self.report(
+                    Severity::Warning,
+                    "unconsumed-attribute",
+                    format!("unsupported attribute '{}'", attribute),
+                    None,
+                );
+            }
+
+            for child in element.children.iter().filter_map(|c| c.as_element()) {
+                if child.namespace.as_deref() == Some(XSD_NAMESPACE) {
+                    let span = self
+                        .positions
+                        .get(child)
+                        .map(|(start, end)| self.to_span(start, end));
+                    self.report(
+                        Severity::Warning,
+                        "unconsumed-element",
+                        format!("unsupported element '{}'", child.name),
+                        span,
+                    );
+                }
+            }
+        }
+
+        self.path.pop();
+    }
+
+    fn path_string(&self) -> String {
+        self.path
+            .iter()
+            .map(|(segment, _)| segment.as_str())
+            .collect::<Vec<&str>>()
+            .join("/")
+    }
+
+    fn to_span(&self, start: Position, end: Position) -> SourceSpan {
+        SourceSpan {
+            document: self.document.clone(),
+            start,
+            end,
+        }
+    }
+}
+
+/// Guard returned by [`ParseContext::scope`].
+pub struct Scope<'c> {
+    ctx: &'c mut ParseContext,
+    depth: usize,
+}
+
+impl Deref for Scope<'_> {
+    type Target = ParseContext;
+
+    fn deref(&self) -> &Self::Target {
+        self.ctx
+    }
+}
+
+impl DerefMut for Scope<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ctx
+    }
+}
+
+impl Drop for Scope<'_> {
+    fn drop(&mut self) {
+        self.ctx.path.truncate(self.depth);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::Schema;
+
+    #[test]
+    fn strict() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:xlink="http://www.w3.org/1999/xlink" elementFormDefault="qualified" attributeFormDefault="unqualified" version="4.1">
+    <xs:complexType name="arrow">
+        <xs:assert test="true()"/>
+        <xs:attribute name="smufl" type="smufl-glyph-name" xlink:title="ignored"/>
+        <xs:openContent/>
+    </xs:complexType>
+</xs:schema>
+"#;
+
+        let options = ParseOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let (_, diagnostics) = Schema::parse_with_diagnostics(xml, &options).unwrap();
+
+        let found: Vec<(&str, &str, &str)> = diagnostics
+            .iter()
+            .map(|d| (d.path.as_str(), d.code.as_str(), d.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "schema/complexType[arrow]",
+                    "unconsumed-element",
+                    "unsupported element 'assert'"
+                ),
+                (
+                    "schema/complexType[arrow]",
+                    "unconsumed-element",
+                    "unsupported element 'openContent'"
+                ),
+                (
+                    "schema",
+                    "unconsumed-attribute",
+                    "unsupported attribute 'version'"
+                ),
+            ]
+        );
+
+        // Skipped elements point to themselves, attributes to their component.
+        assert_eq!(diagnostics[0].span.as_ref().unwrap().start.line, 4);
+        assert_eq!(diagnostics[2].span.as_ref().unwrap().start.line, 2);
+    }
+
+    #[test]
+    fn balanced_after_error() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:complexType name="note">
+        <xs:sequence>
+            <xs:element name="pitch" type="pitch"><xs:complexType/></xs:element>
+        </xs:sequence>
+    </xs:complexType>
+</xs:schema>
+"#;
+
+        let mut element = xmltree::Element::parse(xml.as_bytes()).unwrap();
+        let mut ctx = ParseContext::default();
+        let error = Schema::read_with_context(&mut element, &mut ctx).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::InvalidSchema { ref path, .. } if path == "schema/complexType[note]/sequence/element[pitch]"
+        ));
+
+        ctx.report(Severity::Warning, "test", String::new(), None);
+        assert_eq!(ctx.diagnostics[0].path, "");
+    }
+
+    #[test]
+    fn lenient() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:complexType name="arrow">
+        <xs:complexContent>
+            <xs:extension base="empty"/>
+        </xs:complexContent>
+    </xs:complexType>
+</xs:schema>
+"#;
+
+        let (_, diagnostics) =
+            Schema::parse_with_diagnostics(xml, &ParseOptions::default()).unwrap();
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn lossy() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="tenths">
+        <xs:annotation>
+            <xs:documentation/>
+        </xs:annotation>
+        <xs:restriction base="xs:decimal">
+            <xs:maxInclusive/>
+            <xs:totalDigits value="0"/>
+        </xs:restriction>
+    </xs:simpleType>
+</xs:schema>
+"#;
+
+        let (schema, diagnostics) =
+            Schema::parse_with_diagnostics(xml, &ParseOptions::default()).unwrap();
+        assert!(schema.simple_types[0]
+            .restriction
+            .as_ref()
+            .unwrap()
+            .content
+            .is_empty());
+
+        let found: Vec<(Severity, &str, &str, u64)> = diagnostics
+            .iter()
+            .map(|d| {
+                (
+                    d.severity,
+                    d.code.as_str(),
+                    d.path.as_str(),
+                    d.span.as_ref().unwrap().start.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    Severity::Warning,
+                    "empty-documentation",
+                    "schema/simpleType[tenths]/annotation",
+                    5
+                ),
+                (
+                    Severity::Error,
+                    "missing-facet-value",
+                    "schema/simpleType[tenths]/restriction[xs:decimal]/maxInclusive",
+                    8
+                ),
+                (
+                    Severity::Warning,
+                    "unparsable-facet-value",
+                    "schema/simpleType[tenths]/restriction[xs:decimal]",
+                    9
+                ),
+            ]
+        );
+    }
+}
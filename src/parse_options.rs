@@ -0,0 +1,58 @@
+use crate::{
+    builtins::BUILTIN_TYPES,
+    document::{expanded_name, XSD_NAMESPACE},
+};
+
+/// Constraining facets of XML Schema 1.0.
+const XSD_10_FACETS: [&str; 12] = [
+    "length",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "enumeration",
+    "whiteSpace",
+    "maxInclusive",
+    "maxExclusive",
+    "minExclusive",
+    "minInclusive",
+    "totalDigits",
+    "fractionDigits",
+];
+
+/// Settings used while turning a schema document into a [`crate::prelude::Schema`].
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// The XSD version the parser claims to implement. Compared against `vc:minVersion` and
+    /// `vc:maxVersion` to decide whether a part of the schema is loaded.
+    pub processor_version: f64,
+
+    /// Types considered available by `vc:typeAvailable` and `vc:typeUnavailable`, in
+    /// `{namespace}local` notation.
+    pub available_types: Vec<String>,
+
+    /// Facets considered available by `vc:facetAvailable` and `vc:facetUnavailable`, in
+    /// `{namespace}local` notation.
+    pub available_facets: Vec<String>,
+
+    /// Report every child element in the XSD namespace and every unqualified attribute the
+    /// parser does not understand. See [`crate::prelude::ParseContext::leave`].
+    pub strict: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            processor_version: 1.0,
+            // The built-in datatypes of XML Schema 1.0.
+            available_types: std::iter::once("anyType")
+                .chain(BUILTIN_TYPES.iter().map(|t| t.name))
+                .map(|t| expanded_name(XSD_NAMESPACE, t))
+                .collect(),
+            available_facets: XSD_10_FACETS
+                .iter()
+                .map(|f| expanded_name(XSD_NAMESPACE, f))
+                .collect(),
+            strict: false,
+        }
+    }
+}
@@ -0,0 +1,574 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    content_model::{ContentModel, ElementTerm, Particle, Term},
+    diagnostic::{Diagnostic, Severity},
+    prelude::{Any, ComplexType, QName},
+    schema_set::{DeclarationId, LocalScope, SchemaSet},
+    source_span::SourceSpan,
+    substitution::SubstitutionGroups,
+};
+
+/// Occurrence bounds above this are treated as if they were this large when the content model
+/// is unfolded. Ambiguities that only show after more repetitions are not found.
+const MAX_UNFOLD: u32 = 8;
+
+impl SchemaSet {
+    /// Check the effective content model of the global complex type `name` for Unique Particle
+    /// Attribution and Element Declarations Consistent, see
+    /// [`SchemaSet::check_particles_of`]. `None` if there is no such type.
+    pub fn check_particles(&self, name: &QName) -> Option<Vec<Diagnostic>> {
+        let id = self.lookup(name, |t| &t.complex_types)?;
+        Some(self.check_particles_of(
+            id.schema,
+            &self.schemas[id.schema].complex_types[id.index],
+            LocalScope::Type(id),
+        ))
+    }
+
+    /// Check the effective content model of a complex type defined in the schema document with
+    /// the index `schema`. `scope` identifies the type, see [`SchemaSet::content_model_of`]:
+    ///
+    /// * `cos-nonambig`: every child element must match a single particle without looking
+    ///   ahead, e.g. `(a?, a)` is ambiguous.
+    /// * `cos-element-consistent`: elements with the same name in one content model must have
+    ///   the same type.
+    ///
+    /// References to global elements match the members of their substitution group as well.
+    /// Problems of the content model itself are reported by [`SchemaSet::content_model_of`].
+    pub fn check_particles_of(
+        &self,
+        schema: usize,
+        complex_type: &ComplexType,
+        scope: LocalScope,
+    ) -> Vec<Diagnostic> {
+        let (model, _) = self.content_model_of(schema, complex_type, scope);
+        let groups = self.substitution_groups();
+
+        self.particle_diagnostics(
+            &groups,
+            &format!("complexType[{}]", complex_type.name),
+            &model,
+        )
+    }
+
+    pub(crate) fn particle_diagnostics(
+        &self,
+        groups: &SubstitutionGroups,
+        path: &str,
+        model: &ContentModel,
+    ) -> Vec<Diagnostic> {
+        let Some(particle) = &model.particle else {
+            return Vec::new();
+        };
+
+        let mut check = ParticleCheck {
+            set: self,
+            groups,
+            path,
+            diagnostics: Vec::new(),
+        };
+        check.unique_attribution(particle);
+        check.consistent_declarations(particle);
+
+        check.diagnostics
+    }
+}
+
+struct ParticleCheck<'c> {
+    set: &'c SchemaSet,
+    groups: &'c SubstitutionGroups,
+    path: &'c str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'c> ParticleCheck<'c> {
+    fn unique_attribution(&mut self, particle: &Particle) {
+        let mut automaton = Glushkov::default();
+        leaves(&particle.term, &mut automaton.leaves);
+        let root = automaton.particle(particle, 0);
+
+        let leaves: Vec<Vec<Match>> = automaton
+            .leaves
+            .iter()
+            .map(|term| self.matches(term))
+            .collect();
+
+        let mut reported = BTreeSet::new();
+        let candidates = std::iter::once(&root.first).chain(automaton.follow.iter());
+        for positions in candidates {
+            let competing: BTreeSet<usize> =
+                positions.iter().map(|p| automaton.positions[*p]).collect();
+
+            for a in competing.iter() {
+                for b in competing.range(a + 1..) {
+                    if overlap(&leaves[*a], &leaves[*b]) && reported.insert((*a, *b)) {
+                        let (first, second) = (automaton.leaves[*a], automaton.leaves[*b]);
+                        self.report(
+                            "cos-nonambig",
+                            format!(
+                                "{} and {} can match the same element, the content model is \
+                                 ambiguous",
+                                describe(first),
+                                describe(second)
+                            ),
+                            span(second),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// The names or namespaces a leaf of the content model matches.
+    fn matches<'t>(&self, term: &'t Term) -> Vec<Match<'t>>
+    where
+        'c: 't,
+    {
+        match term {
+            Term::Element(e) if matches!(e.id, DeclarationId::Global(_)) => self
+                .groups
+                .substitutes(&e.name)
+                .into_iter()
+                .map(|name| Match::Name(name.clone()))
+                .collect(),
+            Term::Element(e) => vec![Match::Name(e.name.clone())],
+            Term::Any { wildcard, schema } => vec![Match::Wildcard(
+                wildcard,
+                self.set.schemas[*schema].target_namespace.as_deref(),
+            )],
+            _ => Vec::new(),
+        }
+    }
+
+    fn consistent_declarations(&mut self, particle: &Particle) {
+        let mut types: BTreeMap<QName, TypeKey> = BTreeMap::new();
+        let mut elements = Vec::new();
+        element_terms(particle, &mut elements);
+
+        for e in elements {
+            let mut declarations = vec![(e.name.clone(), type_key(e))];
+            if matches!(e.id, DeclarationId::Global(_)) {
+                for member in self.groups.members(&e.name) {
+                    if let Some(term) = ElementTerm::global(self.set, member) {
+                        declarations.push((member.clone(), type_key(&term)));
+                    }
+                }
+            }
+
+            for (name, key) in declarations {
+                let Some(existing) = types.get(&name) else {
+                    types.insert(name, key);
+                    continue;
+                };
+
+                if *existing != key {
+                    self.report(
+                        "cos-element-consistent",
+                        format!(
+                            "element '{}' is declared with different types in the same content \
+                             model",
+                            name
+                        ),
+                        e.declaration.span.clone(),
+                    );
+                }
+            }
+        }
+    }
+
+    fn report(&mut self, code: &str, message: String, span: Option<SourceSpan>) {
+        let d = Diagnostic {
+            severity: Severity::Error,
+            code: code.to_string(),
+            message,
+            path: self.path.to_string(),
+            span,
+        };
+        if !self.diagnostics.contains(&d) {
+            self.diagnostics.push(d);
+        }
+    }
+}
+
+enum Match<'t> {
+    Name(QName),
+
+    /// A wildcard and the target namespace of its schema document.
+    Wildcard(&'t Any, Option<&'t str>),
+}
+
+impl Match<'_> {
+    fn allows(&self, namespace: Option<&str>) -> bool {
+        match self {
+            Match::Name(name) => name.namespace.as_deref() == namespace,
+            Match::Wildcard(wildcard, target) => wildcard.allows(*target, namespace),
+        }
+    }
+
+    /// Namespaces that decide whether two wildcards overlap. Any other namespace is matched
+    /// exactly like a namespace that no schema mentions.
+    fn namespaces(&self) -> Vec<Option<String>> {
+        match self {
+            Match::Name(name) => vec![name.namespace.clone()],
+            Match::Wildcard(wildcard, target) => {
+                let mut r = vec![
+                    None,
+                    target.map(str::to_string),
+                    Some("urn:x-unmentioned".to_string()),
+                ];
+                r.extend(
+                    wildcard
+                        .namespace
+                        .iter()
+                        .flat_map(|c| c.split_whitespace())
+                        .filter(|n| !n.starts_with("##"))
+                        .map(|n| Some(n.to_string())),
+                );
+                r
+            }
+        }
+    }
+}
+
+fn overlap(a: &[Match], b: &[Match]) -> bool {
+    a.iter().any(|a| {
+        b.iter().any(|b| match (a, b) {
+            (Match::Name(a), Match::Name(b)) => a == b,
+            _ => a
+                .namespaces()
+                .into_iter()
+                .chain(b.namespaces())
+                .any(|n| a.allows(n.as_deref()) && b.allows(n.as_deref())),
+        })
+    })
+}
+
+/// Identifies the type of an element declaration: a named type or the anonymous type of a
+/// declaration.
+#[derive(PartialEq)]
+enum TypeKey {
+    Named(Option<QName>),
+    Anonymous(DeclarationId),
+}
+
+fn type_key(e: &ElementTerm) -> TypeKey {
+    match &e.r#type {
+        None if e.declaration.has_anonymous_type() => TypeKey::Anonymous(e.id.clone()),
+        r#type => TypeKey::Named(r#type.clone()),
+    }
+}
+
+/// The element and wildcard terms inside `term`, in document order.
+fn leaves<'m, 'a>(term: &'m Term<'a>, r: &mut Vec<&'m Term<'a>>) {
+    match term {
+        Term::Element(_) | Term::Any { .. } => r.push(term),
+        Term::Sequence(particles) | Term::Choice(particles) | Term::All(particles) => {
+            particles.iter().for_each(|p| leaves(&p.term, r))
+        }
+    }
+}
+
+fn leaf_count(term: &Term) -> usize {
+    match term {
+        Term::Element(_) | Term::Any { .. } => 1,
+        Term::Sequence(particles) | Term::Choice(particles) | Term::All(particles) => {
+            particles.iter().map(|p| leaf_count(&p.term)).sum()
+        }
+    }
+}
+
+pub(crate) fn element_terms<'p, 'a>(particle: &'p Particle<'a>, r: &mut Vec<&'p ElementTerm<'a>>) {
+    match &particle.term {
+        Term::Element(e) => r.push(e),
+        Term::Any { .. } => {}
+        Term::Sequence(particles) | Term::Choice(particles) | Term::All(particles) => {
+            particles.iter().for_each(|p| element_terms(p, r))
+        }
+    }
+}
+
+fn describe(term: &Term) -> String {
+    match term {
+        Term::Element(e) => format!("element '{}'", e.name),
+        _ => "a wildcard".to_string(),
+    }
+}
+
+fn span(term: &Term) -> Option<SourceSpan> {
+    match term {
+        Term::Element(e) => e.declaration.span.clone(),
+        Term::Any { wildcard, .. } => wildcard.span.clone(),
+        _ => None,
+    }
+}
+
+/// The position automaton of a content model, with particles unfolded according to their
+/// occurrence bounds. Copies made by unfolding share their leaf, so that only different
+/// particles competing for an element count as ambiguous.
+#[derive(Default)]
+struct Glushkov<'m, 'a> {
+    /// The element and wildcard terms of the content model, in document order.
+    leaves: Vec<&'m Term<'a>>,
+
+    /// The leaf of each position.
+    positions: Vec<usize>,
+
+    /// The positions that may follow each position.
+    follow: Vec<BTreeSet<usize>>,
+}
+
+/// The positions a part of the content model can start and end with.
+struct Fragment {
+    nullable: bool,
+    first: BTreeSet<usize>,
+    last: BTreeSet<usize>,
+}
+
+impl Fragment {
+    fn empty() -> Self {
+        Fragment {
+            nullable: true,
+            first: BTreeSet::new(),
+            last: BTreeSet::new(),
+        }
+    }
+}
+
+impl<'m, 'a> Glushkov<'m, 'a> {
+    /// `leaf` is the index of the first leaf inside the particle.
+    fn particle(&mut self, particle: &'m Particle<'a>, leaf: usize) -> Fragment {
+        let required = particle.min_occurs.min(MAX_UNFOLD);
+        let mut copies: Vec<Fragment> = (0..required)
+            .map(|_| self.term(&particle.term, leaf))
+            .collect();
+
+        match particle.max_occurs {
+            None => {
+                let mut repeated = copies.pop().unwrap_or_else(|| {
+                    let mut f = self.term(&particle.term, leaf);
+                    f.nullable = true;
+                    f
+                });
+                for last in repeated.last.iter() {
+                    self.follow[*last].extend(repeated.first.iter().copied());
+                }
+                repeated.nullable |= required == 0;
+                copies.push(repeated);
+            }
+            Some(max) => {
+                let optional = max.saturating_sub(particle.min_occurs).min(MAX_UNFOLD);
+                for _ in 0..optional {
+                    let mut f = self.term(&particle.term, leaf);
+                    f.nullable = true;
+                    copies.push(f);
+                }
+            }
+        }
+
+        self.sequence(copies)
+    }
+
+    fn term(&mut self, term: &'m Term<'a>, leaf: usize) -> Fragment {
+        match term {
+            Term::Element(_) | Term::Any { .. } => {
+                let position = self.positions.len();
+                self.positions.push(leaf);
+                self.follow.push(BTreeSet::new());
+
+                Fragment {
+                    nullable: false,
+                    first: BTreeSet::from([position]),
+                    last: BTreeSet::from([position]),
+                }
+            }
+            Term::Sequence(particles) => {
+                let fragments = self.particles(particles, leaf);
+                self.sequence(fragments)
+            }
+            Term::Choice(particles) => {
+                let mut r = Fragment {
+                    nullable: false,
+                    first: BTreeSet::new(),
+                    last: BTreeSet::new(),
+                };
+                for f in self.particles(particles, leaf) {
+                    r.nullable |= f.nullable;
+                    r.first.extend(f.first);
+                    r.last.extend(f.last);
+                }
+                r
+            }
+            Term::All(particles) => {
+                // Any order: every particle may follow every other one. Allowing a particle to
+                // follow itself as well cannot hide an ambiguity, as it competes only with itself.
+                let fragments = self.particles(particles, leaf);
+
+                let mut r = Fragment::empty();
+                for f in fragments.iter() {
+                    r.nullable &= f.nullable;
+                    r.first.extend(f.first.iter().copied());
+                    r.last.extend(f.last.iter().copied());
+                }
+                for f in fragments.iter() {
+                    for last in f.last.iter() {
+                        self.follow[*last].extend(r.first.iter().copied());
+                    }
+                }
+                r
+            }
+        }
+    }
+
+    /// The fragments of the particles of a model group whose first leaf is `leaf`.
+    fn particles(&mut self, particles: &'m [Particle<'a>], mut leaf: usize) -> Vec<Fragment> {
+        let mut r = Vec::new();
+        for p in particles {
+            r.push(self.particle(p, leaf));
+            leaf += leaf_count(&p.term);
+        }
+
+        r
+    }
+
+    fn sequence(&mut self, fragments: Vec<Fragment>) -> Fragment {
+        let mut r = Fragment::empty();
+
+        for f in fragments {
+            for last in r.last.iter() {
+                self.follow[*last].extend(f.first.iter().copied());
+            }
+
+            if r.nullable {
+                r.first.extend(f.first.iter().copied());
+            }
+            if f.nullable {
+                r.last.extend(f.last);
+            } else {
+                r.last = f.last;
+            }
+            r.nullable &= f.nullable;
+        }
+
+        r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Schema;
+
+    const XML: &str = r###"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:t" xmlns="urn:t" elementFormDefault="qualified">
+    <xs:complexType name="deterministic">
+        <xs:sequence>
+            <xs:element name="a" type="xs:string" minOccurs="2" maxOccurs="2"/>
+            <xs:element name="a" type="xs:string" minOccurs="0"/>
+            <xs:choice maxOccurs="unbounded">
+                <xs:element name="b" type="xs:string"/>
+                <xs:element name="c" type="xs:string"/>
+            </xs:choice>
+            <xs:any namespace="##other" minOccurs="0"/>
+        </xs:sequence>
+    </xs:complexType>
+    <xs:complexType name="optional-first">
+        <xs:sequence>
+            <xs:element name="a" type="xs:string" minOccurs="0"/>
+            <xs:element name="a" type="xs:string"/>
+        </xs:sequence>
+    </xs:complexType>
+    <xs:complexType name="repeated-choice">
+        <xs:choice maxOccurs="unbounded">
+            <xs:sequence>
+                <xs:element name="a" type="xs:string"/>
+                <xs:element name="b" type="xs:string"/>
+            </xs:sequence>
+            <xs:element name="a" type="xs:string"/>
+        </xs:choice>
+    </xs:complexType>
+    <xs:complexType name="wildcard">
+        <xs:sequence>
+            <xs:element name="a" type="xs:string" minOccurs="0"/>
+            <xs:any namespace="##targetNamespace"/>
+        </xs:sequence>
+    </xs:complexType>
+    <xs:complexType name="substitution">
+        <xs:choice>
+            <xs:element ref="head"/>
+            <xs:element ref="member"/>
+        </xs:choice>
+    </xs:complexType>
+
+    <xs:complexType name="inconsistent">
+        <xs:sequence>
+            <xs:element name="a" type="xs:string"/>
+            <xs:choice>
+                <xs:element name="b" type="xs:string"/>
+                <xs:element name="a" type="xs:int"/>
+            </xs:choice>
+        </xs:sequence>
+    </xs:complexType>
+    <xs:complexType name="anonymous">
+        <xs:sequence>
+            <xs:element name="a">
+                <xs:complexType/>
+            </xs:element>
+            <xs:element name="b" type="xs:string"/>
+            <xs:element name="a">
+                <xs:complexType/>
+            </xs:element>
+        </xs:sequence>
+    </xs:complexType>
+    <xs:complexType name="inconsistent-member">
+        <xs:sequence>
+            <xs:element ref="head"/>
+            <xs:element name="member" type="xs:string"/>
+        </xs:sequence>
+    </xs:complexType>
+
+    <xs:element name="head" type="xs:string"/>
+    <xs:element name="member" type="xs:token" substitutionGroup="head"/>
+</xs:schema>
+"###;
+
+    fn check(name: &str) -> Vec<(String, String)> {
+        let set = SchemaSet::new(vec![Schema::parse(XML).unwrap()]);
+        set.check_particles(&QName::new(Some("urn:t"), name))
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.code, d.message))
+            .collect()
+    }
+
+    #[test]
+    fn unique_particle_attribution() {
+        assert_eq!(check("deterministic"), vec![]);
+
+        for name in [
+            "optional-first",
+            "repeated-choice",
+            "wildcard",
+            "substitution",
+        ] {
+            let r = check(name);
+            assert_eq!(r.len(), 1, "{}: {:?}", name, r);
+            assert_eq!(r[0].0, "cos-nonambig");
+        }
+
+        assert_eq!(
+            check("wildcard")[0].1,
+            "element '{urn:t}a' and a wildcard can match the same element, the content model is \
+             ambiguous"
+        );
+    }
+
+    #[test]
+    fn element_declarations_consistent() {
+        for name in ["inconsistent", "anonymous", "inconsistent-member"] {
+            let r = check(name);
+            assert_eq!(r.len(), 1, "{}: {:?}", name, r);
+            assert_eq!(r[0].0, "cos-element-consistent");
+        }
+    }
+}
@@ -0,0 +1,685 @@
+use std::fmt::Display;
+
+use regex::Regex;
+
+/// The Unicode blocks `\p{IsBlock}` can name, as listed in appendix F of XML Schema Part 2.
+const BLOCKS: [(&str, u32, u32); 92] = [
+    ("BasicLatin", 0x0000, 0x007F),
+    ("Latin-1Supplement", 0x0080, 0x00FF),
+    ("LatinExtended-A", 0x0100, 0x017F),
+    ("LatinExtended-B", 0x0180, 0x024F),
+    ("IPAExtensions", 0x0250, 0x02AF),
+    ("SpacingModifierLetters", 0x02B0, 0x02FF),
+    ("CombiningDiacriticalMarks", 0x0300, 0x036F),
+    ("Greek", 0x0370, 0x03FF),
+    ("Cyrillic", 0x0400, 0x04FF),
+    ("Armenian", 0x0530, 0x058F),
+    ("Hebrew", 0x0590, 0x05FF),
+    ("Arabic", 0x0600, 0x06FF),
+    ("Syriac", 0x0700, 0x074F),
+    ("Thaana", 0x0780, 0x07BF),
+    ("Devanagari", 0x0900, 0x097F),
+    ("Bengali", 0x0980, 0x09FF),
+    ("Gurmukhi", 0x0A00, 0x0A7F),
+    ("Gujarati", 0x0A80, 0x0AFF),
+    ("Oriya", 0x0B00, 0x0B7F),
+    ("Tamil", 0x0B80, 0x0BFF),
+    ("Telugu", 0x0C00, 0x0C7F),
+    ("Kannada", 0x0C80, 0x0CFF),
+    ("Malayalam", 0x0D00, 0x0D7F),
+    ("Sinhala", 0x0D80, 0x0DFF),
+    ("Thai", 0x0E00, 0x0E7F),
+    ("Lao", 0x0E80, 0x0EFF),
+    ("Tibetan", 0x0F00, 0x0FFF),
+    ("Myanmar", 0x1000, 0x109F),
+    ("Georgian", 0x10A0, 0x10FF),
+    ("HangulJamo", 0x1100, 0x11FF),
+    ("Ethiopic", 0x1200, 0x137F),
+    ("Cherokee", 0x13A0, 0x13FF),
+    ("UnifiedCanadianAboriginalSyllabics", 0x1400, 0x167F),
+    ("Ogham", 0x1680, 0x169F),
+    ("Runic", 0x16A0, 0x16FF),
+    ("Khmer", 0x1780, 0x17FF),
+    ("Mongolian", 0x1800, 0x18AF),
+    ("LatinExtendedAdditional", 0x1E00, 0x1EFF),
+    ("GreekExtended", 0x1F00, 0x1FFF),
+    ("GeneralPunctuation", 0x2000, 0x206F),
+    ("SuperscriptsandSubscripts", 0x2070, 0x209F),
+    ("CurrencySymbols", 0x20A0, 0x20CF),
+    ("CombiningMarksforSymbols", 0x20D0, 0x20FF),
+    ("LetterlikeSymbols", 0x2100, 0x214F),
+    ("NumberForms", 0x2150, 0x218F),
+    ("Arrows", 0x2190, 0x21FF),
+    ("MathematicalOperators", 0x2200, 0x22FF),
+    ("MiscellaneousTechnical", 0x2300, 0x23FF),
+    ("ControlPictures", 0x2400, 0x243F),
+    ("OpticalCharacterRecognition", 0x2440, 0x245F),
+    ("EnclosedAlphanumerics", 0x2460, 0x24FF),
+    ("BoxDrawing", 0x2500, 0x257F),
+    ("BlockElements", 0x2580, 0x259F),
+    ("GeometricShapes", 0x25A0, 0x25FF),
+    ("MiscellaneousSymbols", 0x2600, 0x26FF),
+    ("Dingbats", 0x2700, 0x27BF),
+    ("BraillePatterns", 0x2800, 0x28FF),
+    ("CJKRadicalsSupplement", 0x2E80, 0x2EFF),
+    ("KangxiRadicals", 0x2F00, 0x2FDF),
+    ("IdeographicDescriptionCharacters", 0x2FF0, 0x2FFF),
+    ("CJKSymbolsandPunctuation", 0x3000, 0x303F),
+    ("Hiragana", 0x3040, 0x309F),
+    ("Katakana", 0x30A0, 0x30FF),
+    ("Bopomofo", 0x3100, 0x312F),
+    ("HangulCompatibilityJamo", 0x3130, 0x318F),
+    ("Kanbun", 0x3190, 0x319F),
+    ("BopomofoExtended", 0x31A0, 0x31BF),
+    ("EnclosedCJKLettersandMonths", 0x3200, 0x32FF),
+    ("CJKCompatibility", 0x3300, 0x33FF),
+    ("CJKUnifiedIdeographsExtensionA", 0x3400, 0x4DB5),
+    ("CJKUnifiedIdeographs", 0x4E00, 0x9FFF),
+    ("YiSyllables", 0xA000, 0xA48F),
+    ("YiRadicals", 0xA490, 0xA4CF),
+    ("HangulSyllables", 0xAC00, 0xD7A3),
+    ("HighSurrogates", 0xD800, 0xDB7F),
+    ("HighPrivateUseSurrogates", 0xDB80, 0xDBFF),
+    ("LowSurrogates", 0xDC00, 0xDFFF),
+    ("CJKCompatibilityIdeographs", 0xF900, 0xFAFF),
+    ("AlphabeticPresentationForms", 0xFB00, 0xFB4F),
+    ("ArabicPresentationForms-A", 0xFB50, 0xFDFF),
+    ("CombiningHalfMarks", 0xFE20, 0xFE2F),
+    ("CJKCompatibilityForms", 0xFE30, 0xFE4F),
+    ("SmallFormVariants", 0xFE50, 0xFE6F),
+    ("ArabicPresentationForms-B", 0xFE70, 0xFEFE),
+    ("HalfwidthandFullwidthForms", 0xFF00, 0xFFEF),
+    ("OldItalic", 0x10300, 0x1032F),
+    ("Gothic", 0x10330, 0x1034F),
+    ("Deseret", 0x10400, 0x1044F),
+    ("ByzantineMusicalSymbols", 0x1D000, 0x1D0FF),
+    ("MusicalSymbols", 0x1D100, 0x1D1FF),
+    ("MathematicalAlphanumericSymbols", 0x1D400, 0x1D7FF),
+    ("CJKUnifiedIdeographsExtensionB", 0x20000, 0x2A6D6),
+];
+
+/// Blocks that consist of more than one range, and blocks outside the Basic Multilingual Plane
+/// that are listed separately.
+const SPLIT_BLOCKS: [(&str, &[(u32, u32)]); 4] = [
+    (
+        "PrivateUse",
+        &[(0xE000, 0xF8FF), (0xF0000, 0xFFFFD), (0x100000, 0x10FFFD)],
+    ),
+    ("Specials", &[(0xFEFF, 0xFEFF), (0xFFF0, 0xFFFD)]),
+    (
+        "CJKCompatibilityIdeographsSupplement",
+        &[(0x2F800, 0x2FA1F)],
+    ),
+    ("Tags", &[(0xE0000, 0xE007F)]),
+];
+
+/// Unicode general categories `\p{X}` can name.
+const CATEGORIES: [&str; 37] = [
+    "L", "Lu", "Ll", "Lt", "Lm", "Lo", "M", "Mn", "Mc", "Me", "N", "Nd", "Nl", "No", "P", "Pc",
+    "Pd", "Ps", "Pe", "Pi", "Pf", "Po", "Z", "Zs", "Zl", "Zp", "S", "Sm", "Sc", "Sk", "So", "C",
+    "Cc", "Cf", "Co", "Cn", "Cs",
+];
+
+/// `NameStartChar` of XML 1.0, fifth edition, for `\i`.
+const NAME_START: &[(u32, u32)] = &[
+    (0x3A, 0x3A),
+    (0x41, 0x5A),
+    (0x5F, 0x5F),
+    (0x61, 0x7A),
+    (0xC0, 0xD6),
+    (0xD8, 0xF6),
+    (0xF8, 0x2FF),
+    (0x370, 0x37D),
+    (0x37F, 0x1FFF),
+    (0x200C, 0x200D),
+    (0x2070, 0x218F),
+    (0x2C00, 0x2FEF),
+    (0x3001, 0xD7FF),
+    (0xF900, 0xFDCF),
+    (0xFDF0, 0xFFFD),
+    (0x10000, 0xEFFFF),
+];
+
+/// `NameChar` of XML 1.0, fifth edition, without the `NameStartChar`s, for `\c`.
+const NAME_REST: &[(u32, u32)] = &[
+    (0x2D, 0x2E),
+    (0x30, 0x39),
+    (0xB7, 0xB7),
+    (0x300, 0x36F),
+    (0x203F, 0x2040),
+];
+
+/// A regular expression of the XML Schema dialect, as used by `pattern` facets, compiled to a
+/// matcher. Patterns are implicitly anchored: they match whole values only.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    source: String,
+    regex: Regex,
+}
+
+/// Why a pattern could not be compiled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternError {
+    pub pattern: String,
+
+    /// Offset of the problem in characters.
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid pattern '{}' at position {}: {}",
+            self.pattern, self.position, self.message
+        )
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+impl Pattern {
+    /// Compile an XML Schema 1.0 regular expression.
+    pub fn new(pattern: &str) -> Result<Self, PatternError> {
+        Self::with_version(pattern, 1.0)
+    }
+
+    /// Compile a regular expression of the XSD version `version`, see
+    /// [`crate::prelude::ParseOptions::processor_version`]. Starting with 1.1, block names that
+    /// are not known are accepted and match every character.
+    pub fn with_version(pattern: &str, version: f64) -> Result<Self, PatternError> {
+        let mut translator = Translator {
+            chars: pattern.chars().collect(),
+            position: 0,
+            version,
+        };
+        let translated = translator.translate()?;
+
+        let regex = Regex::new(&format!(r"\A(?:{})\z", translated)).map_err(|e| PatternError {
+            pattern: pattern.to_string(),
+            position: 0,
+            message: e.to_string(),
+        })?;
+
+        Ok(Pattern {
+            source: pattern.to_string(),
+            regex,
+        })
+    }
+
+    /// True if the whole of `value` matches.
+    pub fn is_match(&self, value: &str) -> bool {
+        self.regex.is_match(value)
+    }
+
+    /// The pattern as written in the schema.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Something inside a character class.
+enum ClassItem {
+    Char(char),
+
+    /// A class escape like `\d`, translated.
+    Class(String),
+}
+
+/// Translates the XSD dialect to the syntax of the `regex` crate.
+struct Translator {
+    chars: Vec<char>,
+    position: usize,
+    version: f64,
+}
+
+impl Translator {
+    fn translate(&mut self) -> Result<String, PatternError> {
+        let r = self.branches()?;
+        match self.peek() {
+            None => Ok(r),
+            Some(')') => Err(self.error("unmatched ')'")),
+            Some(c) => Err(self.error(&format!("unexpected '{}'", c))),
+        }
+    }
+
+    fn error(&self, message: &str) -> PatternError {
+        PatternError {
+            pattern: self.chars.iter().collect(),
+            position: self.position,
+            message: message.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.position + offset).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let r = self.peek() == Some(c);
+        if r {
+            self.position += 1;
+        }
+        r
+    }
+
+    /// `branch ('|' branch)*`
+    fn branches(&mut self) -> Result<String, PatternError> {
+        let mut r = self.branch()?;
+        while self.eat('|') {
+            r.push('|');
+            r.push_str(&self.branch()?);
+        }
+
+        Ok(r)
+    }
+
+    /// A sequence of atoms with optional quantifiers.
+    fn branch(&mut self) -> Result<String, PatternError> {
+        let mut r = String::new();
+
+        while let Some(c) = self.peek() {
+            let atom = match c {
+                '|' | ')' => break,
+                '(' => {
+                    self.position += 1;
+                    let inner = self.branches()?;
+                    if !self.eat(')') {
+                        return Err(self.error("missing ')'"));
+                    }
+                    format!("(?:{})", inner)
+                }
+                '[' => {
+                    self.position += 1;
+                    self.class()?
+                }
+                '\\' => match self.escape()? {
+                    ClassItem::Char(c) => literal(c),
+                    ClassItem::Class(class) => class,
+                },
+                '.' => {
+                    self.position += 1;
+                    r"[^\n\r]".to_string()
+                }
+                '?' | '*' | '+' | '{' => return Err(self.error("nothing to repeat")),
+                ']' | '}' => return Err(self.error(&format!("'{}' must be escaped", c))),
+                c => {
+                    self.position += 1;
+                    literal(c)
+                }
+            };
+
+            r.push_str(&atom);
+            r.push_str(&self.quantifier()?);
+        }
+
+        Ok(r)
+    }
+
+    /// `?`, `*`, `+`, `{n}`, `{n,}` or `{n,m}`, or nothing.
+    fn quantifier(&mut self) -> Result<String, PatternError> {
+        let r = match self.peek() {
+            Some(c @ ('?' | '*' | '+')) => {
+                self.position += 1;
+                c.to_string()
+            }
+            Some('{') => {
+                self.position += 1;
+                let min = self.number()?;
+                let max = match self.eat(',') {
+                    true if self.peek() == Some('}') => None,
+                    true => Some(self.number()?),
+                    false => Some(min),
+                };
+                if !self.eat('}') {
+                    return Err(self.error("missing '}'"));
+                }
+                match max {
+                    Some(max) if max < min => {
+                        return Err(self.error(&format!(
+                            "the minimum {} is greater than the maximum {}",
+                            min, max
+                        )))
+                    }
+                    Some(max) if max == min => format!("{{{}}}", min),
+                    Some(max) => format!("{{{},{}}}", min, max),
+                    None => format!("{{{},}}", min),
+                }
+            }
+            _ => return Ok(String::new()),
+        };
+
+        if matches!(self.peek(), Some('?' | '*' | '+' | '{')) {
+            return Err(self.error("a quantifier must follow an atom"));
+        }
+
+        Ok(r)
+    }
+
+    fn number(&mut self) -> Result<u32, PatternError> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(self.error("expected a number"));
+        }
+
+        let digits: String = self.chars[start..self.position].iter().collect();
+        digits
+            .parse()
+            .map_err(|_| self.error("the number is too large"))
+    }
+
+    /// A character class expression, after the opening `[`.
+    fn class(&mut self) -> Result<String, PatternError> {
+        let negated = self.eat('^');
+        let mut items = String::new();
+        let mut empty = true;
+        let mut subtraction = None;
+
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("missing ']'"));
+            };
+
+            match c {
+                ']' if empty => return Err(self.error("empty character class")),
+                ']' => break,
+                '-' if self.peek_at(1) == Some('[') => {
+                    if empty {
+                        return Err(self.error("nothing to subtract from"));
+                    }
+                    self.position += 2;
+                    subtraction = Some(self.class()?);
+                    if self.peek() != Some(']') {
+                        return Err(self.error("a subtraction must end the character class"));
+                    }
+                    break;
+                }
+                '-' if !empty && self.peek_at(1) != Some(']') => {
+                    return Err(self
+                        .error("'-' must be escaped unless it starts or ends the character class"))
+                }
+                '[' => return Err(self.error("'[' must be escaped in a character class")),
+                _ => {}
+            }
+
+            empty = false;
+            let start = match self.class_char()? {
+                ClassItem::Char(c) => c,
+                ClassItem::Class(class) => {
+                    items.push_str(&class);
+                    continue;
+                }
+            };
+
+            let is_range = self.peek() == Some('-')
+                && !matches!(self.peek_at(1), Some('[' | ']') | None)
+                && start != '-';
+            if !is_range {
+                items.push_str(&class_char(start));
+                continue;
+            }
+
+            self.position += 1;
+            if self.peek() == Some('-') {
+                return Err(self.error("'-' must be escaped at the end of a range"));
+            }
+            let ClassItem::Char(end) = self.class_char()? else {
+                return Err(self.error("a range cannot end with a class escape"));
+            };
+            if end < start {
+                return Err(self.error(&format!("the range {}-{} is out of order", start, end)));
+            }
+            items.push_str(&format!("{}-{}", class_char(start), class_char(end)));
+        }
+
+        // The closing `]`.
+        self.position += 1;
+
+        let class = format!("[{}{}]", if negated { "^" } else { "" }, items);
+        Ok(match subtraction {
+            Some(subtraction) => format!("[{}--{}]", class, subtraction),
+            None => class,
+        })
+    }
+
+    fn class_char(&mut self) -> Result<ClassItem, PatternError> {
+        match self.peek() {
+            Some('\\') => self.escape(),
+            Some(c) => {
+                self.position += 1;
+                Ok(ClassItem::Char(c))
+            }
+            None => Err(self.error("missing ']'")),
+        }
+    }
+
+    /// A single character escape or a class escape, starting at the backslash.
+    fn escape(&mut self) -> Result<ClassItem, PatternError> {
+        self.position += 1;
+        let Some(c) = self.peek() else {
+            return Err(self.error("the pattern ends with '\\'"));
+        };
+        self.position += 1;
+
+        let class = match c {
+            'n' => return Ok(ClassItem::Char('\n')),
+            'r' => return Ok(ClassItem::Char('\r')),
+            't' => return Ok(ClassItem::Char('\t')),
+            '\\' | '|' | '.' | '?' | '*' | '+' | '(' | ')' | '{' | '}' | '-' | '[' | ']' | '^' => {
+                return Ok(ClassItem::Char(c))
+            }
+            's' => r"[ \t\n\r]".to_string(),
+            'S' => r"[^ \t\n\r]".to_string(),
+            'i' => ranges(NAME_START, false),
+            'I' => ranges(NAME_START, true),
+            'c' => format!(
+                "[{}{}]",
+                ranges(NAME_START, false),
+                ranges(NAME_REST, false)
+            ),
+            'C' => format!(
+                "[^{}{}]",
+                ranges(NAME_START, false),
+                ranges(NAME_REST, false)
+            ),
+            'd' => r"\p{Nd}".to_string(),
+            'D' => r"\P{Nd}".to_string(),
+            'w' => r"[^\p{P}\p{Z}\p{C}]".to_string(),
+            'W' => r"[\p{P}\p{Z}\p{C}]".to_string(),
+            'p' | 'P' => {
+                let property = self.property()?;
+                match c {
+                    'p' => property,
+                    _ => format!("[^{}]", property),
+                }
+            }
+            _ => {
+                self.position -= 1;
+                return Err(self.error(&format!("unknown escape '\\{}'", c)));
+            }
+        };
+
+        Ok(ClassItem::Class(class))
+    }
+
+    /// The `{name}` of `\p{name}`, translated to a character class.
+    fn property(&mut self) -> Result<String, PatternError> {
+        if !self.eat('{') {
+            return Err(self.error("expected '{' after '\\p'"));
+        }
+
+        let start = self.position;
+        while self.peek().is_some_and(|c| c != '}') {
+            self.position += 1;
+        }
+        let name: String = self.chars[start..self.position].iter().collect();
+        if !self.eat('}') {
+            return Err(self.error("missing '}'"));
+        }
+
+        if let Some(block) = name.strip_prefix("Is") {
+            if let Some((_, start, end)) = BLOCKS.iter().find(|(b, _, _)| *b == block) {
+                return Ok(ranges(&[(*start, *end)], false));
+            }
+            if let Some((_, r)) = SPLIT_BLOCKS.iter().find(|(b, _)| *b == block) {
+                return Ok(ranges(r, false));
+            }
+            if self.version >= 1.1 {
+                return Ok(r"[\x{0}-\x{10FFFF}]".to_string());
+            }
+
+            self.position = start;
+            return Err(self.error(&format!("unknown block '{}'", block)));
+        }
+
+        if CATEGORIES.contains(&name.as_str()) {
+            return Ok(format!(r"\p{{{}}}", name));
+        }
+
+        self.position = start;
+        Err(self.error(&format!("unknown category '{}'", name)))
+    }
+}
+
+/// A character outside of a class.
+fn literal(c: char) -> String {
+    regex::escape(c.encode_utf8(&mut [0; 4]))
+}
+
+/// A character inside a class.
+fn class_char(c: char) -> String {
+    format!(r"\x{{{:X}}}", c as u32)
+}
+
+/// A character class of `ranges`. Surrogates are not characters of a Rust string, so they are
+/// left out, which can make the class empty.
+fn ranges(ranges: &[(u32, u32)], negated: bool) -> String {
+    let mut r = String::from(if negated { "[^" } else { "[" });
+    let mut empty = true;
+
+    for (start, end) in ranges.iter().copied() {
+        let parts = [(start, end.min(0xD7FF)), (start.max(0xE000), end)];
+        for (start, end) in parts.into_iter().filter(|(s, e)| s <= e) {
+            r.push_str(&format!(r"\x{{{:X}}}-\x{{{:X}}}", start, end));
+            empty = false;
+        }
+    }
+
+    if empty {
+        // A class without characters.
+        return match negated {
+            true => r"[\x{0}-\x{10FFFF}]".to_string(),
+            false => r"[\x{0}--\x{0}]".to_string(),
+        };
+    }
+
+    r.push(']');
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, value: &str) -> bool {
+        Pattern::new(pattern).unwrap().is_match(value)
+    }
+
+    fn error(pattern: &str) -> (usize, String) {
+        let e = Pattern::new(pattern).unwrap_err();
+        (e.position, e.message)
+    }
+
+    #[test]
+    fn anchored() {
+        assert!(matches("[A-Z]{3}", "ABC"));
+        assert!(!matches("[A-Z]{3}", "ABCD"));
+        assert!(!matches("a|b", "ab"));
+        assert!(matches("a|b", "b"));
+        assert!(!matches("abc", "abc\n"));
+
+        // `^` and `$` are ordinary characters.
+        assert!(matches("^a$", "^a$"));
+        assert!(matches("(a|b)+c?", "abba"));
+        assert!(matches("x{2,}", "xxxx"));
+        assert!(!matches(".", "\n"));
+    }
+
+    #[test]
+    fn escapes() {
+        assert!(matches(r"\i\c*", "xs:string"));
+        assert!(!matches(r"\i\c*", "1st"));
+        assert!(matches(r"[\i-[:]][\c-[:]]*", "_name-1.2"));
+        assert!(!matches(r"[\i-[:]][\c-[:]]*", "a:b"));
+        assert!(matches(r"\d+", "١٢٣"));
+        assert!(matches(r"\w+", "héllo"));
+        assert!(!matches(r"\w", "!"));
+        assert!(matches(r"\s\S", "\tx"));
+        assert!(matches(r"\p{Lu}\P{Lu}", "Ab"));
+        assert!(matches(r"\\\.\-\^", r"\.-^"));
+        assert!(matches(r"\p{IsBasicLatin}+", "abc"));
+        assert!(!matches(r"\p{IsBasicLatin}", "é"));
+        assert!(matches(r"\p{IsGreek}", "λ"));
+        assert!(!matches(r"\p{IsHighSurrogates}", "a"));
+    }
+
+    #[test]
+    fn classes() {
+        assert!(matches("[a-z-[aeiou]]+", "xyz"));
+        assert!(!matches("[a-z-[aeiou]]", "e"));
+        assert!(matches("[^a-z-[0-9]]", "A"));
+        assert!(!matches("[^a-z-[0-9]]", "5"));
+        assert!(matches("[a-c-[b-[c]]]+", "ac"));
+        assert!(matches("[-a]", "-"));
+        assert!(matches("[a-]", "-"));
+        assert!(matches(r"[\-+]", "+"));
+        assert!(matches("[.^$]+", "$^."));
+        assert!(matches(r"[\p{L}\d]+", "a1"));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(error("a**").1, "a quantifier must follow an atom");
+        assert_eq!(error("*a"), (0, "nothing to repeat".to_string()));
+        assert_eq!(error("(ab").1, "missing ')'");
+        assert_eq!(error("ab)").1, "unmatched ')'");
+        assert_eq!(error("[]").1, "empty character class");
+        assert_eq!(
+            error("[a-c-e]").1,
+            "'-' must be escaped unless it starts or ends the character class"
+        );
+        assert_eq!(error("[z-a]").1, "the range z-a is out of order");
+        assert_eq!(
+            error("[a-z-[x]b]").1,
+            "a subtraction must end the character class"
+        );
+        assert_eq!(error(r"\b").1, r"unknown escape '\b'");
+        assert_eq!(
+            error("a{3,2}").1,
+            "the minimum 3 is greater than the maximum 2"
+        );
+        assert_eq!(
+            error(r"\p{IsKlingon}"),
+            (3, "unknown block 'Klingon'".to_string())
+        );
+        assert_eq!(error(r"\p{Xx}").1, "unknown category 'Xx'");
+
+        assert!(Pattern::with_version(r"\p{IsKlingon}", 1.1)
+            .unwrap()
+            .is_match("x"));
+
+        let e = Pattern::new("a{").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Invalid pattern 'a{' at position 2: expected a number"
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use std::io::Read;
+
+use crate::{
+    diagnostic::Diagnostic, error::Error, qname::QName, schema_set::SchemaSet,
+    source_span::SourceSpan, validator::Validator, value::SimpleValue,
+};
+
+/// Whether an element or attribute was found valid, the `[validity]` property of the
+/// post-schema-validation infoset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Validity {
+    /// The item and everything inside it is valid.
+    Valid,
+
+    /// The item or something inside it is invalid. The diagnostics tell why.
+    Invalid,
+
+    /// The item was not validated, e.g. because a wildcard skips it or lax validation found no
+    /// declaration for it.
+    NotKnown,
+}
+
+/// An attribute annotated with the results of validation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedAttribute {
+    pub name: QName,
+
+    /// The named type of the attribute. `None` if the type is anonymous or the attribute was
+    /// not validated.
+    pub r#type: Option<QName>,
+    pub validity: Validity,
+
+    /// The value as written in the instance, or the default of the schema.
+    pub lexical: String,
+
+    /// The normalized value, if it is valid.
+    pub value: Option<SimpleValue>,
+
+    /// True if the attribute is missing in the instance and was added from the default or fixed
+    /// value of its declaration.
+    pub defaulted: bool,
+}
+
+/// An element annotated with the results of validation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedElement {
+    pub name: QName,
+
+    /// The governing type of the element, the type named by `xsi:type` if there is one. `None`
+    /// if the type is anonymous or the element was not validated.
+    pub r#type: Option<QName>,
+    pub validity: Validity,
+
+    /// `xsi:nil` is true for the element.
+    pub nil: bool,
+
+    /// The attributes of the instance, and the attributes added from defaults, by name.
+    pub attributes: Vec<TypedAttribute>,
+    pub children: Vec<TypedElement>,
+
+    /// The normalized value of an element with a simple type or simple content, if it is valid.
+    pub value: Option<SimpleValue>,
+
+    /// True if the element is empty in the instance and `value` is the default or fixed value
+    /// of its declaration.
+    pub defaulted: bool,
+    pub span: Option<SourceSpan>,
+}
+
+impl TypedElement {
+    /// The attribute `name`, including attributes added from defaults.
+    pub fn attribute(&self, name: &QName) -> Option<&TypedAttribute> {
+        self.attributes.iter().find(|a| a.name == *name)
+    }
+
+    /// The children named `name`, in document order.
+    pub fn children_named<'e>(&'e self, name: &'e QName) -> impl Iterator<Item = &'e TypedElement> {
+        self.children.iter().filter(move |c| c.name == *name)
+    }
+}
+
+/// An event of the annotated document, see [`ValidationStream::annotated`].
+///
+/// [`ValidationStream::annotated`]: crate::prelude::ValidationStream::annotated
+#[derive(Debug, Clone, PartialEq)]
+pub enum PsviEvent {
+    StartElement {
+        name: QName,
+
+        /// The governing type, the type named by `xsi:type` if there is one.
+        r#type: Option<QName>,
+        nil: bool,
+        attributes: Vec<TypedAttribute>,
+
+        /// The position of the start tag.
+        span: Option<SourceSpan>,
+    },
+    EndElement {
+        validity: Validity,
+        value: Option<SimpleValue>,
+        defaulted: bool,
+
+        /// From the start tag to the end tag.
+        span: Option<SourceSpan>,
+    },
+}
+
+impl SchemaSet {
+    /// Validate the instance document `xml` and return it annotated with types, validity,
+    /// defaults and normalized values, together with the diagnostics of
+    /// [`SchemaSet::validate`].
+    pub fn annotate(&self, xml: &str) -> Result<(TypedElement, Vec<Diagnostic>), Error> {
+        Validator::new(self).annotate_reader(xml.as_bytes(), None)
+    }
+}
+
+impl Validator<'_> {
+    /// Like [`SchemaSet::annotate`], reading the document from `reader`. `uri` is the location
+    /// of the document.
+    pub fn annotate_reader<R: Read>(
+        &self,
+        reader: R,
+        uri: Option<&str>,
+    ) -> Result<(TypedElement, Vec<Diagnostic>), Error> {
+        let mut builder = TreeBuilder::default();
+        let mut diagnostics = Vec::new();
+        self.annotate_stream(
+            reader,
+            uri,
+            |event| builder.event(event),
+            |d| diagnostics.push(d),
+        )?;
+
+        // The reader fails on documents without a root element.
+        let root = builder
+            .root
+            .expect("well-formed document without root element");
+        Ok((root, diagnostics))
+    }
+}
+
+/// Builds the typed tree from the events of an annotated stream.
+#[derive(Default)]
+struct TreeBuilder {
+    open: Vec<TypedElement>,
+    root: Option<TypedElement>,
+}
+
+impl TreeBuilder {
+    fn event(&mut self, event: PsviEvent) {
+        match event {
+            PsviEvent::StartElement {
+                name,
+                r#type,
+                nil,
+                attributes,
+                span,
+            } => self.open.push(TypedElement {
+                name,
+                r#type,
+                validity: Validity::NotKnown,
+                nil,
+                attributes,
+                children: Vec::new(),
+                value: None,
+                defaulted: false,
+                span,
+            }),
+            PsviEvent::EndElement {
+                validity,
+                value,
+                defaulted,
+                span,
+            } => {
+                let Some(mut element) = self.open.pop() else {
+                    return;
+                };
+                element.validity = validity;
+                element.value = value;
+                element.defaulted = defaulted;
+                element.span = span;
+
+                match self.open.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => self.root = Some(element),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Schema;
+
+    const XSD: &str = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="midi-channel">
+        <xs:restriction base="xs:positiveInteger">
+            <xs:maxInclusive value="16"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="channels">
+        <xs:list itemType="midi-channel"/>
+    </xs:simpleType>
+
+    <xs:element name="track">
+        <xs:complexType>
+            <xs:sequence>
+                <xs:element name="channel" type="midi-channel"/>
+                <xs:element name="volume" type="xs:double" default="0.8"/>
+                <xs:element name="layers" type="channels" minOccurs="0"/>
+                <xs:any processContents="skip" minOccurs="0"/>
+            </xs:sequence>
+            <xs:attribute name="name" type="xs:token"/>
+            <xs:attribute name="solo" type="xs:boolean" default="false"/>
+            <xs:attribute name="target" type="xs:IDREF"/>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>
+"#;
+
+    fn set() -> SchemaSet {
+        SchemaSet::new(vec![Schema::parse(XSD).unwrap()])
+    }
+
+    #[test]
+    fn typed_tree() {
+        let xml = r#"<track name="  Lead   Synth "><channel> 10 </channel><volume/><layers>1  2</layers><x/></track>"#;
+        let (root, diagnostics) = set().annotate(xml).unwrap();
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(root.validity, Validity::Valid);
+        assert_eq!(root.r#type, None);
+
+        let name = root.attribute(&QName::new(None, "name")).unwrap();
+        assert_eq!(name.value.as_ref().unwrap().normalized, "Lead Synth");
+        assert!(!name.defaulted);
+
+        let solo = root.attribute(&QName::new(None, "solo")).unwrap();
+        assert!(solo.defaulted);
+        assert_eq!(solo.value.as_ref().unwrap().as_boolean(), Some(false));
+
+        let channel = &root.children[0];
+        assert_eq!(channel.r#type, Some(QName::new(None, "midi-channel")));
+        assert_eq!(channel.value.as_ref().unwrap().as_integer(), Some(10));
+
+        let volume = &root.children[1];
+        assert!(volume.defaulted);
+        assert_eq!(volume.value.as_ref().unwrap().as_float(), Some(0.8));
+
+        let layers = root.children[2].value.as_ref().unwrap();
+        assert_eq!(layers.items(), vec!["1", "2"]);
+        assert_eq!(layers.as_integer(), None);
+
+        assert_eq!(root.children[3].validity, Validity::NotKnown);
+    }
+
+    #[test]
+    fn invalid() {
+        let xml = r#"<track solo="maybe"><channel>17</channel><volume>1</volume></track>"#;
+        let (root, diagnostics) = set().annotate(xml).unwrap();
+        assert_eq!(diagnostics.len(), 2);
+
+        assert_eq!(root.validity, Validity::Invalid);
+        let solo = root.attribute(&QName::new(None, "solo")).unwrap();
+        assert_eq!(solo.validity, Validity::Invalid);
+        assert_eq!(solo.lexical, "maybe");
+        assert_eq!(solo.value, None);
+
+        assert_eq!(root.children[0].validity, Validity::Invalid);
+        assert_eq!(root.children[0].value, None);
+        assert_eq!(root.children[1].validity, Validity::Valid);
+        assert!(!root.children[1].defaulted);
+    }
+
+    #[test]
+    fn dangling_reference() {
+        let xml = r#"<track target="missing"><channel>1</channel><volume/></track>"#;
+        let (root, diagnostics) = set().annotate(xml).unwrap();
+        assert_eq!(diagnostics[0].code, "cvc-id.1");
+        assert_eq!(diagnostics.len(), 1);
+
+        // Only known to be invalid at the end of the document.
+        assert_eq!(root.validity, Validity::Invalid);
+        assert_eq!(root.children[0].validity, Validity::Valid);
+    }
+}
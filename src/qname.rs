@@ -0,0 +1,27 @@
+use std::fmt::Display;
+
+/// A name together with the namespace it belongs to, as obtained by resolving a prefixed name
+/// like `xs:token` against the namespace bindings of a schema document.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QName {
+    pub namespace: Option<String>,
+    pub name: String,
+}
+
+impl QName {
+    pub fn new(namespace: Option<&str>, name: &str) -> Self {
+        QName {
+            namespace: namespace.map(|ns| ns.to_string()),
+            name: name.to_string(),
+        }
+    }
+}
+
+impl Display for QName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.namespace {
+            Some(namespace) => write!(f, "{{{}}}{}", namespace, self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
@@ -1,5 +1,17 @@
+use std::borrow::Cow;
+
 use xmltree::Element;
 
+use crate::{
+    all::All,
+    builtins::WhiteSpace,
+    diagnostic::Severity,
+    error::Error,
+    parse_context::ParseContext,
+    prelude::{Any, Attribute, AttributeGroup, Choice, Group, Sequence},
+    source_span::SourceSpan,
+};
+
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
 pub enum RestrictionContent {
     /// A single enumeration option. The only item is the attribute value.
@@ -11,78 +23,267 @@ pub enum RestrictionContent {
     /// Max value simple integer.
     MaxInclusive(i32),
 
+    /// Min value simple integer, the value itself is excluded.
+    MinExclusive(i32),
+
+    /// Max value simple integer, the value itself is excluded.
+    MaxExclusive(i32),
+
+    /// A bound whose value is not a simple integer, e.g. a decimal, a date or a duration, as
+    /// written in the schema. It is compared in the value space of the base type.
+    Bound(BoundKind, String),
+
+    /// Exact length: characters for strings, octets for binary data and items for lists.
+    Length(u64),
+    MinLength(u64),
+    MaxLength(u64),
+
+    /// The maximum number of digits of a decimal.
+    TotalDigits(u32),
+
+    /// The maximum number of digits of a decimal after the decimal point.
+    FractionDigits(u32),
+
+    WhiteSpace(WhiteSpace),
+
     /// A regex pattern
     Pattern(String),
 }
 
-#[derive(Debug, Default, Clone)]
+/// The facet a [`RestrictionContent::Bound`] stands for.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Clone, Copy)]
+pub enum BoundKind {
+    MinInclusive,
+    MaxInclusive,
+    MinExclusive,
+    MaxExclusive,
+}
+
+impl RestrictionContent {
+    /// A bound facet with the value `value`. Simple integers are kept in their own variant,
+    /// everything else as written in a [`RestrictionContent::Bound`].
+    pub fn new_bound(kind: BoundKind, value: &str) -> Self {
+        match value.parse::<i32>() {
+            Ok(v) if v.to_string() == value => match kind {
+                BoundKind::MinInclusive => RestrictionContent::MinInclusive(v),
+                BoundKind::MaxInclusive => RestrictionContent::MaxInclusive(v),
+                BoundKind::MinExclusive => RestrictionContent::MinExclusive(v),
+                BoundKind::MaxExclusive => RestrictionContent::MaxExclusive(v),
+            },
+            _ => RestrictionContent::Bound(kind, value.to_string()),
+        }
+    }
+
+    /// The kind and lexical form of a bound facet, whichever variant it is kept in.
+    pub fn bound(&self) -> Option<(BoundKind, Cow<'_, str>)> {
+        let (kind, value) = match self {
+            RestrictionContent::MinInclusive(v) => (BoundKind::MinInclusive, v),
+            RestrictionContent::MaxInclusive(v) => (BoundKind::MaxInclusive, v),
+            RestrictionContent::MinExclusive(v) => (BoundKind::MinExclusive, v),
+            RestrictionContent::MaxExclusive(v) => (BoundKind::MaxExclusive, v),
+            RestrictionContent::Bound(kind, v) => return Some((*kind, Cow::Borrowed(v))),
+            _ => return None,
+        };
+
+        Some((kind, Cow::Owned(value.to_string())))
+    }
+}
+
+/// Facets with a single value: the local name, what a valid value looks like and how to read
+/// the trimmed value.
+type FacetReader = (
+    &'static str,
+    &'static str,
+    fn(&str) -> Option<RestrictionContent>,
+);
+
+const FACETS: [FacetReader; 10] = [
+    ("minInclusive", "a value", |v| {
+        Some(RestrictionContent::new_bound(BoundKind::MinInclusive, v))
+    }),
+    ("maxInclusive", "a value", |v| {
+        Some(RestrictionContent::new_bound(BoundKind::MaxInclusive, v))
+    }),
+    ("minExclusive", "a value", |v| {
+        Some(RestrictionContent::new_bound(BoundKind::MinExclusive, v))
+    }),
+    ("maxExclusive", "a value", |v| {
+        Some(RestrictionContent::new_bound(BoundKind::MaxExclusive, v))
+    }),
+    ("length", "a non-negative integer", |v| {
+        v.parse().ok().map(RestrictionContent::Length)
+    }),
+    ("minLength", "a non-negative integer", |v| {
+        v.parse().ok().map(RestrictionContent::MinLength)
+    }),
+    ("maxLength", "a non-negative integer", |v| {
+        v.parse().ok().map(RestrictionContent::MaxLength)
+    }),
+    ("totalDigits", "a positive integer", |v| {
+        v.parse()
+            .ok()
+            .filter(|n| *n > 0)
+            .map(RestrictionContent::TotalDigits)
+    }),
+    ("fractionDigits", "a non-negative integer", |v| {
+        v.parse().ok().map(RestrictionContent::FractionDigits)
+    }),
+    ("whiteSpace", "preserve, replace or collapse", |v| {
+        v.parse().ok().map(RestrictionContent::WhiteSpace)
+    }),
+];
+
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Restriction {
     pub base: String,
 
     pub content: Vec<RestrictionContent>,
+
+    /// Where each entry of `content` was defined, in the same order.
+    pub facet_spans: Vec<Option<SourceSpan>>,
+
+    /// Attributes and content model of a restriction inside `simpleContent` or `complexContent`.
+    pub attributes: Vec<Attribute>,
+    pub attribute_groups: Vec<AttributeGroup>,
+    pub any_attribute: Option<Any>,
+    pub sequences: Vec<Sequence>,
+    pub choices: Vec<Choice>,
+    pub groups: Vec<Group>,
+    pub all: Option<All>,
+
+    pub span: Option<SourceSpan>,
 }
 
 impl Restriction {
+    #[allow(clippy::single_match)]
     pub fn is_enum(&self) -> bool {
         for c in self.content.iter() {
             match c {
-                RestrictionContent::Enumeration(_) => { return true; },
+                RestrictionContent::Enumeration(_) => {
+                    return true;
+                }
                 _ => {}
             }
         }
 
         false
     }
-    
-    pub fn read(element: &mut Element) -> Self {
-        let mut r = Restriction::default();
 
-        if element.attributes.contains_key("base") {
-            r.base = element.attributes["base"].clone();
-        }
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
+
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(element: &mut Element, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let mut r = Restriction {
+            span: ctx.span(element),
+            base: element.attributes.remove("base").unwrap_or_default(),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope("restriction", Some(&r.base), &r.span);
 
         // Read enum values.
-        while let Some(annotation) = element.take_child("enumeration") {
-            if annotation.attributes.contains_key("value") {
-                let option = annotation.attributes["value"].clone();
+        while let Some(mut annotation) = element.take_child("enumeration") {
+            if let (Some(option), span) = Self::read_facet(&mut annotation, ctx) {
                 r.content.push(RestrictionContent::Enumeration(option));
+                r.facet_spans.push(span);
             }
         }
 
-        // Min values
-        while let Some(min) = element.take_child("minInclusive") {
-            if min.attributes.contains_key("value") {
-                let option = min.attributes["value"].clone();
-                match option.parse::<i32>() {
-                    Ok(v) => {
-                        r.content.push(RestrictionContent::MinInclusive(v));
-                    }
-                    Err(_) => {}
-                };
+        // Bounds, lengths, digits and whitespace
+        Self::read_facets(element, ctx, &mut r);
+
+        // Pattern
+        while let Some(mut min) = element.take_child("pattern") {
+            if let (Some(option), span) = Self::read_facet(&mut min, ctx) {
+                r.content.push(RestrictionContent::Pattern(option));
+                r.facet_spans.push(span);
             }
         }
 
-        // Max values
-        while let Some(max) = element.take_child("maxInclusive") {
-            if max.attributes.contains_key("value") {
-                let option = max.attributes["value"].clone();
-                match option.parse::<i32>() {
-                    Ok(v) => {
-                        r.content.push(RestrictionContent::MaxInclusive(v));
+        while let Some(mut sequence) = element.take_child("sequence") {
+            r.sequences
+                .push(Sequence::read_with_context(&mut sequence, ctx)?);
+        }
+
+        while let Some(mut choice) = element.take_child("choice") {
+            r.choices.push(Choice::read_with_context(&mut choice, ctx)?);
+        }
+
+        while let Some(mut group) = element.take_child("group") {
+            r.groups.push(Group::read_with_context(&mut group, ctx)?);
+        }
+
+        if let Some(mut all) = element.take_child("all") {
+            r.all = Some(All::read_with_context(&mut all, ctx)?);
+        }
+
+        while let Some(mut attribute) = element.take_child("attribute") {
+            r.attributes
+                .push(Attribute::read_with_context(&mut attribute, ctx)?);
+        }
+
+        while let Some(mut attribute_group) = element.take_child("attributeGroup") {
+            r.attribute_groups.push(AttributeGroup::read_with_context(
+                &mut attribute_group,
+                ctx,
+            )?);
+        }
+
+        if let Some(mut any_attribute) = element.take_child("anyAttribute") {
+            r.any_attribute = Some(Any::read_with_context(&mut any_attribute, ctx)?);
+        }
+
+        ctx.leave(element);
+        Ok(r)
+    }
+
+    /// Read all facets of [`FACETS`]. Values that cannot be read are reported and ignored.
+    fn read_facets(element: &mut Element, ctx: &mut ParseContext, r: &mut Restriction) {
+        for (name, expected, read) in FACETS.iter() {
+            while let Some(mut child) = element.take_child(*name) {
+                if let (Some(option), span) = Self::read_facet(&mut child, ctx) {
+                    match read(option.trim()) {
+                        Some(facet) => {
+                            r.content.push(facet);
+                            r.facet_spans.push(span);
+                        }
+                        None => ctx.report(
+                            Severity::Warning,
+                            "unparsable-facet-value",
+                            format!(
+                                "{} value '{}' is not {} and is ignored",
+                                name, option, expected
+                            ),
+                            span,
+                        ),
                     }
-                    Err(_) => {}
-                };
+                }
             }
         }
+    }
 
-        // Pattern
-        while let Some(min) = element.take_child("pattern") {
-            if min.attributes.contains_key("value") {
-                let option = min.attributes["value"].clone();
-                r.content.push(RestrictionContent::Pattern(option));
-            }
+    /// Take the value and location of a facet element like `<xs:enumeration value="above"/>`.
+    fn read_facet(
+        element: &mut Element,
+        ctx: &mut ParseContext,
+    ) -> (Option<String>, Option<SourceSpan>) {
+        let span = ctx.span(element);
+        let value = element.attributes.remove("value");
+        let ctx = &mut *ctx.scope(&element.name, value.as_deref(), &span);
+
+        if value.is_none() {
+            ctx.report(
+                Severity::Error,
+                "missing-facet-value",
+                format!("{} without a value is ignored", element.name),
+                None,
+            );
         }
 
-        r
+        ctx.leave(element);
+
+        (value, span)
     }
 }
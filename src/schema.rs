@@ -1,92 +1,234 @@
-use std::path::PathBuf;
+use std::{collections::BTreeMap, io::Read, path::Path};
 
 use crate::{
+    derivation::DerivationSet,
+    diagnostic::Diagnostic,
+    document,
+    error::Error,
     import::Import,
-    prelude::{Annotation, AttributeGroup, ComplexType},
+    include::Include,
+    parse_context::ParseContext,
+    parse_options::ParseOptions,
+    prelude::{Annotation, Attribute, AttributeGroup, ComplexType, Group},
+    qname::QName,
+    schema_resolver::{FileSystemResolver, HttpResolver, SchemaResolver},
     simple_type::SimpleType,
+    source_span::SourceSpan,
+    version_control,
 };
 use xmltree::Element;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Schema {
+    pub target_namespace: Option<String>,
+    pub element_form_default: Option<String>,
+    pub attribute_form_default: Option<String>,
+    pub block_default: DerivationSet,
+    pub final_default: DerivationSet,
+
+    /// Prefix to namespace bindings in scope of the `<xs:schema>` element, used to resolve the
+    /// qualified names of references. The default namespace has the prefix `""`.
+    pub namespaces: BTreeMap<String, String>,
+
     pub annotations: Vec<Annotation>,
     pub imports: Vec<Import>,
+    pub includes: Vec<Include>,
     pub simple_types: Vec<SimpleType>,
     pub attribute_groups: Vec<AttributeGroup>,
     pub complex_types: Vec<ComplexType>,
+    pub elements: Vec<crate::element::Element>,
+    pub attributes: Vec<Attribute>,
+    pub groups: Vec<Group>,
+
+    pub span: Option<SourceSpan>,
 }
 
 impl Schema {
     /// Download and parse a schema definition from the internet.
-    pub fn download(url: &str) -> Result<Self, String> {
-        match reqwest::blocking::get(url) {
-            Ok(response) => match response.text() {
-                Ok(s) => Self::parse(&s),
-                Err(e) => {
-                    let err = format!("Could read content from response. Error: {:?}", e);
-                    Err(err)
-                }
-            },
-            Err(e) => {
-                let err = format!("Could download xml file. Error: {:?}", e);
-                Err(err)
-            }
-        }
+    pub fn download(url: &str) -> Result<Self, Error> {
+        Self::load_with_resolver(url, &HttpResolver)
+    }
+
+    /// Like [`Schema::download`], using the given options, and return the schema together with
+    /// everything the parser had to work around.
+    pub fn download_with_diagnostics(
+        url: &str,
+        options: &ParseOptions,
+    ) -> Result<(Self, Vec<Diagnostic>), Error> {
+        let mut ctx = ParseContext::new(options.clone());
+        let schema = Self::load_with_context(url, &HttpResolver, &mut ctx)?;
+
+        Ok((schema, ctx.diagnostics))
     }
 
     /// Load and parse a schema definition from disk.
-    pub fn load(path: &PathBuf) -> Result<Self, String> {
-        match std::fs::read_to_string(path) {
-            Ok(xml) => Self::parse(&xml),
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        Self::load_with_resolver(&path.to_string_lossy(), &FileSystemResolver)
+    }
 
-            Err(e) => {
-                let err = format!("Could open xml file. Error: {:?}", e);
-                Err(err)
-            }
-        }
+    /// Like [`Schema::load`], using the given options, and return the schema together with
+    /// everything the parser had to work around.
+    pub fn load_with_diagnostics(
+        path: &Path,
+        options: &ParseOptions,
+    ) -> Result<(Self, Vec<Diagnostic>), Error> {
+        let mut ctx = ParseContext::new(options.clone());
+        let schema =
+            Self::load_with_context(&path.to_string_lossy(), &FileSystemResolver, &mut ctx)?;
+
+        Ok((schema, ctx.diagnostics))
+    }
+
+    /// Read the schema document at `location` using the given resolver. Imports and includes
+    /// are not followed, see [`crate::prelude::SchemaSet`] for that.
+    pub fn load_with_resolver(
+        location: &str,
+        resolver: &dyn SchemaResolver,
+    ) -> Result<Self, Error> {
+        Self::load_with_context(location, resolver, &mut ParseContext::default())
+    }
+
+    /// Like [`Schema::load_with_resolver`]. Options are taken from the context and everything
+    /// the parser has to report is collected in it.
+    pub fn load_with_context(
+        location: &str,
+        resolver: &dyn SchemaResolver,
+        ctx: &mut ParseContext,
+    ) -> Result<Self, Error> {
+        let document = resolver.resolve(None, location, None)?;
+
+        ctx.document = Some(document.uri);
+        Self::parse_reader(document.content.as_slice(), ctx)
     }
 
     /// Parse the given string into a schema.
-    pub fn parse(xml: &str) -> Result<Self, String> {
-        match xmltree::Element::parse(xml.as_bytes()) {
-            Ok(mut element) => {
-                let item = Schema::read(&mut element);
-                Ok(item)
-            }
+    pub fn parse(xml: &str) -> Result<Self, Error> {
+        Self::parse_with_options(xml, &ParseOptions::default())
+    }
+
+    /// Parse the given string into a schema, using the given options.
+    ///
+    /// Parts of the schema excluded by `vc:minVersion`, `vc:maxVersion`, `vc:typeAvailable`,
+    /// `vc:typeUnavailable`, `vc:facetAvailable` or `vc:facetUnavailable` are removed before the
+    /// schema is read.
+    pub fn parse_with_options(xml: &str, options: &ParseOptions) -> Result<Self, Error> {
+        Self::parse_with_context(xml, &mut ParseContext::new(options.clone()))
+    }
+
+    /// Parse the given string into a schema and return it together with everything the parser
+    /// had to work around.
+    pub fn parse_with_diagnostics(
+        xml: &str,
+        options: &ParseOptions,
+    ) -> Result<(Self, Vec<Diagnostic>), Error> {
+        let mut ctx = ParseContext::new(options.clone());
+        let schema = Self::parse_with_context(xml, &mut ctx)?;
+
+        Ok((schema, ctx.diagnostics))
+    }
+
+    /// Parse the given string into a schema. Options are taken from the context and everything
+    /// the parser has to report is collected in it.
+    pub fn parse_with_context(xml: &str, ctx: &mut ParseContext) -> Result<Self, Error> {
+        Self::parse_reader(xml.as_bytes(), ctx)
+    }
+
+    /// Parse a schema from a reader, e.g. an open file. The encoding is taken from the XML
+    /// declaration.
+    pub fn parse_reader<R: Read>(reader: R, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let (mut element, positions) = document::parse(reader)?;
+        ctx.positions = positions;
+        version_control::apply(&mut element, &ctx.options);
 
-            Err(e) => {
-                let err = format!("Could not parse xml file. Error: {:?}", e);
-                Err(err)
+        Schema::read_with_context(&mut element, ctx)
+    }
+
+    /// Resolve a qualified name used in this schema document, e.g. the `type` of an element.
+    /// Names without a prefix belong to the default namespace, if there is one. Returns `None`
+    /// if the prefix is not bound.
+    pub fn resolve_qname(&self, qname: &str) -> Option<QName> {
+        match qname.split_once(':') {
+            Some((prefix, name)) => self
+                .namespaces
+                .get(prefix)
+                .map(|namespace| QName::new(Some(namespace), name)),
+            None => {
+                let namespace = self.namespaces.get("").filter(|ns| !ns.is_empty());
+                Some(QName::new(namespace.map(|ns| ns.as_str()), qname))
             }
         }
     }
 
     /// Read an element after it has been parsed.
-    pub fn read(element: &mut Element) -> Self {
-        let mut r = Schema::default();
+    pub fn read(element: &mut Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
+
+    /// Read an element after it has been parsed, reporting to the given context.
+    pub fn read_with_context(element: &mut Element, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let mut r = Schema {
+            span: ctx.span(element),
+            target_namespace: element.attributes.remove("targetNamespace"),
+            element_form_default: element.attributes.remove("elementFormDefault"),
+            attribute_form_default: element.attributes.remove("attributeFormDefault"),
+            namespaces: element
+                .namespaces
+                .as_ref()
+                .map(|namespaces| namespaces.0.clone())
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope("schema", None, &r.span);
+        r.block_default = DerivationSet::read(element, "blockDefault", ctx)?.unwrap_or_default();
+        r.final_default = DerivationSet::read(element, "finalDefault", ctx)?.unwrap_or_default();
 
         while let Some(mut annotation) = element.take_child("annotation") {
-            r.annotations.push(Annotation::read(&mut annotation));
+            r.annotations
+                .push(Annotation::read_with_context(&mut annotation, ctx)?);
         }
 
         while let Some(mut import) = element.take_child("import") {
-            r.imports.push(Import::read(&mut import));
+            r.imports.push(Import::read_with_context(&mut import, ctx)?);
+        }
+
+        while let Some(mut include) = element.take_child("include") {
+            r.includes
+                .push(Include::read_with_context(&mut include, ctx)?);
         }
 
         while let Some(mut simple_type) = element.take_child("simpleType") {
-            r.simple_types.push(SimpleType::read(&mut simple_type));
+            r.simple_types
+                .push(SimpleType::read_with_context(&mut simple_type, ctx)?);
         }
 
         while let Some(mut simple_type) = element.take_child("attributeGroup") {
             r.attribute_groups
-                .push(AttributeGroup::read(&mut simple_type));
+                .push(AttributeGroup::read_with_context(&mut simple_type, ctx)?);
         }
 
         while let Some(mut complex_type) = element.take_child("complexType") {
-            r.complex_types.push(ComplexType::read(&mut complex_type));
+            r.complex_types
+                .push(ComplexType::read_with_context(&mut complex_type, ctx)?);
         }
 
-        r
+        while let Some(mut global) = element.take_child("element") {
+            r.elements.push(crate::element::Element::read_with_context(
+                &mut global,
+                ctx,
+            )?);
+        }
+
+        while let Some(mut attribute) = element.take_child("attribute") {
+            r.attributes
+                .push(Attribute::read_with_context(&mut attribute, ctx)?);
+        }
+
+        while let Some(mut group) = element.take_child("group") {
+            r.groups.push(Group::read_with_context(&mut group, ctx)?);
+        }
+
+        ctx.leave(element);
+        Ok(r)
     }
 }
 
@@ -111,7 +253,7 @@ mod tests {
 "#;
 
         let mut element = Element::parse(xml.as_bytes()).unwrap();
-        let item = Schema::read(&mut element);
+        let item = Schema::read(&mut element).unwrap();
 
         assert_eq!(item.annotations.len(), 1);
         assert_eq!(item.annotations[0].content.len(), 1);
@@ -135,7 +277,7 @@ mod tests {
 "#;
 
         let mut element = Element::parse(xml.as_bytes()).unwrap();
-        let item = Schema::read(&mut element);
+        let item = Schema::read(&mut element).unwrap();
 
         assert_eq!(item.imports.len(), 2);
         assert_eq!(
@@ -156,4 +298,51 @@ mod tests {
             "http://www.musicxml.org/xsd/xlink.xsd".to_string()
         );
     }
+
+    #[test]
+    fn load_with_diagnostics() {
+        let mut resolver = crate::schema_resolver::MemoryResolver::new();
+        resolver.insert(
+            "schemas/tenths.xsd",
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="tenths">
+        <xs:annotation><xs:documentation/></xs:annotation>
+        <xs:restriction base="xs:decimal"/>
+    </xs:simpleType>
+</xs:schema>"#,
+        );
+
+        let mut ctx = ParseContext::default();
+        let schema = Schema::load_with_context("schemas/tenths.xsd", &resolver, &mut ctx).unwrap();
+        assert_eq!(schema.simple_types.len(), 1);
+        assert_eq!(ctx.diagnostics.len(), 1);
+        assert_eq!(ctx.diagnostics[0].code, "empty-documentation");
+
+        let span = ctx.diagnostics[0].span.as_ref().unwrap();
+        assert_eq!(span.document.as_deref(), Some("schemas/tenths.xsd"));
+        assert_eq!(span.start.line, 3);
+
+        assert!(matches!(
+            Schema::load_with_diagnostics(
+                Path::new("does/not/exist.xsd"),
+                &ParseOptions::default()
+            ),
+            Err(Error::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn errors() {
+        match Schema::parse("<xs:schema") {
+            Err(Error::Xml(_)) => {}
+            r => panic!("Expected xml error, got {:?}", r),
+        }
+
+        match Schema::load(Path::new("does/not/exist.xsd")) {
+            Err(e @ Error::Io { .. }) => {
+                assert!(std::error::Error::source(&e).is_some());
+            }
+            r => panic!("Expected io error, got {:?}", r),
+        }
+    }
 }
@@ -0,0 +1,311 @@
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+};
+
+use url::Url;
+
+use crate::{bundled::BundledResolver, error::Error};
+
+/// A schema document found by a [`SchemaResolver`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ResolvedSchema {
+    /// Absolute URI (or normalized path) of the document. Locations inside the document are
+    /// resolved relative to it, and documents with the same URI are loaded only once.
+    pub uri: String,
+    pub content: Vec<u8>,
+
+    /// The location that was asked for, if a different document is served in its place, e.g.
+    /// a bundled copy of a well-known schema.
+    pub replaces: Option<String>,
+}
+
+/// Finds the schema document for an `xs:import` or `xs:include`.
+pub trait SchemaResolver {
+    /// Find the document at `location`, relative to the document at `base`. `namespace` is the
+    /// namespace of an import, it is `None` for includes and for the root document. `location`
+    /// is empty for imports without `schemaLocation`, resolvers that cannot find a document by
+    /// namespace return [`Error::NotFound`] for those.
+    fn resolve(
+        &self,
+        namespace: Option<&str>,
+        location: &str,
+        base: Option<&str>,
+    ) -> Result<ResolvedSchema, Error>;
+}
+
+/// Reads schema documents from disk. Locations may be paths or `file:` URLs.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileSystemResolver;
+
+impl SchemaResolver for FileSystemResolver {
+    fn resolve(
+        &self,
+        namespace: Option<&str>,
+        location: &str,
+        base: Option<&str>,
+    ) -> Result<ResolvedSchema, Error> {
+        if location.is_empty() {
+            return Err(not_found(namespace, location));
+        }
+
+        let uri = resolve_uri(base, location);
+        let path = match parse_url(&uri) {
+            Some(url) => url.to_file_path().map_err(|_| not_found(namespace, &uri))?,
+            None => PathBuf::from(&uri),
+        };
+
+        let content = std::fs::read(&path).map_err(|source| Error::Io { path, source })?;
+        Ok(ResolvedSchema {
+            uri,
+            content,
+            replaces: None,
+        })
+    }
+}
+
+/// Downloads schema documents.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HttpResolver;
+
+impl SchemaResolver for HttpResolver {
+    fn resolve(
+        &self,
+        namespace: Option<&str>,
+        location: &str,
+        base: Option<&str>,
+    ) -> Result<ResolvedSchema, Error> {
+        if location.is_empty() {
+            return Err(not_found(namespace, location));
+        }
+
+        let uri = resolve_uri(base, location);
+        let content = reqwest::blocking::get(&uri)
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.bytes())
+            .map_err(|source| Error::Http {
+                url: uri.clone(),
+                source,
+            })?;
+
+        Ok(ResolvedSchema {
+            uri,
+            content: content.to_vec(),
+            replaces: None,
+        })
+    }
+}
+
+/// Downloads `http` and `https` locations and reads everything else from disk. Well-known
+/// schemas are served from the copies bundled with the crate instead of being downloaded, and
+/// when a local copy an import names cannot be read. Served copies that were asked for by
+/// another location are marked in [`ResolvedSchema::replaces`]. See [`BundledResolver`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultResolver;
+
+impl SchemaResolver for DefaultResolver {
+    fn resolve(
+        &self,
+        namespace: Option<&str>,
+        location: &str,
+        base: Option<&str>,
+    ) -> Result<ResolvedSchema, Error> {
+        if let Ok(bundled) = BundledResolver.resolve(namespace, location, base) {
+            return Ok(bundled);
+        }
+
+        let uri = resolve_uri(base, location);
+        let bundled = || {
+            namespace
+                .and_then(BundledResolver::by_namespace)
+                .map(|bundled| ResolvedSchema {
+                    replaces: Some(uri.clone()),
+                    ..bundled
+                })
+        };
+
+        match parse_url(&uri) {
+            Some(url) if url.scheme() == "http" || url.scheme() == "https" => match bundled() {
+                Some(bundled) => Ok(bundled),
+                None => HttpResolver.resolve(namespace, &uri, None),
+            },
+            _ => FileSystemResolver
+                .resolve(namespace, &uri, None)
+                .or_else(|error| bundled().ok_or(error)),
+        }
+    }
+}
+
+/// Serves schema documents from memory, e.g. for tests or for schemas bundled with an
+/// application.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryResolver {
+    documents: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a document. Relative locations inside it are resolved against `uri`.
+    pub fn insert(&mut self, uri: &str, content: impl Into<Vec<u8>>) {
+        self.documents
+            .insert(resolve_uri(None, uri), content.into());
+    }
+}
+
+impl SchemaResolver for MemoryResolver {
+    fn resolve(
+        &self,
+        namespace: Option<&str>,
+        location: &str,
+        base: Option<&str>,
+    ) -> Result<ResolvedSchema, Error> {
+        let uri = resolve_uri(base, location);
+        match self.documents.get(&uri) {
+            Some(content) if !location.is_empty() => Ok(ResolvedSchema {
+                uri,
+                content: content.clone(),
+                replaces: None,
+            }),
+            _ => Err(not_found(namespace, &uri)),
+        }
+    }
+}
+
+/// Resolve `location` relative to the document at `base`. Both may be URLs or paths. Paths are
+/// normalized, so that every document has exactly one URI.
+pub fn resolve_uri(base: Option<&str>, location: &str) -> String {
+    if let Some(url) = parse_url(location) {
+        return url.to_string();
+    }
+
+    match base {
+        Some(base) => match parse_url(base) {
+            Some(base) => base
+                .join(location)
+                .map(|url| url.to_string())
+                .unwrap_or_else(|_| location.to_string()),
+            None if base.ends_with('/') => normalize(&Path::new(base).join(location)),
+            None => normalize(
+                &Path::new(base)
+                    .parent()
+                    .unwrap_or(Path::new(""))
+                    .join(location),
+            ),
+        },
+        None => normalize(Path::new(location)),
+    }
+}
+
+fn not_found(namespace: Option<&str>, location: &str) -> Error {
+    Error::NotFound {
+        namespace: namespace.map(|ns| ns.to_string()),
+        location: location.to_string(),
+    }
+}
+
+/// Parse `value` as an absolute URL. Single letter schemes are Windows drive letters, not URLs.
+fn parse_url(value: &str) -> Option<Url> {
+    Url::parse(value).ok().filter(|url| url.scheme().len() > 1)
+}
+
+/// Remove `.` and resolve `..` components without touching the file system. A trailing `/` is
+/// kept, so that directories stay usable as base.
+fn normalize(path: &Path) -> String {
+    let directory = path.to_string_lossy().ends_with('/');
+
+    let mut r = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match r.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    r.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => r.push(".."),
+            },
+            component => r.push(component),
+        }
+    }
+
+    let mut r = r.to_string_lossy().to_string();
+    if directory && !r.is_empty() && !r.ends_with('/') {
+        r.push('/');
+    }
+
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uris() {
+        assert_eq!(
+            resolve_uri(
+                Some("http://www.musicxml.org/xsd/musicxml.xsd"),
+                "xlink.xsd"
+            ),
+            "http://www.musicxml.org/xsd/xlink.xsd".to_string()
+        );
+        assert_eq!(
+            resolve_uri(Some("schemas/musicxml.xsd"), "../common/./xml.xsd"),
+            "common/xml.xsd".to_string()
+        );
+        assert_eq!(
+            resolve_uri(Some("/schemas/musicxml.xsd"), "../../xml.xsd"),
+            "/xml.xsd".to_string()
+        );
+        assert_eq!(
+            resolve_uri(Some("schemas/musicxml.xsd"), "https://example.com/a.xsd"),
+            "https://example.com/a.xsd".to_string()
+        );
+        assert_eq!(resolve_uri(None, "../a.xsd"), "../a.xsd".to_string());
+    }
+
+    #[test]
+    fn memory() {
+        let mut resolver = MemoryResolver::new();
+        resolver.insert("schemas/common/types.xsd", "<xs:schema/>");
+
+        let found = resolver
+            .resolve(None, "common/types.xsd", Some("schemas/main.xsd"))
+            .unwrap();
+        assert_eq!(found.uri, "schemas/common/types.xsd".to_string());
+        assert_eq!(found.content, b"<xs:schema/>".to_vec());
+
+        assert!(matches!(
+            resolver.resolve(Some("urn:x"), "types.xsd", Some("schemas/main.xsd")),
+            Err(Error::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn file_system() {
+        let directory = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(directory.path().join("common")).unwrap();
+        std::fs::write(
+            directory.path().join("common").join("types.xsd"),
+            "<xs:schema/>",
+        )
+        .unwrap();
+
+        let base = directory.path().join("main.xsd");
+        let found = FileSystemResolver
+            .resolve(None, "./common/types.xsd", Some(&base.to_string_lossy()))
+            .unwrap();
+        assert_eq!(found.content, b"<xs:schema/>".to_vec());
+
+        let url = Url::from_file_path(directory.path().join("common").join("types.xsd")).unwrap();
+        assert!(FileSystemResolver.resolve(None, url.as_str(), None).is_ok());
+
+        assert!(matches!(
+            FileSystemResolver.resolve(None, "missing.xsd", Some(&base.to_string_lossy())),
+            Err(Error::Io { .. })
+        ));
+    }
+}
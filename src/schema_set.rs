@@ -0,0 +1,1093 @@
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    path::Path,
+};
+
+use crate::{
+    builtins::{is_builtin_type, BuiltinType},
+    choice::ChoiceItems,
+    diagnostic::{Diagnostic, Severity},
+    error::Error,
+    identity_constraint::{IdentityConstraint, IdentityConstraintKind, XPath},
+    parse_context::ParseContext,
+    parse_options::ParseOptions,
+    prelude::{
+        All, Attribute, AttributeGroup, Choice, ComplexContent, ComplexType, Element, Extension,
+        Group, QName, Restriction, Schema, Sequence, SimpleContent, SimpleType,
+    },
+    schema_resolver::{resolve_uri, DefaultResolver, ResolvedSchema, SchemaResolver},
+    source_span::SourceSpan,
+};
+
+/// Identifies a global component: the index of the schema document inside the [`SchemaSet`] and
+/// the index inside the matching vector of that [`Schema`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ComponentId {
+    pub schema: usize,
+    pub index: usize,
+}
+
+/// Identifies an element declaration: a global one by its [`ComponentId`], a local one by the
+/// component it is written in.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DeclarationId {
+    Global(ComponentId),
+
+    /// The local declaration with the index `index` in `scope`. Local declarations are counted
+    /// in the order the content model of `scope` lists them, see
+    /// [`SchemaSet::content_model_of`].
+    Local {
+        scope: Box<LocalScope>,
+        index: usize,
+    },
+}
+
+/// A component local element declarations are written in. The first two also identify a
+/// complex type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LocalScope {
+    /// A global complex type.
+    Type(ComponentId),
+
+    /// The anonymous complex type of an element declaration.
+    AnonymousType(DeclarationId),
+
+    /// A global model group definition.
+    Group(ComponentId),
+}
+
+/// The global components of one target namespace, by local name.
+#[derive(Debug, Default, Clone)]
+pub struct SymbolTable {
+    pub simple_types: BTreeMap<String, ComponentId>,
+    pub complex_types: BTreeMap<String, ComponentId>,
+    pub elements: BTreeMap<String, ComponentId>,
+    pub attributes: BTreeMap<String, ComponentId>,
+    pub groups: BTreeMap<String, ComponentId>,
+    pub attribute_groups: BTreeMap<String, ComponentId>,
+}
+
+/// A set of schema documents whose references are resolved against each other.
+#[derive(Debug, Default)]
+pub struct SchemaSet {
+    pub schemas: Vec<Schema>,
+
+    /// Problems found while loading the documents and resolving references.
+    pub diagnostics: Vec<Diagnostic>,
+
+    symbols: BTreeMap<Option<String>, SymbolTable>,
+}
+
+impl SchemaSet {
+    /// Build the symbol tables for the given schema documents and resolve all references.
+    /// Unresolved references are reported in [`SchemaSet::diagnostics`].
+    pub fn new(schemas: Vec<Schema>) -> Self {
+        let mut r = SchemaSet {
+            schemas,
+            ..Default::default()
+        };
+
+        r.build_symbols();
+        let mut diagnostics = ReferenceCheck::run(&r);
+        r.diagnostics.append(&mut diagnostics);
+
+        r
+    }
+
+    /// Load a schema from disk together with everything it imports or includes.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        Self::load_with_resolver(&path.to_string_lossy(), &DefaultResolver)
+    }
+
+    /// Download a schema together with everything it imports or includes.
+    pub fn download(url: &str) -> Result<Self, Error> {
+        Self::load_with_resolver(url, &DefaultResolver)
+    }
+
+    /// The global components of a target namespace.
+    pub fn symbols(&self, namespace: Option<&str>) -> Option<&SymbolTable> {
+        self.symbols.get(&namespace.map(|ns| ns.to_string()))
+    }
+
+    pub fn simple_type(&self, name: &QName) -> Option<&SimpleType> {
+        self.lookup(name, |t| &t.simple_types)
+            .map(|id| &self.schemas[id.schema].simple_types[id.index])
+    }
+
+    pub fn complex_type(&self, name: &QName) -> Option<&ComplexType> {
+        self.lookup(name, |t| &t.complex_types)
+            .map(|id| &self.schemas[id.schema].complex_types[id.index])
+    }
+
+    pub fn element(&self, name: &QName) -> Option<&Element> {
+        self.lookup(name, |t| &t.elements)
+            .map(|id| &self.schemas[id.schema].elements[id.index])
+    }
+
+    pub fn attribute(&self, name: &QName) -> Option<&Attribute> {
+        self.lookup(name, |t| &t.attributes)
+            .map(|id| &self.schemas[id.schema].attributes[id.index])
+    }
+
+    pub fn group(&self, name: &QName) -> Option<&Group> {
+        self.lookup(name, |t| &t.groups)
+            .map(|id| &self.schemas[id.schema].groups[id.index])
+    }
+
+    pub fn attribute_group(&self, name: &QName) -> Option<&AttributeGroup> {
+        self.lookup(name, |t| &t.attribute_groups)
+            .map(|id| &self.schemas[id.schema].attribute_groups[id.index])
+    }
+
+    /// The nearest built-in type the simple type `name` is derived from by restriction, or the
+    /// type itself if it is built in. Returns `None` for lists, unions and unresolved names.
+    pub fn builtin_base(&self, name: &QName) -> Option<&'static BuiltinType> {
+        let mut name = name.clone();
+        let mut visited = HashSet::new();
+
+        loop {
+            if let Some(builtin) = BuiltinType::lookup_qname(&name) {
+                return Some(builtin);
+            }
+
+            if !visited.insert(name.clone()) {
+                return None;
+            }
+
+            let id = self.lookup(&name, |t| &t.simple_types)?;
+            let schema = &self.schemas[id.schema];
+            let restriction = schema.simple_types[id.index].restriction.as_ref()?;
+            name = schema.resolve_qname(&restriction.base)?;
+        }
+    }
+
+    /// The primitive type the simple type `name` is derived from, e.g. `decimal` for a
+    /// restriction of `positiveInteger`.
+    pub fn primitive_type(&self, name: &QName) -> Option<&'static BuiltinType> {
+        self.builtin_base(name)?.primitive()
+    }
+
+    /// The id of a global complex type of the schema document with the index `schema`. `None`
+    /// for anonymous types.
+    pub(crate) fn complex_type_id(
+        &self,
+        schema: usize,
+        complex_type: &ComplexType,
+    ) -> Option<ComponentId> {
+        if complex_type.name.is_empty() {
+            return None;
+        }
+
+        let index = self.schemas[schema]
+            .complex_types
+            .iter()
+            .position(|t| t.name == complex_type.name)?;
+        Some(ComponentId { schema, index })
+    }
+
+    pub(crate) fn lookup(
+        &self,
+        name: &QName,
+        table: impl Fn(&SymbolTable) -> &BTreeMap<String, ComponentId>,
+    ) -> Option<ComponentId> {
+        self.symbols
+            .get(&name.namespace)
+            .and_then(|symbols| table(symbols).get(&name.name))
+            .copied()
+    }
+
+    /// Register every global component. If a name is defined more than once, the first
+    /// definition wins.
+    fn build_symbols(&mut self) {
+        for (schema_index, schema) in self.schemas.iter().enumerate() {
+            let symbols = self
+                .symbols
+                .entry(schema.target_namespace.clone())
+                .or_default();
+
+            let id = |index| ComponentId {
+                schema: schema_index,
+                index,
+            };
+
+            for (index, t) in schema.simple_types.iter().enumerate() {
+                symbols
+                    .simple_types
+                    .entry(t.name.clone())
+                    .or_insert(id(index));
+            }
+
+            for (index, t) in schema.complex_types.iter().enumerate() {
+                symbols
+                    .complex_types
+                    .entry(t.name.clone())
+                    .or_insert(id(index));
+            }
+
+            for (index, e) in schema.elements.iter().enumerate() {
+                symbols.elements.entry(e.name.clone()).or_insert(id(index));
+            }
+
+            for (index, a) in schema.attributes.iter().enumerate() {
+                if let Some(name) = &a.name {
+                    symbols.attributes.entry(name.clone()).or_insert(id(index));
+                }
+            }
+
+            for (index, g) in schema.groups.iter().enumerate() {
+                symbols.groups.entry(g.name.clone()).or_insert(id(index));
+            }
+
+            for (index, g) in schema.attribute_groups.iter().enumerate() {
+                symbols
+                    .attribute_groups
+                    .entry(g.name.clone())
+                    .or_insert(id(index));
+            }
+        }
+    }
+
+    /// Load the schema at `root` and follow its imports and includes breadth first, using
+    /// `resolver` to find the documents. Every document is loaded once, so imports and includes
+    /// may be circular. Only a failure to load `root` itself is an error, other documents that
+    /// cannot be loaded are reported as diagnostics.
+    pub fn load_with_resolver(root: &str, resolver: &dyn SchemaResolver) -> Result<Self, Error> {
+        Self::load_with_options(root, resolver, &ParseOptions::default())
+    }
+
+    /// Like [`SchemaSet::load_with_resolver`], parsing every document with the given options.
+    /// What the parser reports ends up in the diagnostics of the set.
+    pub fn load_with_options(
+        root: &str,
+        resolver: &dyn SchemaResolver,
+        options: &ParseOptions,
+    ) -> Result<Self, Error> {
+        Self::load_queue(
+            VecDeque::from([Pending {
+                namespace: None,
+                location: root.to_string(),
+                base: None,
+                required: true,
+                chameleon_namespace: None,
+                path: String::new(),
+                span: None,
+            }]),
+            resolver,
+            options,
+        )
+    }
+
+    /// Load the schema documents at `locations`, given as namespace, location relative to
+    /// `base` and where the location was found, together with their imports and includes.
+    /// Documents that cannot be loaded are reported as diagnostics.
+    pub(crate) fn load_locations(
+        locations: &[(Option<String>, String, Option<SourceSpan>)],
+        base: Option<&str>,
+        path: &str,
+        resolver: &dyn SchemaResolver,
+    ) -> Result<Self, Error> {
+        let queue = locations.iter().map(|(namespace, location, span)| Pending {
+            namespace: namespace.clone(),
+            location: location.clone(),
+            base: base.map(str::to_string),
+            required: false,
+            chameleon_namespace: None,
+            path: path.to_string(),
+            span: span.clone(),
+        });
+
+        Self::load_queue(queue.collect(), resolver, &ParseOptions::default())
+    }
+
+    fn load_queue(
+        mut queue: VecDeque<Pending>,
+        resolver: &dyn SchemaResolver,
+        options: &ParseOptions,
+    ) -> Result<Self, Error> {
+        let mut schemas = Vec::new();
+        let mut diagnostics = Vec::new();
+        let mut visited = HashSet::new();
+
+        while let Some(pending) = queue.pop_front() {
+            if !pending.location.is_empty()
+                && visited.contains(&resolve_uri(pending.base.as_deref(), &pending.location))
+            {
+                continue;
+            }
+
+            let loaded = resolver
+                .resolve(
+                    pending.namespace.as_deref(),
+                    &pending.location,
+                    pending.base.as_deref(),
+                )
+                .and_then(|document| {
+                    if let Some(replaced) = &document.replaces {
+                        diagnostics.push(Diagnostic {
+                            severity: Severity::Warning,
+                            code: "schema-replaced".to_string(),
+                            message: format!("{} is replaced by {}", replaced, document.uri),
+                            path: pending.path.clone(),
+                            span: pending.span.clone(),
+                        });
+                    }
+
+                    if visited.insert(document.uri.clone()) {
+                        Self::parse_document(&document, options).map(Some)
+                    } else {
+                        Ok(None)
+                    }
+                });
+
+            let (uri, mut schema, mut found) = match loaded {
+                Ok(Some(r)) => r,
+                Ok(None) => continue,
+                Err(e) if pending.required => return Err(e),
+                // Nothing is known about imports without location, unless the resolver knows
+                // the namespace.
+                Err(_) if pending.location.is_empty() => continue,
+                Err(e) => {
+                    diagnostics.push(Diagnostic {
+                        severity: Severity::Error,
+                        code: "schema-not-loaded".to_string(),
+                        message: e.to_string(),
+                        path: pending.path,
+                        span: pending.span,
+                    });
+                    continue;
+                }
+            };
+            diagnostics.append(&mut found);
+
+            // A schema without target namespace included into one with a target namespace
+            // takes over the namespace of the including schema.
+            if let Some(namespace) = pending.chameleon_namespace {
+                if schema.target_namespace.is_none() {
+                    schema.target_namespace = Some(namespace.clone());
+                    let default = schema.namespaces.entry(String::new()).or_default();
+                    if default.is_empty() {
+                        *default = namespace;
+                    }
+                }
+            }
+
+            let imports = schema.imports.iter().map(|import| Pending {
+                namespace: Some(import.namespace.clone()).filter(|ns| !ns.is_empty()),
+                location: import.schema_location.clone(),
+                base: Some(uri.clone()),
+                required: false,
+                chameleon_namespace: None,
+                path: format!("schema/import[{}]", import.namespace),
+                span: import.span.clone(),
+            });
+            let includes = schema.includes.iter().map(|include| Pending {
+                namespace: None,
+                location: include.schema_location.clone(),
+                base: Some(uri.clone()),
+                required: false,
+                chameleon_namespace: schema.target_namespace.clone(),
+                path: format!("schema/include[{}]", include.schema_location),
+                span: include.span.clone(),
+            });
+
+            queue.extend(
+                imports
+                    .chain(includes)
+                    .filter(|pending| !pending.location.is_empty() || pending.namespace.is_some()),
+            );
+
+            schemas.push(schema);
+        }
+
+        let mut r = SchemaSet::new(schemas);
+        diagnostics.append(&mut r.diagnostics);
+        r.diagnostics = diagnostics;
+
+        Ok(r)
+    }
+
+    fn parse_document(
+        document: &ResolvedSchema,
+        options: &ParseOptions,
+    ) -> Result<(String, Schema, Vec<Diagnostic>), Error> {
+        let mut ctx = ParseContext::new(options.clone());
+        ctx.document = Some(document.uri.clone());
+        let schema = Schema::parse_reader(document.content.as_slice(), &mut ctx)?;
+
+        Ok((document.uri.clone(), schema, ctx.diagnostics))
+    }
+}
+
+/// A document still to be loaded by [`SchemaSet::load_with_resolver`].
+struct Pending {
+    namespace: Option<String>,
+    location: String,
+
+    /// URI of the document that imports or includes this one.
+    base: Option<String>,
+
+    /// A failure to load the document is an error rather than a diagnostic.
+    required: bool,
+    chameleon_namespace: Option<String>,
+
+    /// Path and location of the import or include that asked for the document.
+    path: String,
+    span: Option<SourceSpan>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SymbolSpace {
+    Type,
+    Element,
+    Attribute,
+    Group,
+    AttributeGroup,
+}
+
+/// Walks all components of a [`SchemaSet`] and reports references that cannot be resolved.
+/// Identity constraints are checked here too, as they are only found by walking the element
+/// declarations.
+struct ReferenceCheck<'a> {
+    set: &'a SchemaSet,
+    schema: &'a Schema,
+    path: Vec<String>,
+    diagnostics: Vec<Diagnostic>,
+    constraints: Vec<DeclaredConstraint>,
+}
+
+/// An identity constraint found while walking the element declarations.
+struct DeclaredConstraint {
+    name: QName,
+    kind: IdentityConstraintKind,
+    fields: usize,
+
+    /// `refer` of a keyref as written, and resolved if its prefix is declared.
+    refer: Option<(String, Option<QName>)>,
+    path: String,
+    span: Option<SourceSpan>,
+}
+
+impl<'a> ReferenceCheck<'a> {
+    fn run(set: &'a SchemaSet) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut constraints = Vec::new();
+
+        for schema in set.schemas.iter() {
+            let mut check = ReferenceCheck {
+                set,
+                schema,
+                path: vec!["schema".to_string()],
+                diagnostics: Vec::new(),
+                constraints: Vec::new(),
+            };
+
+            schema
+                .simple_types
+                .iter()
+                .for_each(|t| check.simple_type(t));
+            schema
+                .attribute_groups
+                .iter()
+                .for_each(|g| check.attribute_group(g));
+            schema
+                .complex_types
+                .iter()
+                .for_each(|t| check.complex_type(t));
+            schema.elements.iter().for_each(|e| check.element(e));
+            schema.attributes.iter().for_each(|a| check.attribute(a));
+            schema.groups.iter().for_each(|g| check.group(g));
+
+            diagnostics.append(&mut check.diagnostics);
+            constraints.append(&mut check.constraints);
+        }
+
+        diagnostics.append(&mut Self::keyrefs(&constraints));
+        diagnostics
+    }
+
+    /// Identity constraint names must be unique, and a keyref must refer to a key or unique
+    /// constraint with as many fields.
+    fn keyrefs(constraints: &[DeclaredConstraint]) -> Vec<Diagnostic> {
+        let mut r = Vec::new();
+        let mut report = |code: &str, message: String, c: &DeclaredConstraint| {
+            r.push(Diagnostic {
+                severity: Severity::Error,
+                code: code.to_string(),
+                message,
+                path: c.path.clone(),
+                span: c.span.clone(),
+            })
+        };
+
+        let mut names: BTreeMap<&QName, &DeclaredConstraint> = BTreeMap::new();
+        for c in constraints {
+            if names.insert(&c.name, c).is_some() {
+                report(
+                    "sch-props-correct.2",
+                    format!("'{}' is defined more than once", c.name.name),
+                    c,
+                );
+            }
+        }
+
+        for c in constraints {
+            let Some((refer, resolved)) = &c.refer else {
+                continue;
+            };
+
+            match resolved.as_ref().and_then(|name| names.get(name)) {
+                None => report(
+                    "src-resolve",
+                    format!(
+                        "'{}' does not resolve to an identity-constraint definition",
+                        refer
+                    ),
+                    c,
+                ),
+                Some(key) if key.kind == IdentityConstraintKind::KeyRef => report(
+                    "c-props-correct.1",
+                    format!("'{}' is a keyref, not a key or unique constraint", refer),
+                    c,
+                ),
+                Some(key) if key.fields != c.fields => report(
+                    "c-props-correct.2",
+                    format!(
+                        "keyref has {} fields, but '{}' has {}",
+                        c.fields, refer, key.fields
+                    ),
+                    c,
+                ),
+                Some(_) => {}
+            }
+        }
+
+        r
+    }
+
+    fn reference(&mut self, space: SymbolSpace, qname: &str, span: &Option<SourceSpan>) {
+        let found = match self.schema.resolve_qname(qname) {
+            Some(name) => match space {
+                SymbolSpace::Type => {
+                    is_builtin_type(&name)
+                        || self.set.simple_type(&name).is_some()
+                        || self.set.complex_type(&name).is_some()
+                }
+                SymbolSpace::Element => self.set.element(&name).is_some(),
+                SymbolSpace::Attribute => self.set.attribute(&name).is_some(),
+                SymbolSpace::Group => self.set.group(&name).is_some(),
+                SymbolSpace::AttributeGroup => self.set.attribute_group(&name).is_some(),
+            },
+            None => false,
+        };
+
+        if !found {
+            let kind = match space {
+                SymbolSpace::Type => "a type definition",
+                SymbolSpace::Element => "an element declaration",
+                SymbolSpace::Attribute => "an attribute declaration",
+                SymbolSpace::Group => "a model group definition",
+                SymbolSpace::AttributeGroup => "an attribute group definition",
+            };
+
+            self.diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                code: "src-resolve".to_string(),
+                message: format!("'{}' does not resolve to {}", qname, kind),
+                path: self.path.join("/"),
+                span: span.clone(),
+            });
+        }
+    }
+
+    fn enter(&mut self, component: &str, name: &str) {
+        if name.is_empty() {
+            self.path.push(component.to_string());
+        } else {
+            self.path.push(format!("{}[{}]", component, name));
+        }
+    }
+
+    fn simple_type(&mut self, t: &SimpleType) {
+        self.enter("simpleType", &t.name);
+
+        if let Some(restriction) = &t.restriction {
+            if !restriction.base.is_empty() {
+                self.reference(SymbolSpace::Type, &restriction.base, &restriction.span);
+            }
+        }
+
+        if let Some(union) = &t.union {
+            for member in union.types.iter().filter(|m| !m.is_empty()) {
+                self.reference(SymbolSpace::Type, member, &union.span);
+            }
+
+            union.simple_types.iter().for_each(|t| self.simple_type(t));
+        }
+
+        if let Some(list) = &t.list {
+            if let Some(item_type) = &list.item_type {
+                self.reference(SymbolSpace::Type, item_type, &list.span);
+            }
+
+            if let Some(t) = &list.simple_type {
+                self.simple_type(t);
+            }
+        }
+
+        self.path.pop();
+    }
+
+    fn complex_type(&mut self, t: &ComplexType) {
+        self.enter("complexType", &t.name);
+
+        t.attributes.iter().for_each(|a| self.attribute(a));
+        t.attribute_groups
+            .iter()
+            .for_each(|g| self.attribute_group(g));
+        t.simple_content.iter().for_each(|c| self.simple_content(c));
+        t.complex_content
+            .iter()
+            .for_each(|c| self.complex_content(c));
+        t.choices.iter().for_each(|c| self.choice(c));
+        t.sequences.iter().for_each(|s| self.sequence(s));
+        t.groups.iter().for_each(|g| self.group(g));
+        t.all.iter().for_each(|a| self.all(a));
+
+        self.path.pop();
+    }
+
+    fn simple_content(&mut self, c: &SimpleContent) {
+        self.enter("simpleContent", "");
+
+        c.attributes.iter().for_each(|a| self.attribute(a));
+        c.attribute_groups
+            .iter()
+            .for_each(|g| self.attribute_group(g));
+        c.extensions.iter().for_each(|e| self.extension(e));
+        c.restrictions.iter().for_each(|r| self.restriction(r));
+
+        self.path.pop();
+    }
+
+    fn complex_content(&mut self, c: &ComplexContent) {
+        self.enter("complexContent", "");
+
+        c.extensions.iter().for_each(|e| self.extension(e));
+        c.restrictions.iter().for_each(|r| self.restriction(r));
+
+        self.path.pop();
+    }
+
+    /// A restriction inside `simpleContent` or `complexContent`.
+    fn restriction(&mut self, r: &Restriction) {
+        self.enter("restriction", &r.base);
+
+        if !r.base.is_empty() {
+            self.reference(SymbolSpace::Type, &r.base, &r.span);
+        }
+        r.attributes.iter().for_each(|a| self.attribute(a));
+        r.attribute_groups
+            .iter()
+            .for_each(|g| self.attribute_group(g));
+        r.sequences.iter().for_each(|s| self.sequence(s));
+        r.choices.iter().for_each(|c| self.choice(c));
+        r.groups.iter().for_each(|g| self.group(g));
+        r.all.iter().for_each(|a| self.all(a));
+
+        self.path.pop();
+    }
+
+    fn extension(&mut self, e: &Extension) {
+        self.enter("extension", &e.base);
+
+        if !e.base.is_empty() {
+            self.reference(SymbolSpace::Type, &e.base, &e.span);
+        }
+        e.attributes.iter().for_each(|a| self.attribute(a));
+        e.attribute_groups
+            .iter()
+            .for_each(|g| self.attribute_group(g));
+        e.sequences.iter().for_each(|s| self.sequence(s));
+        e.choices.iter().for_each(|c| self.choice(c));
+        e.groups.iter().for_each(|g| self.group(g));
+        e.all.iter().for_each(|a| self.all(a));
+
+        self.path.pop();
+    }
+
+    fn element(&mut self, e: &Element) {
+        self.enter("element", e.reference.as_deref().unwrap_or(&e.name));
+
+        if let Some(reference) = &e.reference {
+            self.reference(SymbolSpace::Element, reference, &e.span);
+        }
+        for head in e
+            .substitution_group
+            .iter()
+            .flat_map(|g| g.split_whitespace())
+        {
+            self.reference(SymbolSpace::Element, head, &e.span);
+        }
+        if !e.r#type.is_empty() {
+            self.reference(SymbolSpace::Type, &e.r#type, &e.span);
+        }
+        e.complex_types.iter().for_each(|t| self.complex_type(t));
+        if let Some(t) = &e.simple_type {
+            self.simple_type(t);
+        }
+        e.identity_constraints
+            .iter()
+            .for_each(|c| self.identity_constraint(c));
+
+        self.path.pop();
+    }
+
+    fn identity_constraint(&mut self, c: &IdentityConstraint) {
+        let component = match c.kind {
+            IdentityConstraintKind::Unique => "unique",
+            IdentityConstraintKind::Key => "key",
+            IdentityConstraintKind::KeyRef => "keyref",
+        };
+        self.enter(component, &c.name);
+
+        let namespaces = &c.namespaces;
+        if let Err(message) = XPath::new(&c.selector, false, namespaces) {
+            self.report(
+                "c-selector-xpath",
+                format!("selector '{}' is invalid: {}", c.selector, message),
+                &c.span,
+            );
+        }
+        for field in c.fields.iter() {
+            if let Err(message) = XPath::new(field, true, namespaces) {
+                self.report(
+                    "c-fields-xpath",
+                    format!("field '{}' is invalid: {}", field, message),
+                    &c.span,
+                );
+            }
+        }
+
+        self.constraints.push(DeclaredConstraint {
+            name: QName::new(self.schema.target_namespace.as_deref(), &c.name),
+            kind: c.kind,
+            fields: c.fields.len(),
+            refer: c
+                .refer
+                .as_ref()
+                .map(|refer| (refer.clone(), self.schema.resolve_qname(refer))),
+            path: self.path.join("/"),
+            span: c.span.clone(),
+        });
+
+        self.path.pop();
+    }
+
+    fn report(&mut self, code: &str, message: String, span: &Option<SourceSpan>) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            code: code.to_string(),
+            message,
+            path: self.path.join("/"),
+            span: span.clone(),
+        });
+    }
+
+    fn attribute(&mut self, a: &Attribute) {
+        self.enter(
+            "attribute",
+            a.name.as_deref().or(a.reference()).unwrap_or_default(),
+        );
+
+        if let Some(reference) = a.reference() {
+            self.reference(SymbolSpace::Attribute, reference, &a.span);
+        }
+        if let Some(t) = &a.r#type {
+            self.reference(SymbolSpace::Type, t, &a.span);
+        }
+        if let Some(t) = &a.simple_type {
+            self.simple_type(t);
+        }
+
+        self.path.pop();
+    }
+
+    fn attribute_group(&mut self, g: &AttributeGroup) {
+        self.enter("attributeGroup", g.reference.as_deref().unwrap_or(&g.name));
+
+        if let Some(reference) = &g.reference {
+            self.reference(SymbolSpace::AttributeGroup, reference, &g.span);
+        }
+        g.attributes.iter().for_each(|a| self.attribute(a));
+        g.attribute_groups
+            .iter()
+            .for_each(|g| self.attribute_group(g));
+
+        self.path.pop();
+    }
+
+    fn group(&mut self, g: &Group) {
+        self.enter(
+            "group",
+            if g.name.is_empty() {
+                &g.reference
+            } else {
+                &g.name
+            },
+        );
+
+        if !g.reference.is_empty() {
+            self.reference(SymbolSpace::Group, &g.reference, &g.span);
+        }
+        g.sequences.iter().for_each(|s| self.sequence(s));
+        g.choices.iter().for_each(|c| self.choice(c));
+        g.all.iter().for_each(|a| self.all(a));
+
+        self.path.pop();
+    }
+
+    fn sequence(&mut self, s: &Sequence) {
+        self.enter("sequence", "");
+
+        s.particles.iter().for_each(|p| self.particle(p));
+
+        self.path.pop();
+    }
+
+    fn choice(&mut self, c: &Choice) {
+        self.enter("choice", "");
+
+        c.elements.iter().for_each(|p| self.particle(p));
+
+        self.path.pop();
+    }
+
+    fn all(&mut self, a: &All) {
+        self.enter("all", "");
+
+        a.elements.iter().for_each(|e| self.element(e));
+
+        self.path.pop();
+    }
+
+    fn particle(&mut self, item: &ChoiceItems) {
+        match item {
+            ChoiceItems::None | ChoiceItems::Any(_) => {}
+            ChoiceItems::Element(e) => self.element(e),
+            ChoiceItems::Choice(c) => self.choice(c),
+            ChoiceItems::Sequence(s) => self.sequence(s),
+            ChoiceItems::Group(g) => self.group(g),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema_resolver::MemoryResolver;
+
+    const MAIN: &str = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:x="urn:x" xmlns:xlink="http://www.w3.org/1999/xlink" targetNamespace="urn:main" xmlns="urn:main">
+    <xs:import namespace="urn:x" schemaLocation="x.xsd"/>
+    <xs:complexType name="note">
+        <xs:sequence>
+            <xs:element name="pitch" type="x:pitch"/>
+            <xs:group ref="editorial"/>
+        </xs:sequence>
+        <xs:attribute ref="xlink:href"/>
+        <xs:attributeGroup ref="x:print-style"/>
+    </xs:complexType>
+    <xs:element name="note" type="note"/>
+</xs:schema>
+"#;
+
+    const X: &str = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:x" xmlns="urn:x">
+    <xs:simpleType name="pitch">
+        <xs:restriction base="xs:token"/>
+    </xs:simpleType>
+    <xs:attributeGroup name="print-style">
+        <xs:attribute name="color" type="color"/>
+    </xs:attributeGroup>
+</xs:schema>
+"#;
+
+    #[test]
+    fn symbols() {
+        let set = SchemaSet::new(vec![
+            Schema::parse(MAIN).unwrap(),
+            Schema::parse(X).unwrap(),
+        ]);
+
+        let main = set.symbols(Some("urn:main")).unwrap();
+        assert_eq!(main.complex_types.len(), 1);
+        assert_eq!(
+            main.elements["note"],
+            ComponentId {
+                schema: 0,
+                index: 0
+            }
+        );
+
+        let pitch = set
+            .simple_type(&QName::new(Some("urn:x"), "pitch"))
+            .unwrap();
+        assert_eq!(pitch.name, "pitch".to_string());
+        assert!(set.simple_type(&QName::new(None, "pitch")).is_none());
+        assert!(set
+            .attribute_group(&QName::new(Some("urn:x"), "print-style"))
+            .is_some());
+    }
+
+    #[test]
+    fn unresolved() {
+        let set = SchemaSet::new(vec![
+            Schema::parse(MAIN).unwrap(),
+            Schema::parse(X).unwrap(),
+        ]);
+
+        let found: Vec<(&str, &str)> = set
+            .diagnostics
+            .iter()
+            .map(|d| (d.path.as_str(), d.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "schema/complexType[note]/attribute[xlink:href]",
+                    "'xlink:href' does not resolve to an attribute declaration"
+                ),
+                (
+                    "schema/complexType[note]/sequence/group[editorial]",
+                    "'editorial' does not resolve to a model group definition"
+                ),
+                (
+                    "schema/attributeGroup[print-style]/attribute[color]",
+                    "'color' does not resolve to a type definition"
+                ),
+            ]
+        );
+        assert!(set.diagnostics.iter().all(|d| d.code == "src-resolve"));
+    }
+
+    #[test]
+    fn builtin_base() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="midi-16384">
+        <xs:restriction base="xs:positiveInteger">
+            <xs:minInclusive value="1"/>
+            <xs:maxInclusive value="16384"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="midi-channel">
+        <xs:restriction base="midi-16384"/>
+    </xs:simpleType>
+    <xs:simpleType name="sizes">
+        <xs:list itemType="xs:integer"/>
+    </xs:simpleType>
+    <xs:simpleType name="unknown">
+        <xs:restriction base="xs:integers"/>
+    </xs:simpleType>
+</xs:schema>
+"#;
+        let set = SchemaSet::new(vec![Schema::parse(xml).unwrap()]);
+
+        let channel = QName::new(None, "midi-channel");
+        assert_eq!(set.builtin_base(&channel).unwrap().name, "positiveInteger");
+        assert_eq!(set.primitive_type(&channel).unwrap().name, "decimal");
+        assert!(set.primitive_type(&QName::new(None, "sizes")).is_none());
+
+        assert_eq!(set.diagnostics.len(), 1);
+        assert_eq!(
+            set.diagnostics[0].message,
+            "'xs:integers' does not resolve to a type definition".to_string()
+        );
+    }
+
+    #[test]
+    fn load() {
+        let directory = tempfile::tempdir().unwrap();
+        std::fs::write(directory.path().join("main.xsd"), MAIN).unwrap();
+        std::fs::write(directory.path().join("x.xsd"), X).unwrap();
+
+        let set = SchemaSet::load(&directory.path().join("main.xsd")).unwrap();
+
+        assert_eq!(set.schemas.len(), 2);
+        assert_eq!(set.schemas[1].target_namespace, Some("urn:x".to_string()));
+        assert_eq!(set.diagnostics.len(), 3);
+    }
+
+    #[test]
+    fn circular_includes() {
+        let mut resolver = MemoryResolver::new();
+        resolver.insert(
+            "schemas/a.xsd",
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:a" xmlns="urn:a">
+    <xs:include schemaLocation="common/b.xsd"/>
+    <xs:element name="a" type="b"/>
+</xs:schema>"#,
+        );
+        resolver.insert(
+            "schemas/common/b.xsd",
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:include schemaLocation="../a.xsd"/>
+    <xs:include schemaLocation="./missing.xsd"/>
+    <xs:complexType name="b"/>
+</xs:schema>"#,
+        );
+
+        let set = SchemaSet::load_with_resolver("schemas/a.xsd", &resolver).unwrap();
+
+        assert_eq!(set.schemas.len(), 2);
+        assert_eq!(set.schemas[1].target_namespace, Some("urn:a".to_string()));
+        assert!(set.complex_type(&QName::new(Some("urn:a"), "b")).is_some());
+
+        assert_eq!(set.diagnostics.len(), 1);
+        assert_eq!(set.diagnostics[0].code, "schema-not-loaded".to_string());
+        assert_eq!(
+            set.diagnostics[0].span.as_ref().unwrap().document,
+            Some("schemas/common/b.xsd".to_string())
+        );
+
+        assert!(matches!(
+            SchemaSet::load_with_resolver("schemas/missing.xsd", &resolver),
+            Err(Error::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn load_with_options() {
+        let mut resolver = MemoryResolver::new();
+        resolver.insert(
+            "schemas/a.xsd",
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:include schemaLocation="b.xsd"/>
+</xs:schema>"#,
+        );
+        resolver.insert(
+            "schemas/b.xsd",
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:complexType name="b" color="red"/>
+</xs:schema>"#,
+        );
+
+        let set = SchemaSet::load_with_resolver("schemas/a.xsd", &resolver).unwrap();
+        assert!(set.diagnostics.is_empty(), "{:?}", set.diagnostics);
+
+        let options = ParseOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let set = SchemaSet::load_with_options("schemas/a.xsd", &resolver, &options).unwrap();
+
+        assert_eq!(set.diagnostics.len(), 1, "{:?}", set.diagnostics);
+        assert_eq!(set.diagnostics[0].code, "unconsumed-attribute".to_string());
+        assert_eq!(set.diagnostics[0].path, "schema/complexType[b]".to_string());
+        assert_eq!(
+            set.diagnostics[0].span.as_ref().unwrap().document,
+            Some("schemas/b.xsd".to_string())
+        );
+    }
+}
@@ -1,24 +1,50 @@
-use crate::{element::Element, group::Group};
+use crate::{
+    choice::ChoiceItems, element::Element, error::Error, group::Group, parse_context::ParseContext,
+    prelude::Occurs, source_span::SourceSpan,
+};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Sequence {
     pub elements: Vec<Element>,
     pub groups: Vec<Group>,
+    pub occurs: Vec<Occurs>,
+
+    /// All particles of the sequence in document order, including the ones in `elements` and
+    /// `groups`.
+    pub particles: Vec<ChoiceItems>,
+
+    pub span: Option<SourceSpan>,
 }
 
 impl Sequence {
-    pub fn read(element: &mut xmltree::Element) -> Self {
-        let mut r = Sequence::default();
-
-        while let Some(mut element) = element.take_child("element") {
-            r.elements.push(Element::read(&mut element));
-        }
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut xmltree::Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
 
-        while let Some(mut group) = element.take_child("group") {
-            r.groups.push(Group::read(&mut group));
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(
+        element: &mut xmltree::Element,
+        ctx: &mut ParseContext,
+    ) -> Result<Self, Error> {
+        let mut r = Sequence {
+            span: ctx.span(element),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope("sequence", None, &r.span);
+        r.occurs = Occurs::read_with_context(element, ctx);
+
+        while let Some(item) = ChoiceItems::read_next(element, ctx)? {
+            match &item {
+                ChoiceItems::Element(e) => r.elements.push(e.clone()),
+                ChoiceItems::Group(g) => r.groups.push(g.clone()),
+                _ => {}
+            }
+            r.particles.push(item);
         }
 
-        r
+        ctx.leave(element);
+        Ok(r)
     }
 }
 
@@ -73,7 +99,7 @@ mod tests {
 </xs:schema>        
         "#;
         let mut element = xmltree::Element::parse(xml.as_bytes()).unwrap();
-        let item = Schema::read(&mut element);
+        let item = Schema::read(&mut element).unwrap();
 
         assert_eq!(item.complex_types.len(), 1);
         assert_eq!(item.complex_types[0].name, "midi-instrument".to_string());
@@ -1,42 +1,73 @@
-use crate::prelude::{Annotation, Attribute, AttributeGroup, Extension};
+use crate::{
+    error::Error,
+    parse_context::ParseContext,
+    prelude::{Annotation, Any, Attribute, AttributeGroup, Extension, Restriction},
+    source_span::SourceSpan,
+};
 use xmltree::Element;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct SimpleContent {
     pub base: String,
 
     pub annotations: Vec<Annotation>,
     pub attributes: Vec<Attribute>,
     pub attribute_groups: Vec<AttributeGroup>,
+    pub any_attribute: Option<Any>,
     pub extensions: Vec<Extension>,
+    pub restrictions: Vec<Restriction>,
+
+    pub span: Option<SourceSpan>,
 }
 
 impl SimpleContent {
-    pub fn read(element: &mut Element) -> Self {
-        let mut r = SimpleContent::default();
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
 
-        if element.attributes.contains_key("base") {
-            r.base = element.attributes["base"].to_string();
-        }
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(element: &mut Element, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let mut r = SimpleContent {
+            span: ctx.span(element),
+            base: element.attributes.remove("base").unwrap_or_default(),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope("simpleContent", None, &r.span);
 
         while let Some(mut annotation) = element.take_child("annotation") {
-            r.annotations.push(Annotation::read(&mut annotation));
+            r.annotations
+                .push(Annotation::read_with_context(&mut annotation, ctx)?);
         }
 
         while let Some(mut attribute) = element.take_child("attribute") {
-            r.attributes.push(Attribute::read(&mut attribute));
+            r.attributes
+                .push(Attribute::read_with_context(&mut attribute, ctx)?);
         }
 
         while let Some(mut attribute_group) = element.take_child("attributeGroup") {
-            r.attribute_groups
-                .push(AttributeGroup::read(&mut attribute_group));
+            r.attribute_groups.push(AttributeGroup::read_with_context(
+                &mut attribute_group,
+                ctx,
+            )?);
+        }
+
+        if let Some(mut any_attribute) = element.take_child("anyAttribute") {
+            r.any_attribute = Some(Any::read_with_context(&mut any_attribute, ctx)?);
         }
 
         while let Some(mut extension) = element.take_child("extension") {
-            r.extensions.push(Extension::read(&mut extension));
+            r.extensions
+                .push(Extension::read_with_context(&mut extension, ctx)?);
+        }
+
+        while let Some(mut restriction) = element.take_child("restriction") {
+            r.restrictions
+                .push(Restriction::read_with_context(&mut restriction, ctx)?);
         }
 
-        r
+        ctx.leave(element);
+        Ok(r)
     }
 }
 
@@ -63,7 +94,7 @@ mod tests {
 </xs:schema>        
 "#;
         let mut element = Element::parse(xml.as_bytes()).unwrap();
-        let item = Schema::read(&mut element);
+        let item = Schema::read(&mut element).unwrap();
 
         assert_eq!(item.complex_types.len(), 1);
 
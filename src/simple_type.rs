@@ -1,11 +1,16 @@
 use xmltree::Element;
 
 use crate::{
+    derivation::DerivationSet,
+    error::Error,
+    list::List,
+    parse_context::ParseContext,
     prelude::{Annotation, Restriction},
+    source_span::SourceSpan,
     union::Union,
 };
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct SimpleType {
     /// The name of the type as found inside the xsd definition.
     pub name: String,
@@ -18,37 +23,72 @@ pub struct SimpleType {
     pub restriction: Option<Restriction>,
 
     pub union: Option<Union>,
+
+    pub list: Option<List>,
+
+    /// Derivation methods the type may not be used for. `None` if the attribute is not given,
+    /// then `finalDefault` of the schema applies.
+    pub r#final: Option<DerivationSet>,
+
+    /// Where the type was defined.
+    pub span: Option<SourceSpan>,
 }
 
 impl SimpleType {
-    pub fn read(element: &mut Element) -> Self {
-        let mut r = SimpleType::default();
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
 
-        if element.attributes.contains_key("name") {
-            r.name = element.attributes["name"].to_string();
-        }
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(element: &mut Element, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let mut r = SimpleType {
+            span: ctx.span(element),
+            name: element.attributes.remove("name").unwrap_or_default(),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope("simpleType", Some(&r.name), &r.span);
+        r.r#final = DerivationSet::read(element, "final", ctx)?;
 
         while let Some(mut annotation) = element.take_child("annotation") {
-            r.annotations.push(Annotation::read(&mut annotation));
+            r.annotations
+                .push(Annotation::read_with_context(&mut annotation, ctx)?);
         }
 
         while let Some(mut annotation) = element.take_child("restriction") {
-            let res = Restriction::read(&mut annotation);
+            if r.restriction.is_some() {
+                return Err(ctx.invalid("more than one restriction"));
+            }
+
+            let res = Restriction::read_with_context(&mut annotation, ctx)?;
             r.is_enum = res.is_enum();
             r.restriction = Some(res);
         }
 
         while let Some(mut union) = element.take_child("union") {
-            r.union = Some(Union::read(&mut union));
+            if r.restriction.is_some() || r.union.is_some() {
+                return Err(ctx.invalid("only one of restriction, list or union is allowed"));
+            }
+
+            r.union = Some(Union::read_with_context(&mut union, ctx)?);
+        }
+
+        while let Some(mut list) = element.take_child("list") {
+            if r.restriction.is_some() || r.union.is_some() || r.list.is_some() {
+                return Err(ctx.invalid("only one of restriction, list or union is allowed"));
+            }
+
+            r.list = Some(List::read_with_context(&mut list, ctx)?);
         }
 
-        r
+        ctx.leave(element);
+        Ok(r)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{restriction::RestrictionContent, schema::Schema};
+    use crate::{error::Error, restriction::RestrictionContent, schema::Schema};
     use xmltree::Element;
 
     #[test]
@@ -68,7 +108,7 @@ mod tests {
 "#;
 
         let mut element = Element::parse(xml.as_bytes()).unwrap();
-        let item = Schema::read(&mut element);
+        let item = Schema::read(&mut element).unwrap();
 
         assert_eq!(item.simple_types.len(), 1);
         assert_eq!(item.simple_types[0].annotations.len(), 1);
@@ -104,7 +144,7 @@ mod tests {
 "#;
 
         let mut element = Element::parse(xml.as_bytes()).unwrap();
-        let item = Schema::read(&mut element);
+        let item = Schema::read(&mut element).unwrap();
 
         assert_eq!(item.simple_types.len(), 1);
         assert_eq!(item.simple_types[0].annotations.len(), 1);
@@ -134,7 +174,7 @@ mod tests {
 "#;
 
         let mut element = Element::parse(xml.as_bytes()).unwrap();
-        let item = Schema::read(&mut element);
+        let item = Schema::read(&mut element).unwrap();
 
         assert_eq!(item.simple_types.len(), 1);
         assert_eq!(item.simple_types[0].annotations.len(), 1);
@@ -159,7 +199,7 @@ mod tests {
 "#;
 
         let mut element = Element::parse(xml.as_bytes()).unwrap();
-        let item = Schema::read(&mut element);
+        let item = Schema::read(&mut element).unwrap();
 
         assert_eq!(item.simple_types.len(), 1);
         assert_eq!(item.simple_types[0].annotations.len(), 1);
@@ -194,7 +234,7 @@ mod tests {
 "#;
 
         let mut element = Element::parse(xml.as_bytes()).unwrap();
-        let item = Schema::read(&mut element);
+        let item = Schema::read(&mut element).unwrap();
 
         assert_eq!(item.simple_types.len(), 1);
         assert_eq!(item.simple_types[0].annotations.len(), 1);
@@ -216,4 +256,23 @@ mod tests {
             1
         );
     }
+
+    #[test]
+    fn restriction_and_union() {
+        let xml = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="broken">
+		<xs:restriction base="xs:token"/>
+		<xs:union memberTypes="xs:decimal"/>
+	</xs:simpleType>
+</xs:schema>
+"#;
+
+        match Schema::parse(xml) {
+            Err(Error::InvalidSchema { path, .. }) => {
+                assert_eq!(path, "schema/simpleType[broken]".to_string());
+            }
+            r => panic!("Expected invalid schema, got {:?}", r),
+        }
+    }
 }
@@ -0,0 +1,77 @@
+use std::fmt::Display;
+
+/// A position inside a document. Lines and columns start at 1.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub line: u64,
+    pub column: u64,
+}
+
+/// The place a component was read from.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourceSpan {
+    /// URI (or path) of the document, if known.
+    pub document: Option<String>,
+
+    /// Start of the opening tag.
+    pub start: Position,
+
+    /// Start of the closing tag. Equal to `start` for empty elements like `<xs:group ref="a"/>`.
+    pub end: Position,
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+impl Display for SourceSpan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.document {
+            Some(document) => write!(f, "{}:{}", document, self.start),
+            None => write!(f, "{}", self.start),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::Schema;
+
+    #[test]
+    fn spans() {
+        let xml = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="above-below">
+        <xs:restriction base="xs:token">
+            <xs:enumeration value="above"/>
+            <xs:enumeration value="below"/>
+        </xs:restriction>
+    </xs:simpleType>
+</xs:schema>
+"#;
+
+        let item = Schema::parse(xml).unwrap();
+        let simple_type = &item.simple_types[0];
+
+        assert_eq!(
+            simple_type.span,
+            Some(SourceSpan {
+                document: None,
+                start: Position { line: 2, column: 5 },
+                end: Position { line: 7, column: 5 },
+            })
+        );
+
+        let restriction = simple_type.restriction.as_ref().unwrap();
+        assert_eq!(restriction.facet_spans.len(), 2);
+        assert_eq!(
+            restriction.facet_spans[1].as_ref().unwrap().start,
+            Position {
+                line: 5,
+                column: 13
+            }
+        );
+    }
+}
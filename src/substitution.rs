@@ -0,0 +1,371 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::{
+    content_model::{ContentModel, ElementTerm, Particle, Term},
+    derivation::DerivationGraph,
+    diagnostic::{Diagnostic, Severity},
+    document::XSD_NAMESPACE,
+    prelude::{DerivationMethod, Element, QName},
+    schema_set::{ComponentId, DeclarationId, SchemaSet},
+};
+
+/// The substitution groups of the global elements of a [`SchemaSet`]: for every head element,
+/// the elements that may appear in its place.
+#[derive(Debug, Default, Clone)]
+pub struct SubstitutionGroups {
+    /// Members of each group, transitively, in the order they are defined.
+    members: BTreeMap<QName, Vec<QName>>,
+    abstract_elements: HashSet<QName>,
+
+    /// Circular substitution groups.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl SchemaSet {
+    /// Compute the substitution groups of all global elements.
+    pub fn substitution_groups(&self) -> SubstitutionGroups {
+        SubstitutionGroups::new(self)
+    }
+}
+
+impl SubstitutionGroups {
+    pub fn new(set: &SchemaSet) -> Self {
+        let graph = set.derivation_graph();
+        let mut r = SubstitutionGroups::default();
+
+        // Only the first definition of a name counts, like in the symbol tables.
+        let mut elements: Vec<(QName, usize, &Element)> = Vec::new();
+        for (index, schema) in set.schemas.iter().enumerate() {
+            for (i, e) in schema.elements.iter().enumerate() {
+                let name = QName::new(schema.target_namespace.as_deref(), &e.name);
+                if set.lookup(&name, |t| &t.elements).is_some_and(|id| {
+                    id == ComponentId {
+                        schema: index,
+                        index: i,
+                    }
+                }) {
+                    elements.push((name, index, e));
+                }
+            }
+        }
+
+        // Direct members of each head. Unresolved heads are reported when the set is built.
+        let mut affiliations: BTreeMap<&QName, Vec<&QName>> = BTreeMap::new();
+        for (name, schema, e) in elements.iter() {
+            if e.r#abstract {
+                r.abstract_elements.insert(name.clone());
+            }
+
+            let heads = e
+                .substitution_group
+                .iter()
+                .flat_map(|g| g.split_whitespace());
+            for head in heads.filter_map(|head| set.schemas[*schema].resolve_qname(head)) {
+                if let Some((head, _, _)) = elements.iter().find(|(n, _, _)| n == &head) {
+                    affiliations.entry(head).or_default().push(name);
+                }
+            }
+        }
+
+        for (head, schema, e) in elements.iter() {
+            let mut members = Vec::new();
+            let mut visited = HashSet::new();
+            let mut stack: Vec<&QName> = affiliations.get(head).cloned().unwrap_or_default();
+            stack.reverse();
+
+            while let Some(member) = stack.pop() {
+                if member == head {
+                    r.diagnostics.push(Diagnostic {
+                        severity: Severity::Error,
+                        code: "e-props-correct.6".to_string(),
+                        message: format!("element '{}' is in its own substitution group", head),
+                        path: format!("schema/element[{}]", e.name),
+                        span: e.span.clone(),
+                    });
+                    continue;
+                }
+
+                if !visited.insert(member) {
+                    continue;
+                }
+
+                members.push(member.clone());
+                if let Some(next) = affiliations.get(member) {
+                    stack.extend(next.iter().rev());
+                }
+            }
+
+            let block = e.block.unwrap_or(set.schemas[*schema].block_default);
+            members
+                .retain(|member| !block.substitution && substitutable(set, &graph, head, member));
+
+            if !members.is_empty() {
+                r.members.insert(head.clone(), members);
+            }
+        }
+
+        r
+    }
+
+    /// The elements in the substitution group of `head`, directly or through other members,
+    /// including abstract ones. Members whose type derivation is blocked by `head` are left out.
+    pub fn members(&self, head: &QName) -> &[QName] {
+        self.members
+            .get(head)
+            .map(|m| m.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The elements that may appear wherever `head` is allowed: `head` itself, unless it is
+    /// abstract, and every member that is not abstract.
+    pub fn substitutes<'a>(&'a self, head: &'a QName) -> Vec<&'a QName> {
+        std::iter::once(head)
+            .chain(self.members(head).iter())
+            .filter(|name| !self.abstract_elements.contains(*name))
+            .collect()
+    }
+
+    /// Replace every reference to a global element in `model` by a choice of its substitutes.
+    /// References to abstract elements without substitutes are removed if they are optional.
+    pub fn expand<'a>(&self, set: &'a SchemaSet, model: ContentModel<'a>) -> ContentModel<'a> {
+        ContentModel {
+            mixed: model.mixed,
+            particle: model.particle.and_then(|p| self.expand_particle(set, p)),
+        }
+    }
+
+    fn expand_particle<'a>(&self, set: &'a SchemaSet, p: Particle<'a>) -> Option<Particle<'a>> {
+        let expand_all = |particles: Vec<Particle<'a>>| -> Vec<Particle<'a>> {
+            particles
+                .into_iter()
+                .filter_map(|p| self.expand_particle(set, p))
+                .collect()
+        };
+
+        let term = match p.term {
+            Term::Element(e) if matches!(e.id, DeclarationId::Global(_)) => {
+                let mut substitutes: Vec<Particle<'a>> = self
+                    .substitutes(&e.name)
+                    .into_iter()
+                    .filter_map(|name| ElementTerm::global(set, name))
+                    .map(|term| Particle {
+                        min_occurs: 1,
+                        max_occurs: Some(1),
+                        term: Term::Element(term),
+                    })
+                    .collect();
+
+                match substitutes.len() {
+                    0 if p.min_occurs == 0 => return None,
+                    1 => substitutes.pop().unwrap().term,
+                    _ => Term::Choice(substitutes),
+                }
+            }
+            Term::Sequence(particles) => Term::Sequence(expand_all(particles)),
+            Term::Choice(particles) => Term::Choice(expand_all(particles)),
+            Term::All(particles) => Term::All(expand_all(particles)),
+            term => term,
+        };
+
+        Some(Particle { term, ..p })
+    }
+}
+
+/// True if the type of `member` is derived from the type of `head` without using a method
+/// `head` or its type blocks.
+fn substitutable(set: &SchemaSet, graph: &DerivationGraph, head: &QName, member: &QName) -> bool {
+    let (Some(head), Some(member)) = (
+        ElementTerm::global(set, head),
+        ElementTerm::global(set, member),
+    ) else {
+        return false;
+    };
+
+    let blocked = head
+        .declaration
+        .block
+        .unwrap_or(set.schemas[head.schema].block_default);
+    let any_type = QName::new(Some(XSD_NAMESPACE), "anyType");
+
+    // Without a type of its own, an element has the type of its head.
+    let head_type = match head.r#type {
+        Some(t) => t,
+        None if !head.declaration.has_anonymous_type() => any_type.clone(),
+        None => return member.r#type.is_none() && !member.declaration.has_anonymous_type(),
+    };
+
+    let resolve = |base: &str| set.schemas[member.schema].resolve_qname(base);
+    let any_simple_type = QName::new(Some(XSD_NAMESPACE), "anySimpleType");
+    let (method, base) = match (&member.r#type, member.declaration.complex_types.first()) {
+        (Some(t), _) => return graph.is_derived_from(t, &head_type, blocked),
+        (None, Some(anonymous)) => match anonymous.derivation() {
+            Some((method, base)) => match resolve(base) {
+                Some(base) => (method, base),
+                None => return false,
+            },
+            None => (DerivationMethod::Restriction, any_type),
+        },
+        (None, None) => match &member.declaration.simple_type {
+            Some(anonymous) => match (&anonymous.restriction, &anonymous.list) {
+                (Some(restriction), _) => match resolve(&restriction.base) {
+                    Some(base) => (DerivationMethod::Restriction, base),
+                    None => return false,
+                },
+                (None, Some(_)) => (DerivationMethod::List, any_simple_type),
+                (None, None) => (DerivationMethod::Union, any_simple_type),
+            },
+            None => return true,
+        },
+    };
+
+    !blocked.contains(method)
+        && !graph.block(&head_type).contains(method)
+        && graph.is_derived_from(&base, &head_type, blocked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Schema;
+
+    const XML: &str = r##"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:t" xmlns="urn:t" elementFormDefault="qualified">
+    <xs:complexType name="Lane">
+        <xs:attribute name="id" type="xs:ID"/>
+    </xs:complexType>
+    <xs:complexType name="Track">
+        <xs:complexContent>
+            <xs:extension base="Lane"/>
+        </xs:complexContent>
+    </xs:complexType>
+    <xs:complexType name="Marker">
+        <xs:complexContent>
+            <xs:restriction base="Lane"/>
+        </xs:complexContent>
+    </xs:complexType>
+
+    <xs:element name="Lane" type="Lane" abstract="true"/>
+    <xs:element name="Track" type="Track" substitutionGroup="Lane"/>
+    <xs:element name="Channel" substitutionGroup="Lane"/>
+    <xs:element name="AudioTrack" substitutionGroup="Track"/>
+    <xs:element name="Marker" type="Marker" substitutionGroup="Lane"/>
+    <xs:element name="Unrelated" type="xs:string" substitutionGroup="Lane"/>
+    <xs:element name="Anonymous" substitutionGroup="Lane">
+        <xs:complexType>
+            <xs:complexContent>
+                <xs:extension base="Lane"/>
+            </xs:complexContent>
+        </xs:complexType>
+    </xs:element>
+
+    <xs:element name="Strict" type="Lane" block="extension"/>
+    <xs:element name="StrictTrack" type="Track" substitutionGroup="Strict"/>
+    <xs:element name="StrictMarker" type="Marker" substitutionGroup="Strict"/>
+    <xs:element name="Closed" type="Lane" block="#all"/>
+    <xs:element name="ClosedLane" substitutionGroup="Closed"/>
+
+    <xs:element name="a" substitutionGroup="b"/>
+    <xs:element name="b" substitutionGroup="a"/>
+
+    <xs:complexType name="Arrangement">
+        <xs:sequence>
+            <xs:element ref="Lane" maxOccurs="unbounded"/>
+            <xs:element ref="Closed" minOccurs="0"/>
+            <xs:element name="Lane" type="Lane" minOccurs="0"/>
+        </xs:sequence>
+    </xs:complexType>
+</xs:schema>
+"##;
+
+    fn t(name: &str) -> QName {
+        QName::new(Some("urn:t"), name)
+    }
+
+    fn set() -> SchemaSet {
+        SchemaSet::new(vec![Schema::parse(XML).unwrap()])
+    }
+
+    fn names(names: &[&QName]) -> Vec<String> {
+        names.iter().map(|n| n.name.clone()).collect()
+    }
+
+    #[test]
+    fn members() {
+        let set = set();
+        assert!(set.diagnostics.is_empty(), "{:?}", set.diagnostics);
+        let groups = set.substitution_groups();
+
+        let members: Vec<&QName> = groups.members(&t("Lane")).iter().collect();
+        assert_eq!(
+            names(&members),
+            vec!["Track", "AudioTrack", "Channel", "Marker", "Anonymous"]
+        );
+        assert_eq!(
+            names(&groups.substitutes(&t("Lane"))),
+            vec!["Track", "AudioTrack", "Channel", "Marker", "Anonymous"]
+        );
+        assert_eq!(
+            names(&groups.substitutes(&t("Track"))),
+            vec!["Track", "AudioTrack"]
+        );
+        assert!(groups.members(&t("Unrelated")).is_empty());
+
+        // block="extension" and block="#all" on the head.
+        assert_eq!(
+            names(&groups.substitutes(&t("Strict"))),
+            vec!["Strict", "StrictMarker"]
+        );
+        assert_eq!(names(&groups.substitutes(&t("Closed"))), vec!["Closed"]);
+
+        let codes: Vec<&str> = groups.diagnostics.iter().map(|d| d.code.as_str()).collect();
+        assert_eq!(codes, vec!["e-props-correct.6", "e-props-correct.6"]);
+    }
+
+    #[test]
+    fn expand() {
+        let set = set();
+        let groups = set.substitution_groups();
+
+        let (model, _) = set.content_model(&t("Arrangement")).unwrap();
+        let model = groups.expand(&set, model);
+
+        let Some(Particle {
+            term: Term::Sequence(particles),
+            ..
+        }) = &model.particle
+        else {
+            panic!("Expected sequence, got {:?}", model.particle);
+        };
+        assert_eq!(particles.len(), 3);
+
+        match &particles[0] {
+            Particle {
+                min_occurs: 1,
+                max_occurs: None,
+                term: Term::Choice(choices),
+            } => {
+                assert_eq!(choices.len(), 5);
+                match &choices[2].term {
+                    Term::Element(e) => {
+                        assert_eq!(e.name, t("Channel"));
+                        // Channel has no type of its own and inherits Lane.
+                        assert_eq!(e.r#type, Some(t("Lane")));
+                    }
+                    term => panic!("Expected element, got {:?}", term),
+                }
+            }
+            p => panic!("Expected choice, got {:?}", p),
+        }
+
+        match &particles[1].term {
+            Term::Element(e) => assert_eq!(e.name, t("Closed")),
+            term => panic!("Expected element, got {:?}", term),
+        }
+
+        // Local declarations are never substituted.
+        match &particles[2].term {
+            Term::Element(e) => assert_eq!(e.name, t("Lane")),
+            term => panic!("Expected element, got {:?}", term),
+        }
+    }
+}
@@ -1,29 +1,44 @@
 use xmltree::Element;
 
-use crate::prelude::SimpleType;
+use crate::{
+    error::Error, parse_context::ParseContext, prelude::SimpleType, source_span::SourceSpan,
+};
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Union {
     pub types: Vec<String>,
 
     pub simple_types: Vec<SimpleType>,
+
+    pub span: Option<SourceSpan>,
 }
 
 impl Union {
-    pub fn read(element: &mut Element) -> Self {
-        let mut r = Union::default();
+    /// Read the component with a default [`ParseContext`].
+    pub fn read(element: &mut Element) -> Result<Self, Error> {
+        Self::read_with_context(element, &mut ParseContext::default())
+    }
+
+    /// Read the component, reporting to the given context.
+    pub fn read_with_context(element: &mut Element, ctx: &mut ParseContext) -> Result<Self, Error> {
+        let mut r = Union {
+            span: ctx.span(element),
+            ..Default::default()
+        };
+        let ctx = &mut *ctx.scope("union", None, &r.span);
 
-        if element.attributes.contains_key("memberTypes") {
-            let types = element.attributes["memberTypes"].to_string();
+        if let Some(types) = element.attributes.remove("memberTypes") {
             for s in types.split(" ") {
                 r.types.push(s.to_string());
             }
         }
 
         while let Some(mut simple_type) = element.take_child("simpleType") {
-            r.simple_types.push(SimpleType::read(&mut simple_type));
+            r.simple_types
+                .push(SimpleType::read_with_context(&mut simple_type, ctx)?);
         }
 
-        r
+        ctx.leave(element);
+        Ok(r)
     }
 }
@@ -0,0 +1,1977 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    io::Read,
+    rc::Rc,
+};
+
+use xml::{common::Position as _, namespace::Namespace, reader::XmlEvent, EventReader};
+
+use crate::{
+    automaton::{ContentAutomaton, ContentState},
+    builtins::{BuiltinType, WhiteSpace},
+    content_model::{ElementTerm, Term},
+    derivation::DerivationGraph,
+    diagnostic::{Diagnostic, Severity},
+    document::XSD_NAMESPACE,
+    element::Element,
+    error::Error,
+    identity::IdentityTables,
+    prelude::{
+        Any, AttributeUse, AttributeUses, ComplexType, ProcessContents, QName, SchemaResolver,
+        SimpleType,
+    },
+    psvi::{PsviEvent, TypedAttribute, Validity},
+    schema_set::{LocalScope, SchemaSet},
+    source_span::{Position, SourceSpan},
+    substitution::SubstitutionGroups,
+    value::{SimpleValue, ValueError, ValueValidator},
+};
+
+/// Namespace of the `xsi:type`, `xsi:nil` and `xsi:schemaLocation` attributes.
+pub(crate) const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+impl SchemaSet {
+    /// Validate the instance document `xml` against the schemas of the set, see
+    /// [`Validator::validate`].
+    pub fn validate(&self, xml: &str) -> Result<Vec<Diagnostic>, Error> {
+        Validator::new(self).validate(xml)
+    }
+
+    /// Load the schemas the instance document `xml` names with `xsi:schemaLocation` and
+    /// `xsi:noNamespaceSchemaLocation`, on any of its elements. The locations are relative to
+    /// `uri`, the location of the instance. Schemas that cannot be loaded are reported as
+    /// diagnostics.
+    pub fn load_for_instance(
+        xml: &str,
+        uri: Option<&str>,
+        resolver: &dyn SchemaResolver,
+    ) -> Result<Self, Error> {
+        let mut reader = EventReader::new(xml.as_bytes());
+        let mut locations = Vec::new();
+
+        loop {
+            match reader.next()? {
+                XmlEvent::StartElement { attributes, .. } => {
+                    let start = position(&reader);
+                    let span = Some(SourceSpan {
+                        document: uri.map(str::to_string),
+                        start,
+                        end: start,
+                    });
+
+                    for a in attributes
+                        .iter()
+                        .filter(|a| a.name.namespace.as_deref() == Some(XSI_NAMESPACE))
+                    {
+                        match a.name.local_name.as_str() {
+                            "schemaLocation" => {
+                                let parts: Vec<&str> = a.value.split_whitespace().collect();
+                                for pair in parts.chunks_exact(2) {
+                                    let namespace = Some(pair[0].to_string());
+                                    locations.push((namespace, pair[1].to_string(), span.clone()));
+                                }
+                            }
+                            "noNamespaceSchemaLocation" => {
+                                locations.push((None, a.value.trim().to_string(), span.clone()))
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                XmlEvent::EndDocument => break,
+                _ => {}
+            }
+        }
+
+        SchemaSet::load_locations(&locations, uri, "xsi:schemaLocation", resolver)
+    }
+}
+
+/// Where the event `reader` returned last starts.
+fn position<R: Read>(reader: &EventReader<R>) -> Position {
+    let position = reader.position();
+    Position {
+        line: position.row + 1,
+        column: position.column + 1,
+    }
+}
+
+/// Validates instance documents against the schemas of a [`SchemaSet`].
+pub struct Validator<'a> {
+    set: &'a SchemaSet,
+    graph: DerivationGraph,
+    groups: SubstitutionGroups,
+    values: ValueValidator<'a>,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(set: &'a SchemaSet) -> Self {
+        Validator {
+            set,
+            graph: set.derivation_graph(),
+            groups: set.substitution_groups(),
+            values: ValueValidator::new(set),
+        }
+    }
+
+    /// Validate the instance document `xml`, starting with the global element declaration of
+    /// its root element. Returns an error only if `xml` is not well-formed. Everything else is
+    /// reported as a diagnostic, with the code of the violated validation rule of XML Schema
+    /// Part 1, e.g. `cvc-complex-type.2.4.a`, and the path of the element, e.g.
+    /// `/score-partwise/part[1]/measure[2]`.
+    pub fn validate(&self, xml: &str) -> Result<Vec<Diagnostic>, Error> {
+        self.validate_reader(xml.as_bytes(), None)
+    }
+
+    /// Like [`Validator::validate`], reading the document from `reader`. `uri` is the location
+    /// of the document, it is copied into the spans of the diagnostics.
+    pub fn validate_reader<R: Read>(
+        &self,
+        reader: R,
+        uri: Option<&str>,
+    ) -> Result<Vec<Diagnostic>, Error> {
+        let mut diagnostics = Vec::new();
+        self.validate_stream(reader, uri, |d| diagnostics.push(d))?;
+
+        Ok(diagnostics)
+    }
+
+    /// Validate the document read from `reader` while it is parsed, and pass every diagnostic
+    /// to `report` as soon as it is found. Apart from IDs and the values of key constraints,
+    /// only the open elements are kept in memory, so memory use grows with the nesting depth
+    /// of the document, not its size.
+    ///
+    /// If the document turns out not to be well-formed, the diagnostics found up to that point
+    /// have already been reported when the error is returned.
+    pub fn validate_stream<R: Read>(
+        &self,
+        reader: R,
+        uri: Option<&str>,
+        report: impl FnMut(Diagnostic),
+    ) -> Result<(), Error> {
+        self.run(reader, self.stream(uri), |_| {}, report)
+    }
+
+    /// Like [`Validator::validate_stream`], and pass the document annotated with the results of
+    /// validation to `annotate`, one [`PsviEvent`] per start and end tag.
+    pub fn annotate_stream<R: Read>(
+        &self,
+        reader: R,
+        uri: Option<&str>,
+        annotate: impl FnMut(PsviEvent),
+        report: impl FnMut(Diagnostic),
+    ) -> Result<(), Error> {
+        self.run(reader, self.stream(uri).annotated(), annotate, report)
+    }
+
+    fn run<R: Read>(
+        &self,
+        reader: R,
+        mut stream: ValidationStream<'_, 'a>,
+        mut annotate: impl FnMut(PsviEvent),
+        mut report: impl FnMut(Diagnostic),
+    ) -> Result<(), Error> {
+        let mut reader = EventReader::new(reader);
+
+        loop {
+            let event = reader.next()?;
+            stream.event(&event, position(&reader));
+            stream.diagnostics.drain(..).for_each(&mut report);
+            stream.events.drain(..).for_each(&mut annotate);
+
+            if event == XmlEvent::EndDocument {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Validate a document whose events are read elsewhere, e.g. by a reader that also does
+    /// something else with them. Feed every event to [`ValidationStream::event`].
+    pub fn stream(&self, uri: Option<&str>) -> ValidationStream<'_, 'a> {
+        ValidationStream {
+            validator: self,
+            document: uri.map(str::to_string),
+            frames: Vec::new(),
+            path: Vec::new(),
+            contents: HashMap::new(),
+            attribute_uses: HashMap::new(),
+            diagnostics: Vec::new(),
+            reported: 0,
+            identity: IdentityTables::new(self.set),
+            ids: HashSet::new(),
+            references: Vec::new(),
+            annotate: false,
+            events: Vec::new(),
+            root_end: None,
+        }
+    }
+}
+
+/// Validates one document event by event, see [`Validator::stream`].
+pub struct ValidationStream<'v, 'a> {
+    validator: &'v Validator<'a>,
+    document: Option<String>,
+
+    /// The open elements, the innermost last.
+    frames: Vec<Frame<'a>>,
+
+    /// Steps to the element being validated, e.g. `["/score-partwise", "/part[1]"]`.
+    path: Vec<String>,
+
+    /// The compiled content of the complex types seen so far.
+    contents: HashMap<LocalScope, Rc<ElementContent<'a>>>,
+
+    /// The effective attribute uses and wildcards of the complex types seen so far.
+    attribute_uses: HashMap<LocalScope, Rc<AttributeUses<'a>>>,
+    diagnostics: Vec<Diagnostic>,
+
+    /// The number of diagnostics reported so far, including the ones already taken.
+    reported: usize,
+
+    /// The key tables of the identity constraints.
+    identity: IdentityTables<'a>,
+
+    /// The values of type `ID` seen so far.
+    ids: HashSet<String>,
+
+    /// Values of type `IDREF` that were not an ID yet when they were seen, with the path and
+    /// position of their element.
+    references: Vec<(String, String, Position)>,
+
+    /// Whether to record the annotated document in `events`.
+    annotate: bool,
+    events: Vec<PsviEvent>,
+
+    /// The end of the root element, held back until the end of the document because references
+    /// to IDs can still make it invalid.
+    root_end: Option<PsviEvent>,
+}
+
+/// The type an element is validated against.
+enum TypeDefinition<'a> {
+    /// A complex type, the index of the schema document it is written in and its identity.
+    Complex(usize, &'a ComplexType, LocalScope),
+    Simple(SimpleDefinition<'a>),
+
+    /// `xs:anyType`: any attributes and any content.
+    Any,
+}
+
+/// A simple type values are checked against.
+enum SimpleDefinition<'a> {
+    Named(QName),
+
+    /// The anonymous type of an element declaration and the index of the schema document it is
+    /// written in.
+    Anonymous(usize, &'a SimpleType),
+}
+
+/// The element content of a complex type.
+struct ElementContent<'a> {
+    automaton: ContentAutomaton<'a>,
+    mixed: bool,
+
+    /// The type allows no child elements at all.
+    empty: bool,
+}
+
+/// An open element.
+struct Frame<'a> {
+    /// The local name, for messages.
+    name: String,
+    start: Position,
+    content: Content<'a>,
+
+    /// The governing type, for the annotated document.
+    r#type: Option<QName>,
+
+    /// The element declaration and the index of the schema document it is written in.
+    declaration: Option<(usize, &'a Element)>,
+
+    /// The type `xsi:type` names, until the element is validated.
+    xsi_type: Option<Option<QName>>,
+
+    /// `xsi:nil` is true.
+    nil: bool,
+
+    /// The default or fixed value of the declaration.
+    default: Option<&'a str>,
+
+    /// The fixed value of the declaration, the content must be equal to it.
+    fixed: Option<&'a str>,
+
+    /// The value of [`ValidationStream::reported`] before the element was validated.
+    reported: usize,
+
+    /// The annotated attributes, until the start tag is annotated.
+    attributes: Vec<TypedAttribute>,
+
+    /// The character data so far, if the element has a simple type or simple content.
+    text: String,
+    has_children: bool,
+
+    /// The number of children seen so far by local name, for the path steps.
+    counts: HashMap<String, usize>,
+}
+
+/// How the content of an open element is validated.
+enum Content<'a> {
+    /// The element has the simple type `r#type`, or a complex type with simple content.
+    Simple {
+        r#type: SimpleDefinition<'a>,
+        complex: bool,
+    },
+    Complex {
+        content: Rc<ElementContent<'a>>,
+        state: ContentState,
+
+        /// A problem with the children was reported, the remaining children are skipped.
+        failed: bool,
+
+        /// Character data was reported as not allowed.
+        text_reported: bool,
+    },
+
+    /// `xs:anyType` and lax wildcards: children are validated if they have a global
+    /// declaration.
+    Lax,
+
+    /// `xsi:nil` is true, the element must be empty.
+    Nil,
+
+    /// Nothing inside the element is validated.
+    Skip,
+}
+
+/// What the parent decided about a new child element.
+enum Child<'a> {
+    Root,
+    Element(ElementTerm<'a>),
+    Wildcard(&'a Any),
+    Lax,
+    Unexpected(String),
+    Skip,
+}
+
+impl<'v, 'a> ValidationStream<'v, 'a> {
+    /// Validate the next event of the document. `position` is where the event starts. References
+    /// to IDs are checked at [`XmlEvent::EndDocument`].
+    pub fn event(&mut self, event: &XmlEvent, position: Position) {
+        match event {
+            XmlEvent::StartElement {
+                name,
+                attributes,
+                namespace,
+            } => {
+                let mut attributes: Vec<(QName, &str)> = attributes
+                    .iter()
+                    .map(|a| {
+                        let namespace = a.name.namespace.as_deref().filter(|n| !n.is_empty());
+                        (QName::new(namespace, &a.name.local_name), a.value.as_str())
+                    })
+                    .collect();
+                attributes.sort();
+
+                let xsi_type = attributes
+                    .iter()
+                    .find(|(n, _)| {
+                        n.namespace.as_deref() == Some(XSI_NAMESPACE) && n.name == "type"
+                    })
+                    .map(|(_, value)| resolve_qname(namespace, value));
+
+                let qname = QName::new(
+                    name.namespace.as_deref().filter(|n| !n.is_empty()),
+                    &name.local_name,
+                );
+                self.start_element(&qname, &attributes, xsi_type, position);
+            }
+            XmlEvent::EndElement { .. } => self.end_element(position),
+            XmlEvent::Characters(text) | XmlEvent::CData(text) => self.text(text, false),
+            XmlEvent::Whitespace(text) => self.text(text, true),
+            XmlEvent::EndDocument => self.end_document(),
+            _ => {}
+        }
+    }
+
+    /// Take the diagnostics found so far.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    /// Also annotate the document with the results of validation, see
+    /// [`ValidationStream::take_events`].
+    pub fn annotated(mut self) -> Self {
+        self.annotate = true;
+        self
+    }
+
+    /// Take the events of the annotated document found so far. An element is annotated with
+    /// its validity and value at its end tag, the root element at [`XmlEvent::EndDocument`].
+    /// Always empty if the stream is not [`annotated`](ValidationStream::annotated).
+    pub fn take_events(&mut self) -> Vec<PsviEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Start the element `name`. `xsi_type` is the type named by its `xsi:type` attribute, `None`
+    /// inside if the prefix is not declared.
+    fn start_element(
+        &mut self,
+        name: &QName,
+        attributes: &[(QName, &str)],
+        xsi_type: Option<Option<QName>>,
+        position: Position,
+    ) {
+        let child = self.child(name);
+
+        let step = match self.frames.last_mut() {
+            Some(parent) => {
+                let count = parent.counts.entry(name.name.clone()).or_default();
+                *count += 1;
+                format!("/{}[{}]", name.name, count)
+            }
+            None => format!("/{}", name.name),
+        };
+        self.path.push(step);
+        self.frames.push(Frame {
+            name: name.name.clone(),
+            start: position,
+            content: Content::Skip,
+            r#type: None,
+            declaration: None,
+            xsi_type,
+            nil: false,
+            default: None,
+            fixed: None,
+            reported: self.reported,
+            attributes: Vec::new(),
+            text: String::new(),
+            has_children: false,
+            counts: HashMap::new(),
+        });
+
+        let set = self.validator.set;
+        let content = match child {
+            Child::Root => match ElementTerm::global(set, name) {
+                Some(term) if term.declaration.r#abstract => {
+                    self.report(
+                        "cvc-elt.2",
+                        format!("element '{}' is abstract", name),
+                        position,
+                        None,
+                    );
+                    Content::Skip
+                }
+                Some(term) => self.element(&term, attributes, position),
+                None => {
+                    self.report(
+                        "cvc-elt.1",
+                        format!("no declaration found for element '{}'", name),
+                        position,
+                        None,
+                    );
+                    Content::Skip
+                }
+            },
+            Child::Element(term) => self.element(&term, attributes, position),
+            Child::Wildcard(wildcard) => self.wildcard(name, wildcard, attributes, position),
+            Child::Lax => match ElementTerm::global(set, name) {
+                Some(term) => self.element(&term, attributes, position),
+                None => Content::Lax,
+            },
+            Child::Unexpected(expected) => {
+                self.report(
+                    "cvc-complex-type.2.4.a",
+                    format!("element '{}' is not expected here{}", name.name, expected),
+                    position,
+                    None,
+                );
+                Content::Skip
+            }
+            Child::Skip => Content::Skip,
+        };
+        let Some(frame) = self.top() else {
+            return;
+        };
+        frame.content = content;
+
+        let declaration = frame.declaration;
+        self.identity.start(name, declaration, &self.path, position);
+        if self.identity.is_selecting() {
+            self.identity_attributes(attributes);
+        }
+
+        match self.annotate {
+            true => self.annotate_start(name, attributes, position),
+            false => self.top().into_iter().for_each(|f| f.attributes.clear()),
+        }
+    }
+
+    /// Every reference to an ID must have a matching ID in the document.
+    fn end_document(&mut self) {
+        let reported = self.reported;
+        for (id, path, start) in std::mem::take(&mut self.references) {
+            if !self.ids.contains(&id) {
+                self.report_at(
+                    "cvc-id.1",
+                    format!("there is no ID '{}' in the document", id),
+                    path,
+                    start,
+                    start,
+                );
+            }
+        }
+
+        if let Some(mut end) = self.root_end.take() {
+            if let PsviEvent::EndElement { validity, .. } = &mut end {
+                if self.reported > reported {
+                    *validity = Validity::Invalid;
+                }
+            }
+            self.events.push(end);
+        }
+    }
+
+    /// Record the value of an attribute or element of type `ID`, `IDREF` or `IDREFS`, or a
+    /// type derived from them. `start` is the position of the element.
+    fn identifiers(&mut self, value: &SimpleValue, start: Position) {
+        let Some(builtin) = value.builtin else {
+            return;
+        };
+        let derived = |name| {
+            std::iter::once(builtin)
+                .chain(builtin.ancestors())
+                .any(|t| t.name == name)
+        };
+
+        if derived("ID") && !value.list {
+            if !self.ids.insert(value.normalized.clone()) {
+                self.report(
+                    "cvc-id.2",
+                    format!("ID '{}' is not unique", value.normalized),
+                    start,
+                    None,
+                );
+            }
+        } else if derived("IDREF") {
+            for item in value.items() {
+                if !self.ids.contains(item) {
+                    self.references
+                        .push((item.to_string(), self.path.concat(), start));
+                }
+            }
+        }
+    }
+
+    /// Pass the attributes of the element being started to the identity constraints, with
+    /// the values normalized for their types.
+    fn identity_attributes(&mut self, attributes: &[(QName, &str)]) {
+        let Some(frame) = self.frames.last() else {
+            return;
+        };
+
+        for (name, value) in attributes {
+            let normalized = frame
+                .attributes
+                .iter()
+                .find(|a| a.name == *name)
+                .and_then(|a| a.value.as_ref())
+                .map_or(*value, |v| v.normalized.as_str());
+            self.identity.attribute(name, normalized);
+        }
+
+        for a in frame.attributes.iter().filter(|a| a.defaulted) {
+            let value = a
+                .value
+                .as_ref()
+                .map_or(a.lexical.as_str(), |v| &v.normalized);
+            self.identity.attribute(&a.name, value);
+        }
+    }
+
+    /// The innermost open element. `None` only if events are fed out of order.
+    fn top(&mut self) -> Option<&mut Frame<'a>> {
+        self.frames.last_mut()
+    }
+
+    fn annotate_start(&mut self, name: &QName, attributes: &[(QName, &str)], position: Position) {
+        let Some(frame) = self.top() else {
+            return;
+        };
+        let r#type = frame.r#type.clone();
+        let nil = frame.nil;
+        let mut typed = std::mem::take(&mut frame.attributes);
+
+        // Attributes that were not validated: xsi attributes, and everything in lax and
+        // skipped content.
+        for (name, value) in attributes {
+            if !typed.iter().any(|a| a.name == *name) {
+                typed.push(TypedAttribute {
+                    name: name.clone(),
+                    r#type: None,
+                    validity: Validity::NotKnown,
+                    lexical: value.to_string(),
+                    value: None,
+                    defaulted: false,
+                });
+            }
+        }
+        typed.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let span = self.span(position, position);
+        self.events.push(PsviEvent::StartElement {
+            name: name.clone(),
+            r#type,
+            nil,
+            attributes: typed,
+            span,
+        });
+    }
+
+    /// Match a new child element against the content of its parent. Problems of the parent
+    /// are reported right away.
+    fn child(&mut self, name: &QName) -> Child<'a> {
+        let Some(parent) = self.frames.last_mut() else {
+            return Child::Root;
+        };
+        let first_child = !std::mem::replace(&mut parent.has_children, true);
+
+        let problem = match &mut parent.content {
+            Content::Simple { complex, .. } if first_child => {
+                let code = match complex {
+                    true => "cvc-complex-type.2.2",
+                    false => "cvc-type.3.1.2",
+                };
+                (code, "must not have element children")
+            }
+            Content::Nil if first_child => ("cvc-elt.3.2.1", "is nil and must be empty"),
+            Content::Complex {
+                content,
+                state,
+                failed: failed @ false,
+                ..
+            } => {
+                if !content.empty {
+                    return match content.automaton.step(state, name) {
+                        Some(Term::Element(term)) => Child::Element(term.clone()),
+                        Some(Term::Any { wildcard, .. }) => Child::Wildcard(wildcard),
+                        Some(_) => Child::Skip,
+                        None => {
+                            *failed = true;
+                            Child::Unexpected(expected(&content.automaton, state))
+                        }
+                    };
+                }
+
+                *failed = true;
+                ("cvc-complex-type.2.1", "must be empty")
+            }
+            Content::Lax => return Child::Lax,
+            _ => return Child::Skip,
+        };
+
+        let (name, start) = (parent.name.clone(), parent.start);
+        let (code, message) = problem;
+        self.report(code, format!("element '{}' {}", name, message), start, None);
+
+        Child::Skip
+    }
+
+    fn end_element(&mut self, position: Position) {
+        // The reader guarantees balanced tags, events fed by hand may not be.
+        let Some(frame) = self.frames.pop() else {
+            self.report(
+                "unbalanced-end-element",
+                "end tag without an open element is ignored".to_string(),
+                position,
+                None,
+            );
+            return;
+        };
+        let mut value = None;
+        let mut defaulted = false;
+
+        if let (Some(fixed), Content::Complex { .. }) = (frame.fixed, &frame.content) {
+            if frame.has_children {
+                self.report(
+                    "cvc-elt.5.2.2.1",
+                    format!(
+                        "element '{}' has a fixed value and must not have element children",
+                        frame.name
+                    ),
+                    frame.start,
+                    Some(position),
+                );
+            } else if !frame.text.is_empty() && frame.text != fixed {
+                self.report(
+                    "cvc-elt.5.2.2.2.1",
+                    format!(
+                        "content of element '{}' must be its fixed value '{}'",
+                        frame.name, fixed
+                    ),
+                    frame.start,
+                    Some(position),
+                );
+            }
+        }
+
+        match frame.content {
+            Content::Simple { r#type, complex } if !frame.has_children => {
+                let text = match frame.default {
+                    Some(default) if frame.text.is_empty() => {
+                        defaulted = true;
+                        default
+                    }
+                    _ => frame.text.as_str(),
+                };
+
+                match self.simple_value(&r#type, text) {
+                    Ok(v) => {
+                        let fixed = frame.fixed.map(|fixed| self.simple_value(&r#type, fixed));
+                        if fixed.is_some_and(|fixed| !fixed.is_ok_and(|fixed| fixed.equals(&v))) {
+                            self.report(
+                                "cvc-elt.5.2.2.2.2",
+                                format!(
+                                    "value of element '{}' must be equal to its fixed value '{}'",
+                                    frame.name,
+                                    frame.fixed.unwrap_or_default()
+                                ),
+                                frame.start,
+                                Some(position),
+                            );
+                        }
+
+                        self.identifiers(&v, frame.start);
+                        value = Some(v);
+                    }
+                    Err(error) => {
+                        let code = match complex {
+                            true => "cvc-complex-type.2.2",
+                            false => "cvc-type.3.1.3",
+                        };
+                        self.report(code, error.message, frame.start, Some(position));
+                    }
+                }
+            }
+            Content::Nil if !frame.has_children && !frame.text.is_empty() => {
+                self.report(
+                    "cvc-elt.3.2.1",
+                    format!("element '{}' is nil and must be empty", frame.name),
+                    frame.start,
+                    Some(position),
+                );
+            }
+            Content::Complex {
+                content,
+                state,
+                failed: false,
+                ..
+            } if !content.empty && !content.automaton.is_accepting(&state) => {
+                self.report(
+                    "cvc-complex-type.2.4.b",
+                    format!(
+                        "content of element '{}' is incomplete{}",
+                        frame.name,
+                        expected(&content.automaton, &state)
+                    ),
+                    frame.start,
+                    Some(position),
+                );
+            }
+            _ => {}
+        }
+
+        let collapsed;
+        let text = match &value {
+            Some(v) => v.normalized.as_str(),
+            None => {
+                collapsed = WhiteSpace::Collapse.normalize(&frame.text);
+                collapsed.as_str()
+            }
+        };
+        for problem in self.identity.end(text) {
+            let (path, end) = match problem.path {
+                Some(path) => (path, problem.start),
+                None => (self.path.concat(), position),
+            };
+            self.report_at(problem.code, problem.message, path, problem.start, end);
+        }
+
+        if self.annotate {
+            let validity = match (self.reported > frame.reported, frame.declaration.is_some()) {
+                (true, _) => Validity::Invalid,
+                (false, true) => Validity::Valid,
+                (false, false) => Validity::NotKnown,
+            };
+            let span = self.span(frame.start, position);
+            let event = PsviEvent::EndElement {
+                validity,
+                value,
+                defaulted,
+                span,
+            };
+            match self.frames.is_empty() {
+                true => self.root_end = Some(event),
+                false => self.events.push(event),
+            }
+        }
+
+        self.path.pop();
+    }
+
+    fn text(&mut self, text: &str, whitespace: bool) {
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+
+        match &mut frame.content {
+            Content::Simple { .. } | Content::Nil => frame.text.push_str(text),
+            Content::Complex { content, .. } if content.mixed && frame.fixed.is_some() => {
+                frame.text.push_str(text)
+            }
+            Content::Complex {
+                content,
+                text_reported,
+                ..
+            } if !content.mixed && !*text_reported && !whitespace && !text.trim().is_empty() => {
+                *text_reported = true;
+                let code = match content.empty {
+                    true => "cvc-complex-type.2.1",
+                    false => "cvc-complex-type.2.3",
+                };
+                let (name, start) = (frame.name.clone(), frame.start);
+                self.report(
+                    code,
+                    format!("element '{}' must not contain character data", name),
+                    start,
+                    None,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn element(
+        &mut self,
+        term: &ElementTerm<'a>,
+        attributes: &[(QName, &str)],
+        position: Position,
+    ) -> Content<'a> {
+        let mut definition = self.type_definition(term);
+        let mut r#type = match &definition {
+            TypeDefinition::Simple(SimpleDefinition::Named(name)) => Some(name.clone()),
+            TypeDefinition::Simple(SimpleDefinition::Anonymous(..))
+            | TypeDefinition::Complex(..) => term.r#type.clone(),
+            TypeDefinition::Any => Some(
+                term.r#type
+                    .clone()
+                    .unwrap_or_else(|| QName::new(Some(XSD_NAMESPACE), "anyType")),
+            ),
+        };
+
+        if let Some(xsi_type) = self.top().and_then(|f| f.xsi_type.take()) {
+            if let Some(name) = self.xsi_type(term, xsi_type, position) {
+                definition = self.named_type(&name);
+                r#type = Some(name);
+            }
+        }
+        if let TypeDefinition::Complex(_, t, _) = &definition {
+            if t.r#abstract {
+                self.report(
+                    "cvc-type.2",
+                    format!(
+                        "type '{}' is abstract, name a derived type with xsi:type",
+                        t.name
+                    ),
+                    position,
+                    None,
+                );
+            }
+        }
+        let nil = self.nil(term, attributes, position);
+
+        if let Some(frame) = self.top() {
+            frame.declaration = Some((term.schema, term.declaration));
+            frame.r#type = r#type;
+            frame.nil = nil;
+            if !nil {
+                frame.default = term
+                    .declaration
+                    .default
+                    .as_deref()
+                    .or(term.declaration.fixed.as_deref());
+                frame.fixed = term.declaration.fixed.as_deref();
+            }
+        }
+
+        match definition {
+            TypeDefinition::Complex(schema, t, scope) => {
+                self.attributes(
+                    &term.name.name,
+                    attributes,
+                    schema,
+                    t,
+                    scope.clone(),
+                    position,
+                );
+
+                if nil {
+                    return Content::Nil;
+                }
+                match self.simple_content_type(schema, t) {
+                    Some(simple) => Content::Simple {
+                        r#type: SimpleDefinition::Named(simple),
+                        complex: true,
+                    },
+                    None => {
+                        let content = self.content(schema, t, scope);
+                        Content::Complex {
+                            state: content.automaton.start(),
+                            content,
+                            failed: false,
+                            text_reported: false,
+                        }
+                    }
+                }
+            }
+            TypeDefinition::Simple(simple) => {
+                for (attribute, value) in attributes {
+                    if attribute.namespace.as_deref() != Some(XSI_NAMESPACE) {
+                        self.annotate_attribute(attribute, None, value, None, false);
+                        self.report(
+                            "cvc-type.3.1.1",
+                            format!(
+                                "attribute '{}' is not allowed, the type is simple",
+                                attribute
+                            ),
+                            position,
+                            None,
+                        );
+                    }
+                }
+
+                match nil {
+                    true => Content::Nil,
+                    false => Content::Simple {
+                        r#type: simple,
+                        complex: false,
+                    },
+                }
+            }
+            TypeDefinition::Any if nil => Content::Nil,
+            TypeDefinition::Any => Content::Lax,
+        }
+    }
+
+    /// The type `xsi:type` names instead of the declared type of `term`, if it is validly
+    /// derived from the declared type.
+    fn xsi_type(
+        &mut self,
+        term: &ElementTerm<'a>,
+        xsi_type: Option<QName>,
+        position: Position,
+    ) -> Option<QName> {
+        let validator = self.validator;
+        let Some(name) = xsi_type else {
+            self.report(
+                "cvc-elt.4.1",
+                "the prefix of xsi:type is not declared".to_string(),
+                position,
+                None,
+            );
+            return None;
+        };
+
+        if !validator.graph.contains(&name) {
+            self.report(
+                "cvc-elt.4.2",
+                format!("xsi:type '{}' is not a type definition", name),
+                position,
+                None,
+            );
+            return None;
+        }
+
+        // An anonymous type cannot be the base of another type.
+        let declared = match &term.r#type {
+            Some(t) => Some(t.clone()),
+            None if !term.declaration.has_anonymous_type() => {
+                Some(QName::new(Some(XSD_NAMESPACE), "anyType"))
+            }
+            None => None,
+        };
+        let blocked = term
+            .declaration
+            .block
+            .unwrap_or(validator.set.schemas[term.schema].block_default);
+
+        match declared {
+            Some(declared) if validator.graph.is_derived_from(&name, &declared, blocked) => {
+                Some(name)
+            }
+            _ => {
+                self.report(
+                    "cvc-elt.4.3",
+                    format!(
+                        "xsi:type '{}' is not validly derived from the type of element '{}'",
+                        name, term.name.name
+                    ),
+                    position,
+                    None,
+                );
+                None
+            }
+        }
+    }
+
+    /// Whether `xsi:nil` makes the element of `term` nil.
+    fn nil(
+        &mut self,
+        term: &ElementTerm<'a>,
+        attributes: &[(QName, &str)],
+        position: Position,
+    ) -> bool {
+        let Some((_, value)) = attributes
+            .iter()
+            .find(|(n, _)| n.namespace.as_deref() == Some(XSI_NAMESPACE) && n.name == "nil")
+        else {
+            return false;
+        };
+
+        let nil = match value.trim() {
+            "true" | "1" => true,
+            "false" | "0" => false,
+            _ => {
+                self.report(
+                    "cvc-attribute.3",
+                    format!("xsi:nil must be a boolean, not '{}'", value),
+                    position,
+                    None,
+                );
+                return false;
+            }
+        };
+
+        if !term.declaration.nillable {
+            self.report(
+                "cvc-elt.3.1",
+                format!("element '{}' is not nillable", term.name.name),
+                position,
+                None,
+            );
+            return false;
+        }
+
+        if nil && term.declaration.fixed.is_some() {
+            self.report(
+                "cvc-elt.3.2.2",
+                format!(
+                    "element '{}' has a fixed value and cannot be nil",
+                    term.name.name
+                ),
+                position,
+                None,
+            );
+        }
+
+        nil
+    }
+
+    fn type_definition(&self, term: &ElementTerm<'a>) -> TypeDefinition<'a> {
+        match &term.r#type {
+            Some(name) => self.named_type(name),
+            None => match (
+                term.declaration.complex_types.first(),
+                &term.declaration.simple_type,
+            ) {
+                (Some(t), _) => TypeDefinition::Complex(
+                    term.schema,
+                    t,
+                    LocalScope::AnonymousType(term.id.clone()),
+                ),
+                (None, Some(t)) => {
+                    TypeDefinition::Simple(SimpleDefinition::Anonymous(term.schema, t))
+                }
+                (None, None) => TypeDefinition::Any,
+            },
+        }
+    }
+
+    fn named_type(&self, name: &QName) -> TypeDefinition<'a> {
+        let set = self.validator.set;
+
+        if let Some(id) = set.lookup(name, |t| &t.complex_types) {
+            TypeDefinition::Complex(
+                id.schema,
+                &set.schemas[id.schema].complex_types[id.index],
+                LocalScope::Type(id),
+            )
+        } else if BuiltinType::lookup_qname(name).is_some()
+            || set.lookup(name, |t| &t.simple_types).is_some()
+        {
+            TypeDefinition::Simple(SimpleDefinition::Named(name.clone()))
+        } else {
+            // `xs:anyType`, or a type that does not exist, which SchemaSet::check reports.
+            TypeDefinition::Any
+        }
+    }
+
+    /// Check `value` against the simple type `t`.
+    fn simple_value(
+        &self,
+        t: &SimpleDefinition<'a>,
+        value: &str,
+    ) -> Result<SimpleValue, ValueError> {
+        let values = &self.validator.values;
+        match t {
+            SimpleDefinition::Named(name) => values.validate(name, value),
+            SimpleDefinition::Anonymous(schema, t) => values.validate_type(*schema, t, value),
+        }
+    }
+
+    /// The compiled element content of a complex type. `scope` identifies the type.
+    fn content(
+        &mut self,
+        schema: usize,
+        t: &'a ComplexType,
+        scope: LocalScope,
+    ) -> Rc<ElementContent<'a>> {
+        let validator = self.validator;
+
+        self.contents
+            .entry(scope.clone())
+            .or_insert_with(|| {
+                let set = validator.set;
+                let (model, _) = set.content_model_of(schema, t, scope);
+                let model = validator.groups.expand(set, model);
+
+                Rc::new(ElementContent {
+                    automaton: ContentAutomaton::new(set, &model),
+                    mixed: model.mixed,
+                    empty: model.particle.is_none(),
+                })
+            })
+            .clone()
+    }
+
+    /// The simple type the content of a complex type with simple content must match.
+    fn simple_content_type(&self, schema: usize, t: &'a ComplexType) -> Option<QName> {
+        let set = self.validator.set;
+        let mut current = (schema, t);
+        let mut visited = HashSet::new();
+
+        loop {
+            let (schema, t) = current;
+            if t.simple_content.is_empty() {
+                return None;
+            }
+
+            let base = t
+                .derivation()
+                .and_then(|(_, base)| set.schemas[schema].resolve_qname(base))?;
+            match set.lookup(&base, |t| &t.complex_types) {
+                // Circular derivations are reported by SchemaSet::check.
+                Some(id) if !visited.insert(id) => return None,
+                Some(id) => current = (id.schema, &set.schemas[id.schema].complex_types[id.index]),
+                None => return Some(base),
+            }
+        }
+    }
+
+    fn attributes(
+        &mut self,
+        element: &str,
+        attributes: &[(QName, &str)],
+        schema: usize,
+        t: &'a ComplexType,
+        scope: LocalScope,
+        position: Position,
+    ) {
+        let set = self.validator.set;
+        let attribute_uses = self
+            .attribute_uses
+            .entry(scope)
+            .or_insert_with(|| Rc::new(set.attribute_uses_of(schema, t).0))
+            .clone();
+        let uses = &attribute_uses.uses;
+
+        let mut seen = HashSet::new();
+        for (name, value) in attributes.iter() {
+            if name.namespace.as_deref() == Some(XSI_NAMESPACE) {
+                continue;
+            }
+
+            let global;
+            let r#use = match uses.iter().find(|u| u.name == *name) {
+                Some(r#use) => {
+                    seen.insert(name.clone());
+                    r#use
+                }
+                None => {
+                    let wildcard = attribute_uses
+                        .wildcard
+                        .as_ref()
+                        .filter(|w| w.namespaces.allows(name.namespace.as_deref()));
+                    let process_contents = wildcard.map(|w| w.process_contents);
+
+                    match (process_contents, set.global_attribute_use(name)) {
+                        (None, _) => {
+                            self.annotate_attribute(name, None, value, None, false);
+                            self.report(
+                                "cvc-complex-type.3.2.2",
+                                format!(
+                                    "attribute '{}' is not allowed in element '{}'",
+                                    name, element
+                                ),
+                                position,
+                                None,
+                            );
+                            continue;
+                        }
+                        (Some(ProcessContents::Skip), _) | (Some(ProcessContents::Lax), None) => {
+                            self.annotate_unknown_attribute(name, value);
+                            continue;
+                        }
+                        (Some(ProcessContents::Strict), None) => {
+                            self.annotate_attribute(name, None, value, None, false);
+                            self.report(
+                                "cvc-complex-type.3.2.2",
+                                format!("no declaration found for attribute '{}'", name),
+                                position,
+                                None,
+                            );
+                            continue;
+                        }
+                        (Some(_), Some(r#use)) => {
+                            global = r#use;
+                            &global
+                        }
+                    }
+                }
+            };
+
+            match self.attribute_value(r#use, value) {
+                Err(error) => {
+                    self.annotate_attribute(name, r#use.r#type.clone(), value, None, false);
+                    self.report("cvc-attribute.3", error.message, position, None);
+                }
+                Ok(ref v)
+                    if r#use.fixed.as_ref().is_some_and(|fixed| {
+                        !self
+                            .attribute_value(r#use, fixed)
+                            .is_ok_and(|fixed| fixed.equals(v))
+                    }) =>
+                {
+                    self.annotate_attribute(name, r#use.r#type.clone(), value, None, false);
+                    self.report(
+                        "cvc-au",
+                        format!(
+                            "attribute '{}' must have the fixed value '{}'",
+                            name,
+                            r#use.fixed.as_deref().unwrap_or_default()
+                        ),
+                        position,
+                        None,
+                    );
+                }
+                Ok(v) => {
+                    self.identifiers(&v, position);
+                    self.annotate_attribute(name, r#use.r#type.clone(), value, Some(v), false);
+                }
+            }
+        }
+
+        if self.records_attributes() {
+            for r#use in uses.iter().filter(|u| !seen.contains(&u.name)) {
+                let Some(default) = r#use.default.as_ref().or(r#use.fixed.as_ref()) else {
+                    continue;
+                };
+                let v = self.attribute_value(r#use, default).ok();
+                self.annotate_attribute(&r#use.name, r#use.r#type.clone(), default, v, true);
+            }
+        }
+
+        for r#use in uses
+            .iter()
+            .filter(|u| u.required && !seen.contains(&u.name))
+        {
+            self.report(
+                "cvc-complex-type.4",
+                format!(
+                    "attribute '{}' is required in element '{}'",
+                    r#use.name, element
+                ),
+                position,
+                None,
+            );
+        }
+    }
+
+    fn attribute_value(
+        &self,
+        r#use: &AttributeUse<'a>,
+        value: &str,
+    ) -> Result<SimpleValue, ValueError> {
+        let values = &self.validator.values;
+        match (&r#use.r#type, &r#use.declaration.simple_type) {
+            (Some(name), _) => values.validate(name, value),
+            (None, Some(t)) => values.validate_type(r#use.schema, t, value),
+            (None, None) => Ok(SimpleValue::any(value)),
+        }
+    }
+
+    /// Whether the attributes of the element being started are recorded, for the annotated
+    /// document or the identity constraints.
+    fn records_attributes(&self) -> bool {
+        self.annotate
+            || self.identity.is_active()
+            || self
+                .frames
+                .last()
+                .and_then(|f| f.declaration)
+                .is_some_and(|(_, e)| !e.identity_constraints.is_empty())
+    }
+
+    /// Record an attribute of the element being started that a wildcard allows without
+    /// validating it.
+    fn annotate_unknown_attribute(&mut self, name: &QName, lexical: &str) {
+        if !self.records_attributes() {
+            return;
+        }
+
+        let Some(frame) = self.top() else {
+            return;
+        };
+        frame.attributes.push(TypedAttribute {
+            name: name.clone(),
+            r#type: None,
+            validity: Validity::NotKnown,
+            lexical: lexical.to_string(),
+            value: None,
+            defaulted: false,
+        });
+    }
+
+    /// Record a validated attribute of the element being started, see
+    /// [`ValidationStream::records_attributes`]. `value` is `None` if the attribute is invalid.
+    fn annotate_attribute(
+        &mut self,
+        name: &QName,
+        r#type: Option<QName>,
+        lexical: &str,
+        value: Option<SimpleValue>,
+        defaulted: bool,
+    ) {
+        if !self.records_attributes() {
+            return;
+        }
+
+        let Some(frame) = self.top() else {
+            return;
+        };
+        frame.attributes.push(TypedAttribute {
+            name: name.clone(),
+            r#type,
+            validity: match value {
+                Some(_) => Validity::Valid,
+                None => Validity::Invalid,
+            },
+            lexical: lexical.to_string(),
+            value,
+            defaulted,
+        });
+    }
+
+    fn wildcard(
+        &mut self,
+        name: &QName,
+        wildcard: &Any,
+        attributes: &[(QName, &str)],
+        position: Position,
+    ) -> Content<'a> {
+        let process_contents = wildcard.process_contents.as_deref().unwrap_or("strict");
+        if process_contents == "skip" {
+            return Content::Skip;
+        }
+
+        match ElementTerm::global(self.validator.set, name) {
+            Some(term) => self.element(&term, attributes, position),
+            None if process_contents == "strict" => {
+                self.report(
+                    "cvc-complex-type.2.4.c",
+                    format!("no declaration found for element '{}'", name),
+                    position,
+                    None,
+                );
+                Content::Skip
+            }
+            None => Content::Lax,
+        }
+    }
+
+    /// Report a problem of the element at the current path. Problems found at the start tag
+    /// have no `end` yet, their span covers the start tag only.
+    fn report(&mut self, code: &str, message: String, start: Position, end: Option<Position>) {
+        let path = self.path.concat();
+        self.report_at(code, message, path, start, end.unwrap_or(start));
+    }
+
+    /// Report a problem of the element at `path`.
+    fn report_at(
+        &mut self,
+        code: &str,
+        message: String,
+        path: String,
+        start: Position,
+        end: Position,
+    ) {
+        self.reported += 1;
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            code: code.to_string(),
+            message,
+            path,
+            span: self.span(start, end),
+        });
+    }
+
+    fn span(&self, start: Position, end: Position) -> Option<SourceSpan> {
+        Some(SourceSpan {
+            document: self.document.clone(),
+            start,
+            end,
+        })
+    }
+}
+
+/// Resolve the qualified name `value` of an instance attribute like `xsi:type` with the
+/// namespaces in scope. `None` if the prefix is not declared.
+fn resolve_qname(namespace: &Namespace, value: &str) -> Option<QName> {
+    let value = value.trim();
+    let (prefix, local) = value.split_once(':').unwrap_or(("", value));
+
+    match namespace.get(prefix).filter(|uri| !uri.is_empty()) {
+        None if !prefix.is_empty() => None,
+        uri => Some(QName::new(uri, local)),
+    }
+}
+
+/// `, expected a, b` for the elements `automaton` allows next, or nothing.
+fn expected(automaton: &ContentAutomaton, state: &ContentState) -> String {
+    let names: BTreeSet<String> = automaton
+        .expected_next(state)
+        .into_iter()
+        .map(|term| match term {
+            Term::Element(e) => e.name.to_string(),
+            _ => "any element".to_string(),
+        })
+        .collect();
+
+    match names.is_empty() {
+        true => String::new(),
+        false => format!(
+            ", expected {}",
+            names.into_iter().collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{MemoryResolver, Schema};
+
+    const XSD: &str = r###"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="yes-no">
+        <xs:restriction base="xs:token">
+            <xs:enumeration value="yes"/>
+            <xs:enumeration value="no"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="midi-16">
+        <xs:restriction base="xs:positiveInteger">
+            <xs:minInclusive value="1"/>
+            <xs:maxInclusive value="16"/>
+        </xs:restriction>
+    </xs:simpleType>
+    <xs:simpleType name="channels">
+        <xs:list itemType="midi-16"/>
+    </xs:simpleType>
+    <xs:simpleType name="channel-or-yes-no">
+        <xs:union memberTypes="midi-16 yes-no"/>
+    </xs:simpleType>
+
+    <xs:complexType name="text">
+        <xs:simpleContent>
+            <xs:extension base="xs:string">
+                <xs:attribute name="lang" type="xs:language"/>
+            </xs:extension>
+        </xs:simpleContent>
+    </xs:complexType>
+
+    <xs:complexType name="track">
+        <xs:sequence>
+            <xs:element name="name" type="text"/>
+            <xs:choice minOccurs="0" maxOccurs="unbounded">
+                <xs:element name="channel" type="midi-16"/>
+                <xs:element name="channels" type="channels"/>
+            </xs:choice>
+            <xs:element name="settings" minOccurs="0">
+                <xs:complexType>
+                    <xs:all>
+                        <xs:element name="solo" type="yes-no"/>
+                        <xs:element name="mute" type="yes-no" minOccurs="0"/>
+                    </xs:all>
+                </xs:complexType>
+            </xs:element>
+            <xs:any namespace="##other" processContents="lax" minOccurs="0" maxOccurs="unbounded"/>
+        </xs:sequence>
+        <xs:attribute name="id" type="xs:ID" use="required"/>
+        <xs:attribute name="color" type="channel-or-yes-no"/>
+        <xs:attribute name="version" type="xs:token" fixed="1.0"/>
+    </xs:complexType>
+
+    <xs:element name="project">
+        <xs:complexType mixed="true">
+            <xs:sequence>
+                <xs:element ref="lane" maxOccurs="unbounded"/>
+            </xs:sequence>
+        </xs:complexType>
+    </xs:element>
+    <xs:element name="lane" abstract="true"/>
+    <xs:element name="track" type="track" substitutionGroup="lane"/>
+    <xs:element name="marker" substitutionGroup="lane">
+        <xs:complexType/>
+    </xs:element>
+</xs:schema>
+"###;
+
+    fn validate(xml: &str) -> Vec<(String, String)> {
+        let set = SchemaSet::new(vec![Schema::parse(XSD).unwrap()]);
+        set.validate(xml)
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.code, d.path))
+            .collect()
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|(code, path)| (code.to_string(), path.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn valid() {
+        let xml = r#"<project>
+    Some text is allowed here.
+    <track id="t1" color="yes" version=" 1.0 ">
+        <name lang="en">Piano</name>
+        <channel>1</channel>
+        <channels> 2 16 </channels>
+        <settings><mute>no</mute><solo>yes</solo></settings>
+        <x:extra xmlns:x="urn:x"><anything/></x:extra>
+    </track>
+    <marker/>
+    <track id="t2" color="3"><name/></track>
+</project>"#;
+
+        assert_eq!(validate(xml), vec![]);
+    }
+
+    #[test]
+    fn root() {
+        assert_eq!(validate("<lane/>"), pairs(&[("cvc-elt.2", "/lane")]));
+        assert_eq!(validate("<song/>"), pairs(&[("cvc-elt.1", "/song")]));
+        assert_eq!(validate(r#"<track id="a"><name/></track>"#), vec![]);
+    }
+
+    #[test]
+    fn attributes() {
+        let xml = r#"<project>
+    <track color="maybe" version="2.0" size="3"><name/></track>
+    <marker id="m"/>
+</project>"#;
+
+        assert_eq!(
+            validate(xml),
+            pairs(&[
+                ("cvc-attribute.3", "/project/track[1]"),
+                ("cvc-complex-type.3.2.2", "/project/track[1]"),
+                ("cvc-au", "/project/track[1]"),
+                ("cvc-complex-type.4", "/project/track[1]"),
+                ("cvc-complex-type.3.2.2", "/project/marker[1]"),
+            ])
+        );
+    }
+
+    #[test]
+    fn attribute_wildcards() {
+        let xsd = r###"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:attribute name="size" type="xs:int"/>
+    <xs:element name="r">
+        <xs:complexType>
+            <xs:sequence>
+                <xs:element name="strict" minOccurs="0">
+                    <xs:complexType>
+                        <xs:anyAttribute namespace="##local"/>
+                    </xs:complexType>
+                </xs:element>
+            </xs:sequence>
+            <xs:anyAttribute processContents="lax"/>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>"###;
+        let set = SchemaSet::new(vec![Schema::parse(xsd).unwrap()]);
+        let validate = |xml: &str| -> Vec<(String, String)> {
+            set.validate(xml)
+                .unwrap()
+                .into_iter()
+                .map(|d| (d.code, d.path))
+                .collect()
+        };
+
+        assert_eq!(validate(r#"<r foo="1"/>"#), vec![]);
+        assert_eq!(
+            validate(
+                r#"<r foo="1" size="x" xmlns:u="urn:u" u:a="2"><strict size="3" bar="4" u:b="5"/></r>"#
+            ),
+            pairs(&[
+                ("cvc-attribute.3", "/r"),
+                ("cvc-complex-type.3.2.2", "/r/strict[1]"),
+                ("cvc-complex-type.3.2.2", "/r/strict[1]"),
+            ])
+        );
+    }
+
+    #[test]
+    fn fixed_values() {
+        let xsd = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:element name="r">
+        <xs:complexType>
+            <xs:sequence>
+                <xs:element name="code" type="xs:string" fixed="abc" minOccurs="0" maxOccurs="unbounded"/>
+                <xs:element name="count" type="xs:decimal" fixed="1.0" minOccurs="0" maxOccurs="unbounded"/>
+                <xs:element name="note" fixed="text" minOccurs="0">
+                    <xs:complexType mixed="true">
+                        <xs:sequence>
+                            <xs:element name="b" minOccurs="0"/>
+                        </xs:sequence>
+                    </xs:complexType>
+                </xs:element>
+            </xs:sequence>
+            <xs:attribute name="scale" type="xs:decimal" fixed="2"/>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>"#;
+        let set = SchemaSet::new(vec![Schema::parse(xsd).unwrap()]);
+        let validate = |xml: &str| -> Vec<(String, String)> {
+            set.validate(xml)
+                .unwrap()
+                .into_iter()
+                .map(|d| (d.code, d.path))
+                .collect()
+        };
+
+        assert_eq!(
+            validate(
+                r#"<r scale="2.00"><code>abc</code><code/><code>xyz</code><count>01</count><count>2</count><note>text</note></r>"#
+            ),
+            pairs(&[
+                ("cvc-elt.5.2.2.2.2", "/r/code[3]"),
+                ("cvc-elt.5.2.2.2.2", "/r/count[2]"),
+            ])
+        );
+        assert_eq!(
+            validate(r#"<r scale="3"><note>te<b/>xt</note></r>"#),
+            pairs(&[("cvc-au", "/r"), ("cvc-elt.5.2.2.1", "/r/note[1]")])
+        );
+        assert_eq!(
+            validate(r#"<r><note>other</note></r>"#),
+            pairs(&[("cvc-elt.5.2.2.2.1", "/r/note[1]")])
+        );
+    }
+
+    #[test]
+    fn content() {
+        let xml = r#"<project>
+    <track id="a"><channel>1</channel></track>
+    <track id="b"><name/>text<settings><mute>no</mute></settings></track>
+    <track id="c"><name/><settings><solo>yes</solo><solo>no</solo></settings></track>
+    <track id="d"><name/><name/></track>
+    <marker>text<child/></marker>
+</project>"#;
+
+        let r = Schema::parse(XSD).map(|s| SchemaSet::new(vec![s])).unwrap();
+        let diagnostics = r.validate(xml).unwrap();
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+
+        assert_eq!(
+            diagnostics
+                .into_iter()
+                .map(|d| (d.code, d.path))
+                .collect::<Vec<_>>(),
+            pairs(&[
+                ("cvc-complex-type.2.4.a", "/project/track[1]/channel[1]"),
+                ("cvc-complex-type.2.3", "/project/track[2]"),
+                ("cvc-complex-type.2.4.b", "/project/track[2]/settings[1]"),
+                (
+                    "cvc-complex-type.2.4.a",
+                    "/project/track[3]/settings[1]/solo[2]"
+                ),
+                ("cvc-complex-type.2.4.a", "/project/track[4]/name[2]"),
+                ("cvc-complex-type.2.1", "/project/marker[1]"),
+                ("cvc-complex-type.2.1", "/project/marker[1]"),
+            ]),
+            "{:#?}",
+            messages
+        );
+        assert!(messages[0].contains("element 'channel' is not expected here, expected name"));
+    }
+
+    #[test]
+    fn values() {
+        let xml = r#"<project>
+    <track id="a">
+        <name lang="en"><b/></name>
+        <channel>17</channel>
+        <channels>1 two</channels>
+        <channel>x</channel>
+    </track>
+</project>"#;
+
+        assert_eq!(
+            validate(xml),
+            pairs(&[
+                ("cvc-complex-type.2.2", "/project/track[1]/name[1]"),
+                ("cvc-type.3.1.3", "/project/track[1]/channel[1]"),
+                ("cvc-type.3.1.3", "/project/track[1]/channels[1]"),
+                ("cvc-type.3.1.3", "/project/track[1]/channel[2]"),
+            ])
+        );
+    }
+
+    #[test]
+    fn stream() {
+        let set = SchemaSet::new(vec![Schema::parse(XSD).unwrap()]);
+        let validator = Validator::new(&set);
+
+        // Diagnostics are reported while the document is read, before it turns out to be broken.
+        let mut xml = String::from("<project>");
+        for i in 0..1000 {
+            let channel = if i == 500 { 17 } else { 1 };
+            xml.push_str(&format!(
+                r#"<track id="t{}"><name/><channel>{}</channel></track>"#,
+                i, channel
+            ));
+        }
+        xml.push_str("<track>");
+
+        let mut reported = Vec::new();
+        let result = validator.validate_stream(xml.as_bytes(), Some("song.xml"), |d| {
+            reported.push((d.code, d.path, d.span.unwrap()))
+        });
+        assert!(result.is_err());
+
+        let (code, path, span) = &reported[0];
+        assert_eq!(code, "cvc-type.3.1.3");
+        assert_eq!(path, "/project/track[501]/channel[1]");
+        assert_eq!(span.document.as_deref(), Some("song.xml"));
+        assert!(span.end.column > span.start.column);
+        assert_eq!(reported[1].0, "cvc-complex-type.4");
+        assert_eq!(reported.len(), 2);
+
+        // Events can also be fed one by one.
+        let mut stream = validator.stream(None);
+        let position = Position { line: 1, column: 1 };
+        let reader = EventReader::new("<marker>text</marker>".as_bytes());
+        for event in reader {
+            stream.event(&event.unwrap(), position);
+        }
+        let diagnostics = stream.take_diagnostics();
+        assert_eq!(diagnostics[0].code, "cvc-complex-type.2.1");
+        assert_eq!(diagnostics[0].path, "/marker");
+
+        // A stray end tag is reported instead of panicking.
+        let mut stream = validator.stream(None);
+        let name = xml::name::OwnedName::local("marker");
+        stream.event(&XmlEvent::EndElement { name }, position);
+        let diagnostics = stream.take_diagnostics();
+        assert_eq!(diagnostics[0].code, "unbalanced-end-element");
+    }
+
+    #[test]
+    fn xsi_type_and_nil() {
+        let xsd = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns="urn:daw" targetNamespace="urn:daw">
+    <xs:complexType name="lane">
+        <xs:attribute name="id" type="xs:token"/>
+    </xs:complexType>
+    <xs:complexType name="track">
+        <xs:complexContent>
+            <xs:extension base="lane">
+                <xs:attribute name="channels" type="xs:positiveInteger" use="required"/>
+            </xs:extension>
+        </xs:complexContent>
+    </xs:complexType>
+    <xs:complexType name="abstract-lane" abstract="true">
+        <xs:complexContent>
+            <xs:extension base="lane"/>
+        </xs:complexContent>
+    </xs:complexType>
+    <xs:complexType name="project">
+        <xs:sequence>
+            <xs:element name="lane" type="lane" maxOccurs="unbounded"/>
+            <xs:element name="fixed-lane" type="lane" block="extension" minOccurs="0"/>
+            <xs:element name="tempo" type="xs:decimal" nillable="true" minOccurs="0"/>
+            <xs:element name="meter" type="xs:decimal" minOccurs="0"/>
+            <xs:element name="gain" minOccurs="0" maxOccurs="unbounded">
+                <xs:simpleType>
+                    <xs:restriction base="xs:decimal">
+                        <xs:maxInclusive value="1"/>
+                    </xs:restriction>
+                </xs:simpleType>
+            </xs:element>
+        </xs:sequence>
+    </xs:complexType>
+    <xs:element name="project" type="project"/>
+</xs:schema>
+"#;
+        let set = SchemaSet::new(vec![Schema::parse(xsd).unwrap()]);
+        let xml = r#"<d:project xmlns:d="urn:daw" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+    <lane xsi:type="d:track" channels="2"/>
+    <lane xsi:type="d:track"/>
+    <lane xsi:type="xs:string"/>
+    <lane xsi:type="d:missing"/>
+    <lane xsi:type="x:track"/>
+    <lane xsi:type="d:abstract-lane"/>
+    <fixed-lane xsi:type="d:track" channels="2"/>
+    <tempo xsi:nil="true"/>
+    <meter xsi:nil="true"/>
+    <gain>0.5</gain>
+    <gain>2</gain>
+    <gain xmlns:xs="http://www.w3.org/2001/XMLSchema" xsi:type="xs:decimal">0.5</gain>
+</d:project>"#;
+
+        let codes: Vec<(String, String)> = set
+            .validate(xml)
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.code, d.path))
+            .collect();
+        assert_eq!(
+            codes,
+            pairs(&[
+                ("cvc-complex-type.4", "/project/lane[2]"),
+                ("cvc-elt.4.1", "/project/lane[3]"),
+                ("cvc-elt.4.2", "/project/lane[4]"),
+                ("cvc-elt.4.1", "/project/lane[5]"),
+                ("cvc-type.2", "/project/lane[6]"),
+                ("cvc-elt.4.3", "/project/fixed-lane[1]"),
+                ("cvc-complex-type.3.2.2", "/project/fixed-lane[1]"),
+                ("cvc-elt.3.1", "/project/meter[1]"),
+                ("cvc-type.3.1.3", "/project/meter[1]"),
+                ("cvc-type.3.1.3", "/project/gain[2]"),
+                ("cvc-elt.4.3", "/project/gain[3]"),
+            ])
+        );
+
+        let nil = r#"<d:project xmlns:d="urn:daw" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+    <lane/><tempo xsi:nil="1">120</tempo>
+</d:project>"#;
+        let codes: Vec<String> = set
+            .validate(nil)
+            .unwrap()
+            .into_iter()
+            .map(|d| d.code)
+            .collect();
+        assert_eq!(codes, vec!["cvc-elt.3.2.1"]);
+
+        let (root, _) = set
+            .annotate(&xml.replace("<meter xsi:nil=\"true\"/>", ""))
+            .unwrap();
+        assert_eq!(
+            root.children[0].r#type,
+            Some(QName::new(Some("urn:daw"), "track"))
+        );
+        assert!(root.children[7].nil);
+    }
+
+    #[test]
+    fn ids() {
+        let xsd = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+    <xs:simpleType name="track-id">
+        <xs:restriction base="xs:ID"/>
+    </xs:simpleType>
+    <xs:element name="project">
+        <xs:complexType>
+            <xs:sequence>
+                <xs:element name="track" maxOccurs="unbounded">
+                    <xs:complexType>
+                        <xs:attribute name="id" type="track-id"/>
+                        <xs:attribute name="send" type="xs:IDREF"/>
+                    </xs:complexType>
+                </xs:element>
+                <xs:element name="group" type="xs:IDREFS" maxOccurs="unbounded"/>
+            </xs:sequence>
+        </xs:complexType>
+    </xs:element>
+</xs:schema>
+"#;
+        let set = SchemaSet::new(vec![Schema::parse(xsd).unwrap()]);
+
+        // References may point forward.
+        let xml = r#"<project>
+    <track id="bass" send="bus"/>
+    <track id="bus"/>
+    <group>bass bus</group>
+</project>"#;
+        assert_eq!(set.validate(xml).unwrap(), vec![]);
+
+        let xml = r#"<project>
+    <track id="bass" send="reverb"/>
+    <track id="bass"/>
+    <group>bass
+        lead drums</group>
+</project>"#;
+        let diagnostics = set.validate(xml).unwrap();
+        let codes: Vec<(String, String, u64)> = diagnostics
+            .into_iter()
+            .map(|d| (d.code, d.path, d.span.unwrap().start.line))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                ("cvc-id.2".to_string(), "/project/track[2]".to_string(), 3),
+                ("cvc-id.1".to_string(), "/project/track[1]".to_string(), 2),
+                ("cvc-id.1".to_string(), "/project/group[1]".to_string(), 4),
+                ("cvc-id.1".to_string(), "/project/group[1]".to_string(), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn schema_location_hints() {
+        let mut resolver = MemoryResolver::new();
+        resolver.insert("schemas/project.xsd", XSD);
+        resolver.insert(
+            "schemas/plugin.xsd",
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:plugin"/>"#,
+        );
+
+        // Hints may appear on any element.
+        let xml = r#"<project xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+    xsi:noNamespaceSchemaLocation="../schemas/project.xsd"><marker
+    xsi:schemaLocation="urn:plugin ../schemas/plugin.xsd"/></project>"#;
+
+        let set = SchemaSet::load_for_instance(xml, Some("songs/song.xml"), &resolver).unwrap();
+        assert_eq!(set.schemas.len(), 2);
+        assert_eq!(set.validate(xml).unwrap(), vec![]);
+
+        let set = SchemaSet::load_for_instance(xml, Some("song.xml"), &resolver).unwrap();
+        assert_eq!(set.schemas.len(), 0);
+        assert_eq!(set.diagnostics[0].code, "schema-not-loaded");
+    }
+}
@@ -0,0 +1,1386 @@
+use std::{cell::RefCell, cmp::Ordering, collections::HashMap, fmt::Display};
+
+use crate::{
+    builtins::{BuiltinType, WhiteSpace},
+    pattern::Pattern,
+    prelude::{BoundKind, QName, RestrictionContent, SimpleType},
+    schema_set::SchemaSet,
+};
+
+/// How deep simple types may be nested before a value is accepted without further checks.
+/// Circular type definitions are reported by the derivation graph.
+const MAX_DEPTH: usize = 64;
+
+/// A value that is valid for a simple type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimpleValue {
+    /// The value after whitespace normalization.
+    pub normalized: String,
+
+    /// The built-in type the value was checked against, e.g. `positiveInteger` for a
+    /// restriction of it. The item type for lists. `None` if only `anySimpleType` applies.
+    pub builtin: Option<&'static BuiltinType>,
+
+    /// The member type of a union that accepted the value, if it has a name.
+    pub member_type: Option<QName>,
+
+    /// True if the value is a list of items separated by single spaces.
+    pub list: bool,
+}
+
+impl SimpleValue {
+    pub(crate) fn any(value: &str) -> Self {
+        SimpleValue {
+            normalized: value.to_string(),
+            builtin: None,
+            member_type: None,
+            list: false,
+        }
+    }
+
+    /// The value as an integer, if its type is derived from `decimal` and it has no fraction
+    /// that fits into an `i64`.
+    pub fn as_integer(&self) -> Option<i64> {
+        match self.primitive()? {
+            "decimal" if !self.list => self.normalized.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// The value as a floating point number, if its type is numeric.
+    pub fn as_float(&self) -> Option<f64> {
+        match self.primitive()? {
+            "decimal" | "float" | "double" if !self.list => self.normalized.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// The value as a boolean, if its type is derived from `boolean`.
+    pub fn as_boolean(&self) -> Option<bool> {
+        match self.primitive()? {
+            "boolean" if !self.list => Some(matches!(self.normalized.as_str(), "true" | "1")),
+            _ => None,
+        }
+    }
+
+    /// The items of a list, or the value itself.
+    pub fn items(&self) -> Vec<&str> {
+        match self.list {
+            true => self
+                .normalized
+                .split(' ')
+                .filter(|i| !i.is_empty())
+                .collect(),
+            false => vec![self.normalized.as_str()],
+        }
+    }
+
+    /// Whether two values are equal in the value space, e.g. `1.0` and `01` as decimals or
+    /// `true` and `1` as booleans. Values of different primitive types are never equal.
+    pub fn equals(&self, other: &SimpleValue) -> bool {
+        if self.list != other.list || self.primitive() != other.primitive() {
+            return false;
+        }
+
+        let (items, other_items) = (self.items(), other.items());
+        let primitive = self.primitive();
+        items.len() == other_items.len()
+            && items
+                .iter()
+                .zip(other_items)
+                .all(|(a, b)| equal_items(primitive, a, b))
+    }
+
+    fn primitive(&self) -> Option<&'static str> {
+        self.builtin.and_then(|b| b.primitive()).map(|p| p.name)
+    }
+}
+
+/// Why a value is not valid for a simple type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueError {
+    /// The violated validation rule of XML Schema Part 2, e.g. `cvc-pattern-valid`.
+    pub code: String,
+    pub message: String,
+}
+
+impl ValueError {
+    fn new(code: &str, message: String) -> Self {
+        ValueError {
+            code: code.to_string(),
+            message,
+        }
+    }
+}
+
+impl Display for ValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+impl SchemaSet {
+    /// Check `value` against the simple type `name`, see [`ValueValidator::validate`].
+    pub fn validate_value(&self, name: &QName, value: &str) -> Result<SimpleValue, ValueError> {
+        ValueValidator::new(self).validate(name, value)
+    }
+}
+
+/// Checks values against the simple types of a [`SchemaSet`] and the built-in types: whitespace
+/// normalization, the lexical space of the built-in type, and the facets of every restriction
+/// step. Compiled patterns are cached, so a validator should be reused for many values.
+pub struct ValueValidator<'a> {
+    set: &'a SchemaSet,
+
+    /// Compiled `pattern` facets. `None` for invalid patterns, which [`SchemaSet::check`]
+    /// reports.
+    patterns: RefCell<HashMap<String, Option<Pattern>>>,
+}
+
+impl<'a> ValueValidator<'a> {
+    pub fn new(set: &'a SchemaSet) -> Self {
+        ValueValidator {
+            set,
+            patterns: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Check `value` against the simple type `name`, built in or defined in the set. A name that
+    /// cannot be resolved is treated as `anySimpleType`, which accepts every value.
+    pub fn validate(&self, name: &QName, value: &str) -> Result<SimpleValue, ValueError> {
+        self.named(name, value, 0)
+    }
+
+    /// Check `value` against `t`, a named or anonymous simple type defined in the schema
+    /// document with the index `schema`.
+    pub fn validate_type(
+        &self,
+        schema: usize,
+        t: &SimpleType,
+        value: &str,
+    ) -> Result<SimpleValue, ValueError> {
+        self.simple_type(schema, t, value, 0)
+    }
+
+    fn named(&self, name: &QName, value: &str, depth: usize) -> Result<SimpleValue, ValueError> {
+        if let Some(builtin) = BuiltinType::lookup_qname(name) {
+            return self.builtin(builtin, value);
+        }
+
+        match self.set.lookup(name, |t| &t.simple_types) {
+            Some(id) => self.simple_type(
+                id.schema,
+                &self.set.schemas[id.schema].simple_types[id.index],
+                value,
+                depth + 1,
+            ),
+            None => Ok(SimpleValue::any(value)),
+        }
+    }
+
+    fn simple_type(
+        &self,
+        schema: usize,
+        t: &SimpleType,
+        value: &str,
+        depth: usize,
+    ) -> Result<SimpleValue, ValueError> {
+        if depth > MAX_DEPTH {
+            return Ok(SimpleValue::any(value));
+        }
+        let document = &self.set.schemas[schema];
+
+        if let Some(restriction) = &t.restriction {
+            let mut v = match document.resolve_qname(&restriction.base) {
+                Some(base) => self.named(&base, value, depth + 1)?,
+                None => SimpleValue::any(value),
+            };
+
+            for facet in restriction.content.iter() {
+                if let RestrictionContent::WhiteSpace(white_space) = facet {
+                    v.normalized = white_space.normalize(&v.normalized);
+                }
+            }
+            self.facets(&restriction.content, &v)?;
+
+            return Ok(v);
+        }
+
+        if let Some(list) = &t.list {
+            let normalized = WhiteSpace::Collapse.normalize(value);
+            let mut builtin = None;
+
+            for item in normalized.split(' ').filter(|i| !i.is_empty()) {
+                let item = match (&list.item_type, &list.simple_type) {
+                    (Some(name), _) => match document.resolve_qname(name) {
+                        Some(name) => self.named(&name, item, depth + 1)?,
+                        None => SimpleValue::any(item),
+                    },
+                    (None, Some(t)) => self.simple_type(schema, t, item, depth + 1)?,
+                    (None, None) => SimpleValue::any(item),
+                };
+                builtin = item.builtin;
+            }
+
+            return Ok(SimpleValue {
+                normalized,
+                builtin,
+                member_type: None,
+                list: true,
+            });
+        }
+
+        if let Some(union) = &t.union {
+            let named = union
+                .types
+                .iter()
+                .filter(|t| !t.is_empty())
+                .filter_map(|t| document.resolve_qname(t));
+            for member in named {
+                if let Ok(mut v) = self.named(&member, value, depth + 1) {
+                    v.member_type = Some(member);
+                    return Ok(v);
+                }
+            }
+
+            for member in union.simple_types.iter() {
+                if let Ok(mut v) = self.simple_type(schema, member, value, depth + 1) {
+                    v.member_type = None;
+                    return Ok(v);
+                }
+            }
+
+            return Err(ValueError::new(
+                "cvc-datatype-valid.1.2.3",
+                format!("'{}' is not valid for any member type of the union", value),
+            ));
+        }
+
+        Ok(SimpleValue::any(value))
+    }
+
+    fn builtin(
+        &self,
+        builtin: &'static BuiltinType,
+        value: &str,
+    ) -> Result<SimpleValue, ValueError> {
+        if let Some(item) = builtin.item_type() {
+            let normalized = WhiteSpace::Collapse.normalize(value);
+            let items: Vec<&str> = normalized.split(' ').filter(|i| !i.is_empty()).collect();
+            for i in items.iter() {
+                self.builtin(item, i)?;
+            }
+
+            let min = builtin.min_length.unwrap_or(0);
+            if (items.len() as u64) < min {
+                return Err(ValueError::new(
+                    "cvc-minLength-valid",
+                    format!(
+                        "a value of type '{}' needs at least {} item(s)",
+                        builtin.name, min
+                    ),
+                ));
+            }
+
+            return Ok(SimpleValue {
+                normalized,
+                builtin: Some(item),
+                member_type: None,
+                list: true,
+            });
+        }
+
+        let normalized = builtin.white_space.normalize(value);
+        if !is_lexically_valid(builtin, &normalized) {
+            return Err(ValueError::new(
+                "cvc-datatype-valid.1.2.1",
+                format!(
+                    "'{}' is not a valid value of type '{}'",
+                    normalized, builtin.name
+                ),
+            ));
+        }
+
+        let bound = |f: fn(&BuiltinType) -> Option<&'static str>| {
+            std::iter::once(builtin)
+                .chain(builtin.ancestors())
+                .find_map(f)
+        };
+        if let Some(min) = bound(|t| t.min_inclusive) {
+            if compare_decimals(&normalized, min) == Some(Ordering::Less) {
+                return Err(ValueError::new(
+                    "cvc-minInclusive-valid",
+                    format!("{} is less than the minimum {}", normalized, min),
+                ));
+            }
+        }
+        if let Some(max) = bound(|t| t.max_inclusive) {
+            if compare_decimals(&normalized, max) == Some(Ordering::Greater) {
+                return Err(ValueError::new(
+                    "cvc-maxInclusive-valid",
+                    format!("{} is greater than the maximum {}", normalized, max),
+                ));
+            }
+        }
+
+        Ok(SimpleValue {
+            normalized,
+            builtin: Some(builtin),
+            member_type: None,
+            list: false,
+        })
+    }
+
+    /// Check the facets of one restriction step. The facets of the base types have already been
+    /// checked.
+    fn facets(&self, facets: &[RestrictionContent], v: &SimpleValue) -> Result<(), ValueError> {
+        let value = v.normalized.as_str();
+        let mut enumerations = Vec::new();
+        let mut patterns = Vec::new();
+
+        for facet in facets.iter() {
+            let (code, message) = match facet {
+                RestrictionContent::Enumeration(e) => {
+                    enumerations.push(e.as_str());
+                    continue;
+                }
+                RestrictionContent::Pattern(p) => {
+                    patterns.push(p.as_str());
+                    continue;
+                }
+                RestrictionContent::WhiteSpace(_) => continue,
+                RestrictionContent::MinInclusive(_)
+                | RestrictionContent::MaxInclusive(_)
+                | RestrictionContent::MinExclusive(_)
+                | RestrictionContent::MaxExclusive(_)
+                | RestrictionContent::Bound(..) => {
+                    match facet.bound().and_then(|(kind, b)| bound_error(kind, &b, v)) {
+                        Some(error) => error,
+                        None => continue,
+                    }
+                }
+                RestrictionContent::Length(n) if length(v) != *n => (
+                    "cvc-length-valid",
+                    format!("'{}' has length {}, expected {}", value, length(v), n),
+                ),
+                RestrictionContent::MinLength(n) if length(v) < *n => (
+                    "cvc-minLength-valid",
+                    format!("'{}' is shorter than the minimum length {}", value, n),
+                ),
+                RestrictionContent::MaxLength(n) if length(v) > *n => (
+                    "cvc-maxLength-valid",
+                    format!("'{}' is longer than the maximum length {}", value, n),
+                ),
+                RestrictionContent::TotalDigits(n) => match digits(v) {
+                    Some((total, _)) if total > *n as usize => (
+                        "cvc-totalDigits-valid",
+                        format!("{} has more than {} digits", value, n),
+                    ),
+                    _ => continue,
+                },
+                RestrictionContent::FractionDigits(n) => match digits(v) {
+                    Some((_, fraction)) if fraction > *n as usize => (
+                        "cvc-fractionDigits-valid",
+                        format!("{} has more than {} fraction digits", value, n),
+                    ),
+                    _ => continue,
+                },
+                RestrictionContent::Length(_)
+                | RestrictionContent::MinLength(_)
+                | RestrictionContent::MaxLength(_) => continue,
+            };
+
+            return Err(ValueError::new(code, message));
+        }
+
+        if !enumerations.is_empty() && !enumerations.iter().any(|e| equal(v, e)) {
+            return Err(ValueError::new(
+                "cvc-enumeration-valid",
+                format!("'{}' is not one of {}", value, enumerations.join(", ")),
+            ));
+        }
+
+        if !patterns.is_empty() && !patterns.iter().any(|p| self.matches(p, value)) {
+            return Err(ValueError::new(
+                "cvc-pattern-valid",
+                format!(
+                    "'{}' does not match the pattern '{}'",
+                    value,
+                    patterns.join("|")
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// True if `value` matches `pattern` as a whole. Patterns that cannot be compiled match
+    /// everything.
+    fn matches(&self, pattern: &str, value: &str) -> bool {
+        let mut patterns = self.patterns.borrow_mut();
+        let compiled = patterns
+            .entry(pattern.to_string())
+            .or_insert_with(|| Pattern::new(pattern).ok());
+
+        compiled.as_ref().is_none_or(|p| p.is_match(value))
+    }
+}
+
+/// The name of the primitive type of a value, if it is not a list.
+fn primitive(v: &SimpleValue) -> Option<&'static str> {
+    match v.list {
+        true => None,
+        false => v.builtin.and_then(|b| b.primitive()).map(|p| p.name),
+    }
+}
+
+/// Compare a numeric, date, time or duration value with a bound in the value space of its type.
+/// `None` if the type is not ordered or the bound is not a valid value, `Some(None)` if the two
+/// are incomparable, e.g. `NaN` with any number or `P1M` with `P30D`.
+fn compare(v: &SimpleValue, bound: &str) -> Option<Option<Ordering>> {
+    match primitive(v)? {
+        "decimal" => compare_decimals(&v.normalized, bound).map(Some),
+        "float" | "double" => Some(parse_float(&v.normalized)?.partial_cmp(&parse_float(bound)?)),
+        "duration" => compare_durations(&v.normalized, bound),
+        primitive => compare_instants(primitive, &v.normalized, bound),
+    }
+}
+
+/// Compare two values of the built-in type `builtin`, e.g. two bounds, see [`compare`]. `None`
+/// if they are not ordered or incomparable.
+pub(crate) fn compare_values(builtin: &'static BuiltinType, a: &str, b: &str) -> Option<Ordering> {
+    compare(
+        &SimpleValue {
+            normalized: a.to_string(),
+            builtin: Some(builtin),
+            member_type: None,
+            list: false,
+        },
+        b,
+    )
+    .flatten()
+}
+
+/// The code and message if `v` is out of the bound facet `kind` with the value `bound`. Values
+/// that are incomparable with the bound are out of it.
+fn bound_error(kind: BoundKind, bound: &str, v: &SimpleValue) -> Option<(&'static str, String)> {
+    let value = v.normalized.as_str();
+    let Some(ordering) = compare(v, bound)? else {
+        let code = match kind {
+            BoundKind::MinInclusive => "cvc-minInclusive-valid",
+            BoundKind::MaxInclusive => "cvc-maxInclusive-valid",
+            BoundKind::MinExclusive => "cvc-minExclusive-valid",
+            BoundKind::MaxExclusive => "cvc-maxExclusive-valid",
+        };
+        return Some((code, format!("{} cannot be compared with {}", value, bound)));
+    };
+
+    match kind {
+        BoundKind::MinInclusive if ordering == Ordering::Less => Some((
+            "cvc-minInclusive-valid",
+            format!("{} is less than the minimum {}", value, bound),
+        )),
+        BoundKind::MaxInclusive if ordering == Ordering::Greater => Some((
+            "cvc-maxInclusive-valid",
+            format!("{} is greater than the maximum {}", value, bound),
+        )),
+        BoundKind::MinExclusive if ordering != Ordering::Greater => Some((
+            "cvc-minExclusive-valid",
+            format!("{} must be greater than {}", value, bound),
+        )),
+        BoundKind::MaxExclusive if ordering != Ordering::Less => Some((
+            "cvc-maxExclusive-valid",
+            format!("{} must be less than {}", value, bound),
+        )),
+        _ => None,
+    }
+}
+
+/// True if `v` equals the enumeration value `option`.
+fn equal(v: &SimpleValue, option: &str) -> bool {
+    if v.list {
+        return WhiteSpace::Collapse.normalize(option) == v.normalized;
+    }
+
+    match primitive(v) {
+        Some("decimal" | "float" | "double") => {
+            compare(v, option.trim()) == Some(Some(Ordering::Equal))
+        }
+        _ => {
+            let white_space = v.builtin.map_or(WhiteSpace::Preserve, |b| b.white_space);
+            white_space.normalize(option) == v.normalized
+        }
+    }
+}
+
+/// True if two items of the primitive type `primitive` are equal in its value space.
+fn equal_items(primitive: Option<&str>, a: &str, b: &str) -> bool {
+    match primitive {
+        Some("decimal") => compare_decimals(a, b) == Some(Ordering::Equal),
+        Some("float" | "double") => match (parse_float(a), parse_float(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => a == b,
+        },
+        Some("boolean") => matches!(a, "true" | "1") == matches!(b, "true" | "1"),
+        _ => a == b,
+    }
+}
+
+/// The length of a value as the length facets count it: items of lists, octets of binary data
+/// and characters of everything else.
+fn length(v: &SimpleValue) -> u64 {
+    if v.list {
+        return v.normalized.split(' ').filter(|i| !i.is_empty()).count() as u64;
+    }
+
+    match primitive(v) {
+        Some("hexBinary") => v.normalized.len() as u64 / 2,
+        Some("base64Binary") => {
+            let characters = v
+                .normalized
+                .chars()
+                .filter(|c| *c != ' ' && *c != '=')
+                .count();
+            characters as u64 * 3 / 4
+        }
+        _ => v.normalized.chars().count() as u64,
+    }
+}
+
+/// The total number of digits and the number of fraction digits of a decimal value, without
+/// leading and trailing zeros.
+fn digits(v: &SimpleValue) -> Option<(usize, usize)> {
+    if primitive(v) != Some("decimal") {
+        return None;
+    }
+
+    let (_, integer, fraction) = parse_decimal(&v.normalized)?;
+    Some(((integer.len() + fraction.len()).max(1), fraction.len()))
+}
+
+/// Split a decimal into sign, integer digits without leading zeros and fraction digits without
+/// trailing zeros. Zero is never negative.
+fn parse_decimal(value: &str) -> Option<(bool, &str, &str)> {
+    let (negative, unsigned) = match value.as_bytes().first() {
+        Some(b'-') => (true, &value[1..]),
+        Some(b'+') => (false, &value[1..]),
+        _ => (false, value),
+    };
+
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if !all_digits(integer) || !all_digits(fraction) || (integer.is_empty() && fraction.is_empty())
+    {
+        return None;
+    }
+
+    let integer = integer.trim_start_matches('0');
+    let fraction = fraction.trim_end_matches('0');
+    let negative = negative && !(integer.is_empty() && fraction.is_empty());
+    Some((negative, integer, fraction))
+}
+
+/// Compare two decimals of any size.
+fn compare_decimals(a: &str, b: &str) -> Option<Ordering> {
+    let (a_negative, a_integer, a_fraction) = parse_decimal(a)?;
+    let (b_negative, b_integer, b_fraction) = parse_decimal(b)?;
+
+    let magnitude = a_integer
+        .len()
+        .cmp(&b_integer.len())
+        .then_with(|| a_integer.cmp(b_integer))
+        .then_with(|| a_fraction.cmp(b_fraction));
+
+    Some(match (a_negative, b_negative) {
+        (false, false) => magnitude,
+        (true, true) => magnitude.reverse(),
+        (false, true) => Ordering::Greater,
+        (true, false) => Ordering::Less,
+    })
+}
+
+/// Compare two date or time values of the primitive type `primitive`. A value without timezone
+/// stands for every instant within 14 hours of its local time, so it is only ordered against a
+/// value with timezone if all of them are on the same side of it.
+fn compare_instants(primitive: &str, a: &str, b: &str) -> Option<Option<Ordering>> {
+    let (a_seconds, a_fraction, a_timezone) = instant(primitive, a)?;
+    let (b_seconds, b_fraction, b_timezone) = instant(primitive, b)?;
+    let order = |a: i128, b: i128| a.cmp(&b).then_with(|| a_fraction.cmp(b_fraction));
+
+    let spread = |timezone: bool| match timezone || a_timezone == b_timezone {
+        true => 0,
+        false => 14 * 3600,
+    };
+    let (a_spread, b_spread) = (spread(a_timezone), spread(b_timezone));
+
+    Some(
+        if order(a_seconds + a_spread, b_seconds - b_spread) == Ordering::Less {
+            Some(Ordering::Less)
+        } else if order(a_seconds - a_spread, b_seconds + b_spread) == Ordering::Greater {
+            Some(Ordering::Greater)
+        } else if a_spread + b_spread == 0 {
+            Some(Ordering::Equal)
+        } else {
+            None
+        },
+    )
+}
+
+/// Compare two durations by the dateTimes they reach from each of the four reference dateTimes
+/// of the specification. `Some(None)` if those are not in the same order for all four.
+fn compare_durations(a: &str, b: &str) -> Option<Option<Ordering>> {
+    let digits = fraction_digits(a).max(fraction_digits(b));
+    let (a_months, a_seconds) = duration(a, digits)?;
+    let (b_months, b_seconds) = duration(b, digits)?;
+    let day = 10i128.checked_pow(digits)?.checked_mul(86400)?;
+
+    // Seconds of `year-month-01T00:00:00Z` plus the duration, scaled like its seconds.
+    let reached = |year: i128, month: i128, months: i128, seconds: i128| -> Option<i128> {
+        let month = (year * 12 + month - 1).checked_add(months)?;
+        let days = days_from_civil(month.div_euclid(12), month.rem_euclid(12) + 1, 1)?;
+        days.checked_mul(day)?.checked_add(seconds)
+    };
+
+    let mut r = None;
+    for (year, month) in [(1696, 9), (1697, 2), (1903, 3), (1903, 7)] {
+        let a = reached(year, month, a_months, a_seconds)?;
+        let b = reached(year, month, b_months, b_seconds)?;
+        if r.is_some_and(|r| r != a.cmp(&b)) {
+            return Some(None);
+        }
+        r = Some(a.cmp(&b));
+    }
+
+    Some(r)
+}
+
+/// The number of fraction digits of the seconds of a duration.
+fn fraction_digits(value: &str) -> u32 {
+    value.split_once('.').map_or(0, |(_, fraction)| {
+        fraction.trim_end_matches('S').len() as u32
+    })
+}
+
+/// A duration as months and seconds scaled by `10^digits`, both negative for negative durations.
+fn duration(value: &str, digits: u32) -> Option<(i128, i128)> {
+    if !is_duration(value) {
+        return None;
+    }
+
+    let scale = 10i128.checked_pow(digits)?;
+    let day = scale.checked_mul(86400)?;
+    let negative = value.starts_with('-');
+    let value = value.trim_start_matches('-').strip_prefix('P')?;
+    let (date, time) = value.split_once('T').unwrap_or((value, ""));
+
+    let (mut months, mut seconds) = (0i128, 0i128);
+    for (part, is_time) in [(date, false), (time, true)] {
+        let mut rest = part;
+        while let Some(end) = rest.find(|c: char| c.is_ascii_alphabetic()) {
+            let (number, designator) = (&rest[..end], &rest[end..end + 1]);
+            rest = &rest[end + 1..];
+
+            let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+            let n: i128 = integer.parse().ok()?;
+            let (total, factor) = match (is_time, designator) {
+                (false, "Y") => (&mut months, 12),
+                (false, "M") => (&mut months, 1),
+                (false, "D") => (&mut seconds, day),
+                (true, "H") => (&mut seconds, 3600 * scale),
+                (true, "M") => (&mut seconds, 60 * scale),
+                (true, "S") => (&mut seconds, scale),
+                _ => return None,
+            };
+            *total = total.checked_add(n.checked_mul(factor)?)?;
+
+            if !fraction.is_empty() {
+                let fraction = format!("{:0<1$}", fraction, digits as usize);
+                seconds = seconds.checked_add(fraction.parse().ok()?)?;
+            }
+        }
+    }
+
+    match negative {
+        true => Some((-months, -seconds)),
+        false => Some((months, seconds)),
+    }
+}
+
+/// A date or time value as seconds on a proleptic Gregorian time line in UTC, the fraction
+/// digits of the seconds without trailing zeros and whether the value has a timezone. Missing
+/// parts are filled in like `2000-01-01T00:00:00`.
+fn instant<'s>(primitive: &str, value: &'s str) -> Option<(i128, &'s str, bool)> {
+    let mut c = Cursor { rest: value };
+    let expect = |c: &mut Cursor, ch: char| c.eat(ch).then_some(());
+    let (mut year, mut month, mut day) = (2000, 1, 1);
+    let (mut hours, mut minutes, mut seconds, mut fraction) = (0, 0, 0, "");
+
+    match primitive {
+        "dateTime" | "date" | "gYearMonth" | "gYear" => {
+            year = c.year()?;
+            if primitive != "gYear" {
+                expect(&mut c, '-')?;
+                month = c.month()?;
+            }
+            if primitive != "gYear" && primitive != "gYearMonth" {
+                expect(&mut c, '-')?;
+                day = c.number(2)?;
+            }
+        }
+        "gMonthDay" | "gMonth" => {
+            (c.eat('-') && c.eat('-')).then_some(())?;
+            month = c.month()?;
+            if primitive == "gMonthDay" {
+                expect(&mut c, '-')?;
+                day = c.number(2)?;
+            }
+        }
+        "gDay" => {
+            (c.eat('-') && c.eat('-') && c.eat('-')).then_some(())?;
+            day = c.number(2)?;
+        }
+        "time" => {}
+        _ => return None,
+    }
+
+    if primitive == "dateTime" || primitive == "time" {
+        if primitive == "dateTime" {
+            expect(&mut c, 'T')?;
+        }
+        hours = c.number(2)?;
+        expect(&mut c, ':')?;
+        minutes = c.number(2)?;
+        expect(&mut c, ':')?;
+        seconds = c.number(2)?;
+        if c.eat('.') {
+            let end = c
+                .rest
+                .find(|ch: char| !ch.is_ascii_digit())
+                .unwrap_or(c.rest.len());
+            fraction = c.rest[..end].trim_end_matches('0');
+            c.rest = &c.rest[end..];
+        }
+    }
+
+    let offset = match c.rest.as_bytes().first() {
+        Some(b'Z') => {
+            c.rest = &c.rest[1..];
+            Some(0)
+        }
+        Some(sign @ (b'+' | b'-')) => {
+            let sign = if *sign == b'-' { -1 } else { 1 };
+            c.rest = &c.rest[1..];
+            let hours = c.number(2)?;
+            expect(&mut c, ':')?;
+            Some(sign * (hours * 60 + c.number(2)?) as i128)
+        }
+        _ => None,
+    };
+    if !c.rest.is_empty() {
+        return None;
+    }
+
+    let days = days_from_civil(year as i128, i128::from(month), i128::from(day))?;
+
+    let seconds =
+        days * 86400 + i128::from(hours) * 3600 + i128::from(minutes) * 60 + i128::from(seconds)
+            - offset.unwrap_or(0) * 60;
+    Some((seconds, fraction, offset.is_some()))
+}
+
+/// Days since 0000-03-01, see "chrono-Compatible Low-Level Date Algorithms" by Howard Hinnant.
+/// `None` on overflow.
+fn days_from_civil(year: i128, month: i128, day: i128) -> Option<i128> {
+    let year = year - i128::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era.checked_mul(146097)?.checked_add(day_of_era)
+}
+
+/// Parse a `float` or `double`, which unlike Rust also allow `INF` but not `inf` or `infinity`.
+fn parse_float(value: &str) -> Option<f64> {
+    match value {
+        "INF" => return Some(f64::INFINITY),
+        "-INF" => return Some(f64::NEG_INFINITY),
+        "NaN" => return Some(f64::NAN),
+        _ => {}
+    }
+
+    let (mantissa, exponent) = match value.split_once(['e', 'E']) {
+        Some((m, e)) => (m, Some(e)),
+        None => (value, None),
+    };
+    let exponent_valid = exponent.is_none_or(|e| {
+        let e = e.strip_prefix(['+', '-']).unwrap_or(e);
+        !e.is_empty() && e.bytes().all(|b| b.is_ascii_digit())
+    });
+
+    match parse_decimal(mantissa).is_some() && exponent_valid {
+        true => value.parse().ok(),
+        false => None,
+    }
+}
+
+/// True if `value` is in the lexical space of `builtin`. `value` is already normalized.
+fn is_lexically_valid(builtin: &'static BuiltinType, value: &str) -> bool {
+    let is = |name: &str| BuiltinType::lookup(name).is_some_and(|t| builtin.is_derived_from(t));
+
+    if is("integer") {
+        return parse_decimal(value).is_some() && !value.contains('.');
+    }
+    if is("language") {
+        return is_language(value);
+    }
+    if is("NCName") {
+        return is_ncname(value);
+    }
+    if is("Name") {
+        return is_name(value);
+    }
+    if is("NMTOKEN") {
+        return !value.is_empty() && value.chars().all(is_name_char);
+    }
+
+    match builtin.primitive().map(|p| p.name) {
+        Some("decimal") => parse_decimal(value).is_some(),
+        Some("float" | "double") => parse_float(value).is_some(),
+        Some("boolean") => matches!(value, "true" | "false" | "1" | "0"),
+        Some("duration") => is_duration(value),
+        Some("dateTime") => value.split_once('T').is_some_and(|(date, time)| {
+            date_time(date, |c| c.date()) && date_time(time, |c| c.time())
+        }),
+        Some("time") => date_time(value, |c| c.time()),
+        Some("date") => date_time(value, |c| c.date()),
+        Some("gYearMonth") => date_time(value, |c| {
+            c.year().is_some() && c.eat('-') && c.month().is_some()
+        }),
+        Some("gYear") => date_time(value, |c| c.year().is_some()),
+        Some("gMonthDay") => date_time(value, |c| {
+            c.eat('-') && c.eat('-') && c.month().is_some_and(|m| c.eat('-') && c.day(2000, m))
+        }),
+        Some("gDay") => date_time(value, |c| {
+            c.eat('-') && c.eat('-') && c.eat('-') && c.day(2000, 1)
+        }),
+        Some("gMonth") => date_time(value, |c| c.eat('-') && c.eat('-') && c.month().is_some()),
+        Some("hexBinary") => {
+            value.len().is_multiple_of(2) && value.bytes().all(|b| b.is_ascii_hexdigit())
+        }
+        Some("base64Binary") => is_base64(value),
+        Some("QName" | "NOTATION") => match value.split_once(':') {
+            Some((prefix, local)) => is_ncname(prefix) && is_ncname(local),
+            None => is_ncname(value),
+        },
+        _ => true,
+    }
+}
+
+/// `NameStartChar` of XML 1.0, fifth edition.
+pub(crate) fn is_name_start_char(c: char) -> bool {
+    matches!(c,
+        ':' | 'A'..='Z' | '_' | 'a'..='z'
+        | '\u{C0}'..='\u{D6}' | '\u{D8}'..='\u{F6}' | '\u{F8}'..='\u{2FF}'
+        | '\u{370}'..='\u{37D}' | '\u{37F}'..='\u{1FFF}' | '\u{200C}'..='\u{200D}'
+        | '\u{2070}'..='\u{218F}' | '\u{2C00}'..='\u{2FEF}' | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}' | '\u{FDF0}'..='\u{FFFD}' | '\u{10000}'..='\u{EFFFF}')
+}
+
+/// `NameChar` of XML 1.0, fifth edition.
+pub(crate) fn is_name_char(c: char) -> bool {
+    is_name_start_char(c)
+        || matches!(c,
+            '-' | '.' | '0'..='9' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}')
+}
+
+fn is_name(value: &str) -> bool {
+    let mut chars = value.chars();
+    chars.next().is_some_and(is_name_start_char) && chars.all(is_name_char)
+}
+
+fn is_ncname(value: &str) -> bool {
+    is_name(value) && !value.contains(':')
+}
+
+/// `[a-zA-Z]{1,8}(-[a-zA-Z0-9]{1,8})*`
+fn is_language(value: &str) -> bool {
+    value.split('-').enumerate().all(|(i, part)| {
+        (1..=8).contains(&part.len())
+            && part.bytes().all(|b| match i {
+                0 => b.is_ascii_alphabetic(),
+                _ => b.is_ascii_alphanumeric(),
+            })
+    })
+}
+
+fn is_base64(value: &str) -> bool {
+    let characters: Vec<u8> = value.bytes().filter(|b| *b != b' ').collect();
+    let padding = characters.iter().rev().take_while(|b| **b == b'=').count();
+    let data = &characters[..characters.len() - padding];
+
+    characters.len().is_multiple_of(4)
+        && padding <= 2
+        && data
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || *b == b'+' || *b == b'/')
+}
+
+/// `-?PnYnMnDTnHnMnS` with at least one component, and at least one time component after `T`.
+fn is_duration(value: &str) -> bool {
+    let value = value.strip_prefix('-').unwrap_or(value);
+    let Some(value) = value.strip_prefix('P') else {
+        return false;
+    };
+    let (date, time) = match value.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+
+    let components = |s: &str, designators: &[char], seconds: bool| -> Option<usize> {
+        let mut rest = s;
+        let mut count = 0;
+        let mut allowed = designators;
+        while !rest.is_empty() {
+            let end = rest.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+            let (number, designator) = (&rest[..end], rest[end..].chars().next()?);
+            let position = allowed.iter().position(|d| *d == designator)?;
+            let fractional = seconds && designator == 'S';
+            let valid = match number.split_once('.') {
+                Some((integer, fraction)) => {
+                    fractional
+                        && !integer.is_empty()
+                        && !fraction.is_empty()
+                        && !fraction.contains('.')
+                }
+                None => !number.is_empty(),
+            };
+            if !valid {
+                return None;
+            }
+
+            allowed = &allowed[position + 1..];
+            rest = &rest[end + 1..];
+            count += 1;
+        }
+        Some(count)
+    };
+
+    let Some(date_count) = components(date, &['Y', 'M', 'D'], false) else {
+        return false;
+    };
+    match time {
+        Some(time) => components(time, &['H', 'M', 'S'], true).is_some_and(|n| n > 0),
+        None => date_count > 0,
+    }
+}
+
+/// Parse a date or time value with `parse`, followed by an optional timezone.
+fn date_time(value: &str, parse: impl Fn(&mut Cursor) -> bool) -> bool {
+    let mut cursor = Cursor { rest: value };
+    parse(&mut cursor) && cursor.timezone()
+}
+
+/// Reads the parts of date and time values.
+struct Cursor<'s> {
+    rest: &'s str,
+}
+
+impl Cursor<'_> {
+    fn eat(&mut self, c: char) -> bool {
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Exactly `n` digits.
+    fn number(&mut self, n: usize) -> Option<u32> {
+        let digits = self.rest.get(..n)?;
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        self.rest = &self.rest[n..];
+        digits.parse().ok()
+    }
+
+    /// At least four digits, without leading zeros if there are more than four, and not `0000`.
+    fn year(&mut self) -> Option<i64> {
+        let negative = self.eat('-');
+        let end = self
+            .rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest.len());
+        let digits = &self.rest[..end];
+        if digits.len() < 4 || (digits.len() > 4 && digits.starts_with('0')) {
+            return None;
+        }
+
+        let year: i64 = digits.parse().ok()?;
+        self.rest = &self.rest[end..];
+        match year {
+            0 => None,
+            _ if negative => Some(-year),
+            _ => Some(year),
+        }
+    }
+
+    fn month(&mut self) -> Option<u32> {
+        self.number(2).filter(|m| (1..=12).contains(m))
+    }
+
+    fn day(&mut self, year: i64, month: u32) -> bool {
+        let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let days = match month {
+            2 if leap => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        };
+
+        self.number(2).is_some_and(|d| (1..=days).contains(&d))
+    }
+
+    fn date(&mut self) -> bool {
+        let Some(year) = self.year() else {
+            return false;
+        };
+        self.eat('-')
+            && self
+                .month()
+                .is_some_and(|m| self.eat('-') && self.day(year, m))
+    }
+
+    /// `hh:mm:ss` with optional fraction digits. `24:00:00` is the end of the day.
+    fn time(&mut self) -> bool {
+        let (Some(hours), true, Some(minutes), true, Some(seconds)) = (
+            self.number(2),
+            self.eat(':'),
+            self.number(2),
+            self.eat(':'),
+            self.number(2),
+        ) else {
+            return false;
+        };
+
+        let mut fraction_zero = true;
+        if self.eat('.') {
+            let end = self
+                .rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(self.rest.len());
+            if end == 0 {
+                return false;
+            }
+            fraction_zero = self.rest[..end].bytes().all(|b| b == b'0');
+            self.rest = &self.rest[end..];
+        }
+
+        match hours {
+            24 => minutes == 0 && seconds == 0 && fraction_zero,
+            _ => hours < 24 && minutes < 60 && seconds < 60,
+        }
+    }
+
+    /// Nothing, `Z` or `±hh:mm` up to 14 hours, and then the end of the value.
+    fn timezone(&mut self) -> bool {
+        if self.eat('+') || self.eat('-') {
+            let (Some(hours), true, Some(minutes)) =
+                (self.number(2), self.eat(':'), self.number(2))
+            else {
+                return false;
+            };
+            if minutes >= 60 || hours > 14 || (hours == 14 && minutes > 0) {
+                return false;
+            }
+        } else {
+            self.eat('Z');
+        }
+
+        self.rest.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Schema;
+
+    fn set(xml: &str) -> SchemaSet {
+        SchemaSet::new(vec![Schema::parse(xml).unwrap()])
+    }
+
+    fn xs(name: &str) -> QName {
+        QName::new(Some(crate::document::XSD_NAMESPACE), name)
+    }
+
+    fn code(result: Result<SimpleValue, ValueError>) -> String {
+        match result {
+            Ok(_) => "valid".to_string(),
+            Err(e) => e.code,
+        }
+    }
+
+    #[test]
+    fn builtins() {
+        let set = SchemaSet::new(Vec::new());
+        let values = ValueValidator::new(&set);
+        let check = |name: &str, value: &str| code(values.validate(&xs(name), value));
+
+        let cases = [
+            ("boolean", " true ", "valid"),
+            ("boolean", "yes", "cvc-datatype-valid.1.2.1"),
+            ("decimal", "-1.50", "valid"),
+            ("decimal", ".", "cvc-datatype-valid.1.2.1"),
+            ("integer", "123456789012345678901234567890", "valid"),
+            ("integer", "1.0", "cvc-datatype-valid.1.2.1"),
+            ("byte", "-128", "valid"),
+            ("byte", "128", "cvc-maxInclusive-valid"),
+            (
+                "unsignedLong",
+                "18446744073709551616",
+                "cvc-maxInclusive-valid",
+            ),
+            ("positiveInteger", "0", "cvc-minInclusive-valid"),
+            ("double", "-INF", "valid"),
+            ("double", "1.5e-3", "valid"),
+            ("float", "infinity", "cvc-datatype-valid.1.2.1"),
+            ("duration", "P1Y2MT3.5S", "valid"),
+            ("duration", "P1YT", "cvc-datatype-valid.1.2.1"),
+            ("duration", "PT1.5M", "cvc-datatype-valid.1.2.1"),
+            ("date", "2024-02-29Z", "valid"),
+            ("date", "2023-02-29", "cvc-datatype-valid.1.2.1"),
+            ("dateTime", "2024-10-19T24:00:00+02:00", "valid"),
+            (
+                "dateTime",
+                "2024-10-19T12:60:00",
+                "cvc-datatype-valid.1.2.1",
+            ),
+            ("time", "13:20:00.5-05:00", "valid"),
+            ("gYearMonth", "-0044-03", "valid"),
+            ("gMonthDay", "--02-29", "valid"),
+            ("gDay", "---32", "cvc-datatype-valid.1.2.1"),
+            ("gMonth", "--12", "valid"),
+            ("hexBinary", "0fB7", "valid"),
+            ("hexBinary", "0fB", "cvc-datatype-valid.1.2.1"),
+            ("base64Binary", "aGVsbG8=", "valid"),
+            ("base64Binary", "aGVsbG8", "cvc-datatype-valid.1.2.1"),
+            ("language", "en-US", "valid"),
+            ("language", "en_US", "cvc-datatype-valid.1.2.1"),
+            ("NCName", "a:b", "cvc-datatype-valid.1.2.1"),
+            ("QName", "xs:string", "valid"),
+            ("ID", "1st", "cvc-datatype-valid.1.2.1"),
+            ("NMTOKEN", "1st", "valid"),
+            ("NMTOKENS", " a  b ", "valid"),
+            ("IDREFS", "", "cvc-minLength-valid"),
+            ("string", "anything", "valid"),
+        ];
+        for (name, value, expected) in cases {
+            assert_eq!(check(name, value), expected, "{} '{}'", name, value);
+        }
+
+        let v = values.validate(&xs("token"), "  a \t b ").unwrap();
+        assert_eq!(v.normalized, "a b");
+        assert_eq!(v.builtin.unwrap().name, "token");
+    }
+
+    #[test]
+    fn facets() {
+        let xml = r#"
+            <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+                <xs:simpleType name="percent">
+                    <xs:restriction base="xs:decimal">
+                        <xs:minExclusive value="0"/>
+                        <xs:maxInclusive value="100"/>
+                        <xs:totalDigits value="5"/>
+                        <xs:fractionDigits value="2"/>
+                    </xs:restriction>
+                </xs:simpleType>
+                <xs:simpleType name="code">
+                    <xs:restriction base="xs:string">
+                        <xs:length value="3"/>
+                        <xs:pattern value="[A-Z]+"/>
+                    </xs:restriction>
+                </xs:simpleType>
+                <xs:simpleType name="identifier">
+                    <xs:restriction base="xs:string">
+                        <xs:pattern value="[\i-[:]][\c-[:]]*"/>
+                    </xs:restriction>
+                </xs:simpleType>
+                <xs:simpleType name="yes-no">
+                    <xs:restriction base="xs:token">
+                        <xs:enumeration value="yes"/>
+                        <xs:enumeration value="no"/>
+                    </xs:restriction>
+                </xs:simpleType>
+                <xs:simpleType name="level">
+                    <xs:restriction base="xs:float">
+                        <xs:enumeration value="1.0"/>
+                        <xs:enumeration value="2"/>
+                    </xs:restriction>
+                </xs:simpleType>
+                <xs:simpleType name="blob">
+                    <xs:restriction base="xs:hexBinary">
+                        <xs:maxLength value="2"/>
+                    </xs:restriction>
+                </xs:simpleType>
+                <xs:simpleType name="label">
+                    <xs:restriction base="xs:string">
+                        <xs:whiteSpace value="collapse"/>
+                        <xs:minLength value="2"/>
+                    </xs:restriction>
+                </xs:simpleType>
+                <xs:simpleType name="share">
+                    <xs:restriction base="xs:decimal">
+                        <xs:minInclusive value="0.5"/>
+                        <xs:maxInclusive value="99999999999"/>
+                    </xs:restriction>
+                </xs:simpleType>
+                <xs:simpleType name="season">
+                    <xs:restriction base="xs:date">
+                        <xs:minInclusive value="2024-01-01"/>
+                        <xs:maxExclusive value="2025-01-01"/>
+                    </xs:restriction>
+                </xs:simpleType>
+                <xs:simpleType name="session">
+                    <xs:restriction base="xs:dateTime">
+                        <xs:minInclusive value="2024-10-19T09:00:00Z"/>
+                    </xs:restriction>
+                </xs:simpleType>
+                <xs:simpleType name="delay">
+                    <xs:restriction base="xs:duration">
+                        <xs:maxInclusive value="P1D"/>
+                        <xs:minExclusive value="-PT1.5S"/>
+                    </xs:restriction>
+                </xs:simpleType>
+                <xs:simpleType name="term">
+                    <xs:restriction base="xs:duration">
+                        <xs:minInclusive value="P1M"/>
+                    </xs:restriction>
+                </xs:simpleType>
+                <xs:simpleType name="ratio">
+                    <xs:restriction base="xs:double">
+                        <xs:maxInclusive value="1.5"/>
+                    </xs:restriction>
+                </xs:simpleType>
+            </xs:schema>
+        "#;
+        let set = set(xml);
+        let values = ValueValidator::new(&set);
+        let check = |name: &str, value: &str| code(values.validate(&QName::new(None, name), value));
+
+        let cases = [
+            ("percent", "99.5", "valid"),
+            ("percent", "0", "cvc-minExclusive-valid"),
+            ("percent", "100.01", "cvc-maxInclusive-valid"),
+            ("percent", "1.125", "cvc-fractionDigits-valid"),
+            ("percent", "1.10", "valid"),
+            ("code", "ABC", "valid"),
+            ("code", "ABCD", "cvc-length-valid"),
+            ("code", "AbC", "cvc-pattern-valid"),
+            ("identifier", "note-1", "valid"),
+            ("identifier", "xs:note", "cvc-pattern-valid"),
+            ("yes-no", " yes ", "valid"),
+            ("yes-no", "maybe", "cvc-enumeration-valid"),
+            ("level", "1", "valid"),
+            ("level", "2.0E0", "valid"),
+            ("level", "3", "cvc-enumeration-valid"),
+            ("blob", "0A0B", "valid"),
+            ("blob", "0A0B0C", "cvc-maxLength-valid"),
+            ("label", "  a  ", "cvc-minLength-valid"),
+            ("label", " a b ", "valid"),
+            ("share", "0.25", "cvc-minInclusive-valid"),
+            ("share", "12345678901", "valid"),
+            ("share", "100000000000", "cvc-maxInclusive-valid"),
+            ("season", "2024-06-30", "valid"),
+            ("season", "2023-12-31", "cvc-minInclusive-valid"),
+            ("season", "2025-01-01", "cvc-maxExclusive-valid"),
+            ("session", "2024-10-19T09:00:00.5Z", "valid"),
+            (
+                "session",
+                "2024-10-19T10:30:00+02:00",
+                "cvc-minInclusive-valid",
+            ),
+            // Without timezone the value may be anywhere from 14 hours before to 14 hours after.
+            ("session", "2024-10-19T12:00:00", "cvc-minInclusive-valid"),
+            ("session", "2024-10-20T00:00:00", "valid"),
+            ("session", "2024-10-18T18:00:00", "cvc-minInclusive-valid"),
+            ("delay", "PT23H59M59.999S", "valid"),
+            ("delay", "PT24H", "valid"),
+            ("delay", "P2D", "cvc-maxInclusive-valid"),
+            ("delay", "P1M", "cvc-maxInclusive-valid"),
+            ("delay", "-PT1S", "valid"),
+            ("delay", "-PT1.5S", "cvc-minExclusive-valid"),
+            ("term", "P31D", "cvc-minInclusive-valid"),
+            ("term", "P32D", "valid"),
+            ("term", "PT744H", "cvc-minInclusive-valid"),
+            ("term", "P1Y", "valid"),
+            ("ratio", "1.5", "valid"),
+            ("ratio", "INF", "cvc-maxInclusive-valid"),
+            ("ratio", "NaN", "cvc-maxInclusive-valid"),
+        ];
+        for (name, value, expected) in cases {
+            assert_eq!(check(name, value), expected, "{} '{}'", name, value);
+        }
+    }
+
+    #[test]
+    fn lists_and_unions() {
+        let xml = r#"
+            <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+                <xs:simpleType name="small">
+                    <xs:restriction base="xs:int">
+                        <xs:maxInclusive value="9"/>
+                    </xs:restriction>
+                </xs:simpleType>
+                <xs:simpleType name="digits">
+                    <xs:list itemType="small"/>
+                </xs:simpleType>
+                <xs:simpleType name="pair">
+                    <xs:restriction base="digits">
+                        <xs:length value="2"/>
+                    </xs:restriction>
+                </xs:simpleType>
+                <xs:simpleType name="size">
+                    <xs:union memberTypes="small">
+                        <xs:simpleType>
+                            <xs:restriction base="xs:token">
+                                <xs:enumeration value="auto"/>
+                            </xs:restriction>
+                        </xs:simpleType>
+                    </xs:union>
+                </xs:simpleType>
+            </xs:schema>
+        "#;
+        let set = set(xml);
+        let values = ValueValidator::new(&set);
+        let check = |name: &str, value: &str| code(values.validate(&QName::new(None, name), value));
+
+        let v = values
+            .validate(&QName::new(None, "digits"), " 1  2\n3 ")
+            .unwrap();
+        assert_eq!(v.normalized, "1 2 3");
+        assert!(v.list);
+        assert_eq!(v.builtin.unwrap().name, "int");
+
+        assert_eq!(check("digits", "1 10"), "cvc-maxInclusive-valid");
+        assert_eq!(check("digits", "1 x"), "cvc-datatype-valid.1.2.1");
+        assert_eq!(check("pair", "1 2"), "valid");
+        assert_eq!(check("pair", "1 2 3"), "cvc-length-valid");
+
+        let v = values.validate(&QName::new(None, "size"), "7").unwrap();
+        assert_eq!(v.member_type, Some(QName::new(None, "small")));
+        let v = values.validate(&QName::new(None, "size"), "auto").unwrap();
+        assert_eq!(v.member_type, None);
+        assert_eq!(v.builtin.unwrap().name, "token");
+        assert_eq!(check("size", "12"), "cvc-datatype-valid.1.2.3");
+
+        assert_eq!(check("unknown", "anything"), "valid");
+    }
+}
@@ -0,0 +1,175 @@
+use xmltree::{Element, XMLNode};
+
+use crate::{
+    document::{expanded_name, resolve_qname},
+    parse_options::ParseOptions,
+};
+
+/// Namespace of the XSD 1.1 conditional inclusion attributes.
+pub const VC_NAMESPACE: &str = "http://www.w3.org/2007/XMLSchema-versioning";
+
+const MIN_VERSION: &str = "minVersion";
+const MAX_VERSION: &str = "maxVersion";
+const TYPE_AVAILABLE: &str = "typeAvailable";
+const TYPE_UNAVAILABLE: &str = "typeUnavailable";
+const FACET_AVAILABLE: &str = "facetAvailable";
+const FACET_UNAVAILABLE: &str = "facetUnavailable";
+
+/// Remove every element below `element` that is excluded by its `vc:*` attributes, as
+/// described in XSD 1.1 Part 1, section 4.2.1 "Conditional inclusion".
+///
+/// The `vc:*` attributes of the retained elements are removed as well, so that the rest of the
+/// parser never sees them.
+pub fn apply(element: &mut Element, options: &ParseOptions) {
+    strip_attributes(element);
+
+    element.children.retain_mut(|child| match child {
+        XMLNode::Element(child) => {
+            if !is_included(child, options) {
+                return false;
+            }
+
+            apply(child, options);
+            true
+        }
+        _ => true,
+    });
+}
+
+/// Evaluate the `vc:*` attributes of a single element.
+pub fn is_included(element: &Element, options: &ParseOptions) -> bool {
+    if let Some(min) = vc_attribute(element, MIN_VERSION).and_then(parse_decimal) {
+        if min > options.processor_version {
+            return false;
+        }
+    }
+
+    if let Some(max) = vc_attribute(element, MAX_VERSION).and_then(parse_decimal) {
+        if max <= options.processor_version {
+            return false;
+        }
+    }
+
+    if let Some(types) = vc_attribute(element, TYPE_AVAILABLE) {
+        if !all_available(element, types, &options.available_types) {
+            return false;
+        }
+    }
+
+    if let Some(types) = vc_attribute(element, TYPE_UNAVAILABLE) {
+        if all_available(element, types, &options.available_types) {
+            return false;
+        }
+    }
+
+    if let Some(facets) = vc_attribute(element, FACET_AVAILABLE) {
+        if !all_available(element, facets, &options.available_facets) {
+            return false;
+        }
+    }
+
+    if let Some(facets) = vc_attribute(element, FACET_UNAVAILABLE) {
+        if all_available(element, facets, &options.available_facets) {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn vc_attribute<'a>(element: &'a Element, name: &str) -> Option<&'a str> {
+    element
+        .attributes
+        .get(&expanded_name(VC_NAMESPACE, name))
+        .map(|s| s.as_str())
+}
+
+fn strip_attributes(element: &mut Element) {
+    for name in [
+        MIN_VERSION,
+        MAX_VERSION,
+        TYPE_AVAILABLE,
+        TYPE_UNAVAILABLE,
+        FACET_AVAILABLE,
+        FACET_UNAVAILABLE,
+    ] {
+        element
+            .attributes
+            .remove(&expanded_name(VC_NAMESPACE, name));
+    }
+}
+
+fn parse_decimal(value: &str) -> Option<f64> {
+    value.trim().parse::<f64>().ok()
+}
+
+/// True, if every qualified name in the whitespace separated `names` is contained in
+/// `available`. Names with an undeclared prefix are never available.
+fn all_available(element: &Element, names: &str, available: &[String]) -> bool {
+    names
+        .split_whitespace()
+        .all(|name| match resolve_qname(element, name) {
+            Some(name) => available.contains(&name),
+            None => false,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_options::ParseOptions, schema::Schema};
+
+    const XML: &str = r#"
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:vc="http://www.w3.org/2007/XMLSchema-versioning">
+    <xs:simpleType name="a" vc:minVersion="1.1">
+        <xs:restriction base="xs:dateTimeStamp"/>
+    </xs:simpleType>
+    <xs:simpleType name="a" vc:maxVersion="1.1">
+        <xs:restriction base="xs:dateTime"/>
+    </xs:simpleType>
+    <xs:simpleType name="b" vc:typeAvailable="xs:dateTimeStamp">
+        <xs:restriction base="xs:dateTimeStamp"/>
+    </xs:simpleType>
+    <xs:simpleType name="b" vc:typeUnavailable="xs:dateTimeStamp">
+        <xs:restriction base="xs:dateTime"/>
+    </xs:simpleType>
+    <xs:simpleType name="c" vc:facetAvailable="xs:pattern">
+        <xs:restriction base="xs:string"/>
+    </xs:simpleType>
+</xs:schema>
+"#;
+
+    #[test]
+    fn version_1_0() {
+        let item = Schema::parse(XML).unwrap();
+
+        assert_eq!(item.simple_types.len(), 3);
+        for t in item.simple_types.iter().take(2) {
+            assert_eq!(
+                t.restriction.as_ref().unwrap().base,
+                "xs:dateTime".to_string()
+            );
+        }
+        assert_eq!(item.simple_types[2].name, "c".to_string());
+    }
+
+    #[test]
+    fn version_1_1() {
+        let mut options = ParseOptions {
+            processor_version: 1.1,
+            ..Default::default()
+        };
+        options
+            .available_types
+            .push("{http://www.w3.org/2001/XMLSchema}dateTimeStamp".to_string());
+
+        let item = Schema::parse_with_options(XML, &options).unwrap();
+
+        assert_eq!(item.simple_types.len(), 3);
+        for t in item.simple_types.iter().take(2) {
+            assert_eq!(
+                t.restriction.as_ref().unwrap().base,
+                "xs:dateTimeStamp".to_string()
+            );
+        }
+    }
+}
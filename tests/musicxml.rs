@@ -27,4 +27,155 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn musicxml_diagnostics() {
+        let path = get_file_path("musicxml.xsd");
+        let options = xsdl_parser::prelude::ParseOptions::default();
+        let (schema, diagnostics) =
+            xsdl_parser::prelude::Schema::load_with_diagnostics(&path, &options).unwrap();
+
+        assert!(!schema.complex_types.is_empty());
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn musicxml_spans() {
+        let path = get_file_path("musicxml.xsd");
+        let schema = xsdl_parser::prelude::Schema::load(&path).unwrap();
+
+        let span = schema.complex_types[0].span.clone().unwrap();
+        assert_eq!(span.document, Some(path.to_string_lossy().to_string()));
+        assert!(span.start.line > 1);
+        assert!(span.end.line > span.start.line);
+    }
+
+    #[test]
+    fn musicxml_schema_set() {
+        let path = get_file_path("musicxml.xsd");
+        let set = xsdl_parser::prelude::SchemaSet::load(&path).unwrap();
+
+        // The imported xml.xsd and xlink.xsd are replaced by the copies bundled with the crate,
+        // so nothing needs to be downloaded.
+        assert_eq!(set.schemas.len(), 3);
+        let codes: Vec<&str> = set.diagnostics.iter().map(|d| d.code.as_str()).collect();
+        assert_eq!(codes, vec!["schema-replaced", "schema-replaced"]);
+
+        // Every simple type defined by restriction can be traced back to a built-in type.
+        let musicxml = &set.schemas[0];
+        for simple_type in musicxml.simple_types.iter() {
+            let name = xsdl_parser::prelude::QName::new(None, &simple_type.name);
+            if simple_type.restriction.is_some() {
+                assert!(set.builtin_base(&name).is_some(), "{}", simple_type.name);
+            }
+        }
+
+        let midi = xsdl_parser::prelude::QName::new(None, "midi-16384");
+        assert_eq!(set.primitive_type(&midi).unwrap().name, "decimal");
+    }
+
+    #[test]
+    fn musicxml_derivation() {
+        use xsdl_parser::prelude::{DerivationMethod, DerivationSet, QName};
+
+        let path = get_file_path("musicxml.xsd");
+        let set = xsdl_parser::prelude::SchemaSet::load(&path).unwrap();
+        let graph = set.derivation_graph();
+        assert!(graph.diagnostics.is_empty(), "{:?}", graph.diagnostics);
+
+        let empty_placement = QName::new(None, "empty-placement");
+        let descendants = graph.descendants(&empty_placement);
+        assert!(descendants.contains(&&QName::new(None, "heel-toe")));
+        assert!(descendants.contains(&&QName::new(None, "strong-accent")));
+
+        let heel_toe = QName::new(None, "heel-toe");
+        assert_eq!(
+            graph.derivation_method(&heel_toe),
+            Some(DerivationMethod::Extension)
+        );
+        assert!(graph.is_derived_from(&heel_toe, &empty_placement, DerivationSet::default()));
+
+        let midi = QName::new(None, "midi-16384");
+        assert_eq!(graph.primitive_type(&midi).unwrap().name, "decimal");
+    }
+
+    #[test]
+    fn musicxml_content_models() {
+        use xsdl_parser::prelude::{QName, Term};
+
+        let path = get_file_path("musicxml.xsd");
+        let set = xsdl_parser::prelude::SchemaSet::load(&path).unwrap();
+
+        for complex_type in set.schemas[0].complex_types.iter() {
+            let name = QName::new(None, &complex_type.name);
+            let (_, diagnostics) = set.content_model(&name).unwrap();
+            assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        }
+
+        // The full-note group and the editorial groups are expanded in place.
+        let (model, _) = set.content_model(&QName::new(None, "note")).unwrap();
+        let particle = model.particle.unwrap();
+        let Term::Sequence(particles) = &particle.term else {
+            panic!("Expected sequence, got {:?}", particle.term);
+        };
+        assert!(particles.iter().any(|p| matches!(
+            &p.term,
+            Term::Element(e) if e.name == QName::new(None, "footnote")
+        )));
+    }
+
+    #[test]
+    fn musicxml_constraints() {
+        let path = get_file_path("musicxml.xsd");
+        let set = xsdl_parser::prelude::SchemaSet::load(&path).unwrap();
+
+        // Only the replaced imports of xml.xsd and xlink.xsd are reported.
+        let diagnostics = set.check();
+        assert!(
+            diagnostics.iter().all(|d| d.code == "schema-replaced"),
+            "{:?}",
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn musicxml_validate() {
+        let path = get_file_path("musicxml.xsd");
+        let set = xsdl_parser::prelude::SchemaSet::load(&path).unwrap();
+
+        let instance = std::fs::read_to_string(get_file_path("hello-world.musicxml")).unwrap();
+        let diagnostics = set.validate(&instance).unwrap();
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+        // A note needs a duration, and the step is an enumeration.
+        let broken = instance
+            .replace("<duration>4</duration>", "")
+            .replace("<step>C</step>", "<step>H</step>");
+        let codes: Vec<String> = set
+            .validate(&broken)
+            .unwrap()
+            .into_iter()
+            .map(|d| format!("{} {}", d.code, d.path))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                "cvc-type.3.1.3 /score-partwise/part[1]/measure[1]/note[1]/pitch[1]/step[1]",
+                "cvc-complex-type.2.4.a /score-partwise/part[1]/measure[1]/note[1]/type[1]",
+            ]
+        );
+    }
+
+    #[test]
+    fn musicxml_catalog() {
+        let resolver =
+            xsdl_parser::prelude::CatalogResolver::load(&get_file_path("catalog.xml")).unwrap();
+        let path = get_file_path("musicxml.xsd");
+        let set =
+            xsdl_parser::prelude::SchemaSet::load_with_resolver(&path.to_string_lossy(), &resolver)
+                .unwrap();
+
+        assert_eq!(set.schemas.len(), 3);
+        assert!(set.diagnostics.is_empty(), "{:?}", set.diagnostics);
+    }
 }